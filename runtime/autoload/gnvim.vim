@@ -21,3 +21,570 @@ function! gnvim#enable_ext_popupmenu(enable)
                 \ 'EnableExtPopupmenu',
                 \ a:enable)
 endfunction
+
+function! gnvim#set_inactive_dim(dim)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetInactiveDim',
+                \ a:dim * 1.0)
+endfunction
+
+function! gnvim#tabline_set_position(pos)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'TablineSetPosition',
+                \ a:pos)
+endfunction
+
+function! gnvim#set_virtual_text_style(style)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetVirtualTextStyle',
+                \ a:style)
+endfunction
+
+function! gnvim#get_font()
+    return rpcrequest(g:gnvim_channel_id, 'Gnvim', 'GetFont')
+endfunction
+
+function! gnvim#get_cell_rect(grid, row, col)
+    return rpcrequest(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'GetCellRect',
+                \ a:grid,
+                \ a:row,
+                \ a:col)
+endfunction
+
+function! gnvim#font_zoom(delta)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'FontZoom',
+                \ a:delta * 1.0)
+endfunction
+
+function! gnvim#font_zoom_reset()
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'FontZoomReset')
+endfunction
+
+function! gnvim#set_invert_colors(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetInvertColors',
+                \ a:enable == 1)
+endfunction
+
+function! gnvim#enable_mode_indicator(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetModeIndicator',
+                \ a:enable == 1)
+endfunction
+
+function! gnvim#wildmenu_set_layout(layout)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'WildmenuSetLayout',
+                \ a:layout)
+endfunction
+
+function! gnvim#set_unfocused_cursor(cursor)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetUnfocusedCursor',
+                \ a:cursor)
+endfunction
+
+function! gnvim#set_underline_style(scale, offset)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetUnderlineStyle',
+                \ a:scale * 1.0,
+                \ a:offset * 1.0)
+endfunction
+
+function! gnvim#set_cursor_visible(visible)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetCursorVisible',
+                \ a:visible == 1)
+endfunction
+
+function! gnvim#set_remember_geometry(remember)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetRememberGeometry',
+                \ a:remember == 1)
+endfunction
+
+function! gnvim#set_box_drawing(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetBoxDrawing',
+                \ a:enable == 1)
+endfunction
+
+function! gnvim#set_letter_spacing(spacing)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetLetterSpacing',
+                \ a:spacing * 1.0)
+endfunction
+
+function! gnvim#flash_region(grid, ranges, color, duration)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'FlashRegion',
+                \ a:grid,
+                \ a:ranges,
+                \ a:color,
+                \ a:duration)
+endfunction
+
+function! gnvim#set_min_contrast(ratio)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetMinContrast',
+                \ a:ratio * 1.0)
+endfunction
+
+function! gnvim#cmdline_block_set_max_height(rows)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'CmdlineBlockMaxHeight',
+                \ a:rows)
+endfunction
+
+function! gnvim#paste()
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'Paste')
+endfunction
+
+function! gnvim#set_pixel_snap(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetPixelSnap',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_cursor_over_text(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetCursorOverText',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_render_whitespace(ws)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetRenderWhitespace',
+                \ a:ws)
+endfunction
+
+function! gnvim#set_extra_cursors(cursors)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetExtraCursors',
+                \ a:cursors)
+endfunction
+
+function! gnvim#set_resize_debounce(ms)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetResizeDebounce',
+                \ a:ms)
+endfunction
+
+function! gnvim#set_scroll_cache(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetScrollCache',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_respect_mouse_option(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetRespectMouseOption',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_gui_selection(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetGuiSelection',
+                \ a:enable)
+endfunction
+
+function! gnvim#cmdline_set_position(position)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'CmdlineSetPosition',
+                \ a:position * 1.0)
+endfunction
+
+function! gnvim#set_busy_spinner(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetBusySpinner',
+                \ a:enable)
+endfunction
+
+function! gnvim#tabline_set_mode(mode)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'TablineSetMode',
+                \ a:mode)
+endfunction
+
+function! gnvim#place_image(grid, row, col, width, height, path)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'PlaceImage',
+                \ a:grid,
+                \ a:row,
+                \ a:col,
+                \ a:width,
+                \ a:height,
+                \ fnamemodify(a:path, ':p'))
+endfunction
+
+function! gnvim#clear_images()
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'ClearImages')
+endfunction
+
+function! gnvim#set_input_method(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetInputMethod',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_natural_scroll(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetNaturalScroll',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_dim_inactive_lines(above, below, amount)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetDimInactiveLines',
+                \ a:above,
+                \ a:below,
+                \ a:amount * 1.0)
+endfunction
+
+function! gnvim#set_min_size(cols, rows)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetMinSize',
+                \ a:cols,
+                \ a:rows)
+endfunction
+
+function! gnvim#set_grid_font(grid, font)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetGridFont',
+                \ a:grid,
+                \ a:font)
+endfunction
+
+function! gnvim#set_winbar(grid, chunks)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetWinbar',
+                \ a:grid,
+                \ map(copy(a:chunks),
+                \     {_, c -> [c[0], nvim_get_hl_by_name(get(c, 1, 'Normal'), 1)]}))
+endfunction
+
+function! gnvim#set_zoom_animation(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetZoomAnimation',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_clipboard_sync(enable)
+    augroup GnvimClipboardSync
+        autocmd!
+        if a:enable
+            autocmd TextYankPost * call s:clipboard_sync(v:event)
+        endif
+    augroup END
+
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetClipboardSync',
+                \ a:enable)
+endfunction
+
+" Sends text yanked to the + register to gnvim's clipboard.
+function! s:clipboard_sync(event)
+    if a:event.regname !=# '+'
+                \ && !(a:event.regname ==# '' && &clipboard =~# 'unnamedplus')
+        return
+    endif
+
+    let l:text = join(a:event.regcontents, "\n")
+    if a:event.regtype ==# 'V'
+        let l:text .= "\n"
+    endif
+
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'ClipboardSetText', l:text)
+endfunction
+
+function! gnvim#set_line_scale(grid, row, scale)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetLineScale',
+                \ a:grid,
+                \ a:row,
+                \ a:scale)
+endfunction
+
+function! gnvim#set_pending_keys(keys)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetPendingKeys',
+                \ a:keys)
+endfunction
+
+function! gnvim#cmdline_set_border(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'CmdlineSetBorder',
+                \ a:enable)
+endfunction
+
+function! gnvim#tabline_set_font(font)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'TablineSetFont',
+                \ a:font)
+endfunction
+
+function! gnvim#set_typewriter_mode(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetTypewriterMode',
+                \ a:enable)
+endfunction
+
+function! gnvim#cmdline_set_cursor_blink(on, off)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'CmdlineSetCursorBlink',
+                \ a:on,
+                \ a:off)
+endfunction
+
+function! gnvim#get_grid_text(grid, with_hl)
+    return rpcrequest(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'GetGridText',
+                \ a:grid,
+                \ a:with_hl)
+endfunction
+
+function! gnvim#set_cell_width_scale(scale)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetCellWidthScale',
+                \ a:scale * 1.0)
+endfunction
+
+function! gnvim#set_active_border(color, width)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetActiveBorder',
+                \ a:color,
+                \ a:width * 1.0)
+endfunction
+
+function! gnvim#set_letterbox_color(color)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetLetterboxColor',
+                \ a:color)
+endfunction
+
+function! gnvim#tabline_set_label_format(format)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'TablineSetLabelFormat',
+                \ a:format)
+endfunction
+
+function! gnvim#set_scroll_target(target)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetScrollTarget',
+                \ a:target)
+endfunction
+
+function! gnvim#set_animation_fps(fps)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetAnimationFps',
+                \ a:fps)
+endfunction
+
+function! gnvim#set_forwarded_mouse_buttons(buttons)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetForwardedMouseButtons',
+                \ a:buttons)
+endfunction
+
+function! gnvim#set_context_menu(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetContextMenu',
+                \ a:enable)
+endfunction
+
+function! gnvim#context_menu_set_items(items)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'ContextMenuSetItems',
+                \ a:items)
+endfunction
+
+function! gnvim#set_cursor_outline(color, width)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetCursorOutline',
+                \ a:color,
+                \ a:width * 1.0)
+endfunction
+
+function! gnvim#set_grid_lines(enable, color)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetGridLines',
+                \ a:enable,
+                \ a:color)
+endfunction
+
+function! gnvim#set_text_shadow(color, dx, dy, blur)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetTextShadow',
+                \ a:color,
+                \ a:dx * 1.0,
+                \ a:dy * 1.0,
+                \ a:blur * 1.0)
+endfunction
+
+function! gnvim#reset_settings()
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'ResetGnvimSettings')
+endfunction
+
+function! gnvim#set_defer_colors_redraw(enable)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetDeferColorsRedraw',
+                \ a:enable)
+endfunction
+
+function! gnvim#set_cursor_blink_for_shape(shape, on, off, wait)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetCursorBlinkForShape',
+                \ a:shape,
+                \ a:on,
+                \ a:off,
+                \ a:wait)
+endfunction
+
+function! gnvim#set_scroll_indicator(line, total)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetScrollIndicator',
+                \ a:line,
+                \ a:total)
+endfunction
+
+function! gnvim#set_whitespace_glyphs(space, tab, nbsp)
+    return rpcnotify(
+                \ g:gnvim_channel_id,
+                \ 'Gnvim',
+                \ 'SetWhitespaceGlyphs',
+                \ a:space,
+                \ a:tab,
+                \ a:nbsp)
+endfunction
@@ -37,3 +37,11 @@ endfunction
 function! gnvim#cursor_tooltip#load_style(path)
     call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'CursorTooltipLoadStyle', a:path)
 endfunction
+
+function! gnvim#cursor_tooltip#reload_style()
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'CursorTooltipReloadStyle')
+endfunction
+
+function! gnvim#cursor_tooltip#set_code_font(font)
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'CursorTooltipSetCodeFont', a:font)
+endfunction
@@ -15,3 +15,32 @@ function! gnvim#popupmenu#show_menu_on_all_items(bool)
     call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'PopupmenuShowMenuOnAllItems', a:bool)
     return ''
 endfunction
+
+function! gnvim#popupmenu#scroll_info(delta)
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'PopupmenuInfoScroll', a:delta)
+    return ''
+endfunction
+
+function! gnvim#popupmenu#set_anchor(row, col, grid)
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'PopupmenuSetAnchor', a:row, a:col, a:grid)
+endfunction
+
+function! gnvim#popupmenu#clear_anchor()
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'PopupmenuClearAnchor')
+endfunction
+
+function! gnvim#popupmenu#set_max_width(width)
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'PopupmenuSetMaxWidthPx', a:width)
+endfunction
+
+function! gnvim#popupmenu#set_animation(enable)
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'SetPopupmenuAnimation', a:enable)
+endfunction
+
+function! gnvim#popupmenu#set_selection_pulse(enable)
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'PopupmenuSelectionPulse', a:enable)
+endfunction
+
+function! gnvim#popupmenu#set_style(border_radius, shadow)
+    call rpcnotify(g:gnvim_channel_id, 'Gnvim', 'PopupmenuSetStyle', a:border_radius, a:shadow)
+endfunction
@@ -179,6 +179,9 @@ async fn build(app: &gtk::Application, opts: &Options) -> Result<(), Error> {
     ui_opts.set_rgb(true);
     ui_opts.set_linegrid_external(true);
     ui_opts.set_multigrid_external(true);
+    // Needed so we get the highlight group names in hl_attr_define, which is
+    // used to detect virtual text highlights.
+    ui_opts.set_hlstate_external(true);
     ui_opts.set_popupmenu_external(!opts.disable_ext_popupmenu);
     ui_opts.set_tabline_external(!opts.disable_ext_tabline);
     ui_opts.set_cmdline_external(!opts.disable_ext_cmdline);
@@ -1,6 +1,7 @@
 use log::{debug, error};
 
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fmt;
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::Arc;
@@ -12,7 +13,7 @@ use rmpv::Value;
 
 use crate::nvim_gio::GioWriter;
 use crate::thread_guard::ThreadGuard;
-use crate::ui::color::{Color, Highlight};
+use crate::ui::color::{Color, Highlight, RenderWhitespace, VirtualTextStyle};
 
 #[cfg(test)]
 mod tests;
@@ -73,6 +74,27 @@ macro_rules! try_u64 {
     };
 }
 
+macro_rules! try_i64 {
+    ($val:expr, $msg:expr) => {
+        $val.as_i64()
+            .ok_or(format!("Value is not an i64: {}", $msg))?
+    };
+}
+
+macro_rules! try_array {
+    ($val:expr, $msg:expr) => {
+        $val.as_array()
+            .ok_or(format!("Value is not an array: {}", $msg))?
+    };
+}
+
+macro_rules! try_f64 {
+    ($val:expr, $msg:expr) => {
+        $val.as_f64()
+            .ok_or(format!("Value is not an f64: {}", $msg))?
+    };
+}
+
 impl Highlight {
     fn from_map_val(map: &[(Value, Value)]) -> Self {
         let mut hl = Highlight::default();
@@ -120,6 +142,12 @@ impl Highlight {
             "undercurl" => {
                 self.undercurl = unwrap_bool!(val);
             }
+            "strikethrough" => {
+                self.strikethrough = unwrap_bool!(val);
+            }
+            "nocombine" => {
+                self.nocombine = unwrap_bool!(val);
+            }
             "blend" => {
                 self.blend = unwrap_f64!(val) / 100.0;
             }
@@ -142,7 +170,7 @@ pub enum Notify {
     GnvimEvent(Result<GnvimEvent, String>),
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum CursorShape {
     Block,
     Horizontal,
@@ -151,13 +179,15 @@ pub enum CursorShape {
 
 impl CursorShape {
     fn from_string(name: &str) -> Self {
+        CursorShape::parse(name).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn parse(name: &str) -> Result<Self, String> {
         match String::from(name).to_lowercase().as_str() {
-            "block" => CursorShape::Block,
-            "horizontal" => CursorShape::Horizontal,
-            "vertical" => CursorShape::Vertical,
-            _ => {
-                panic!("Unknown cursor shape: {}", name);
-            }
+            "block" => Ok(CursorShape::Block),
+            "horizontal" => Ok(CursorShape::Horizontal),
+            "vertical" => Ok(CursorShape::Vertical),
+            _ => Err(format!("Unknown cursor shape: {}", name)),
         }
     }
 }
@@ -175,6 +205,10 @@ pub struct ModeInfo {
     pub cursor_shape: CursorShape,
     /// The cursor's width (in percentages, from 0..1).
     pub cell_percentage: f64,
+    /// Name of the mode.
+    pub name: String,
+    /// Highlight id for the cursor.
+    pub attr_id: u64,
     // TODO(ville): Implement the rest.
 }
 
@@ -196,6 +230,12 @@ impl ModeInfo {
                 }
                 self.cell_percentage = val as f64 / 100.0;
             }
+            "name" => {
+                self.name = unwrap_str!(val).to_string();
+            }
+            "attr_id" => {
+                self.attr_id = unwrap_u64!(val);
+            }
             _ => {}
         }
     }
@@ -572,12 +612,32 @@ impl From<Value> for HlAttrDefine {
         let id = unwrap_u64!(args[0]);
         let map = unwrap_map!(args[1]);
 
-        let hl = Highlight::from_map_val(map);
+        let mut hl = Highlight::from_map_val(map);
+
+        // With ext_hlstate, the fourth argument tells us which highlight
+        // groups this highlight is made of.
+        if let Some(info) = args.get(3).and_then(|info| info.as_array()) {
+            hl.virtual_text = info.iter().any(|item| {
+                item.as_map()
+                    .and_then(|map| {
+                        map.iter().find(|(k, _)| k.as_str() == Some("hi_name"))
+                    })
+                    .and_then(|(_, v)| v.as_str())
+                    .map(is_virtual_text_group)
+                    .unwrap_or(false)
+            });
+        }
 
         HlAttrDefine { id, hl }
     }
 }
 
+/// Highlight groups that contain `VirtualText` or `InlayHint` in their
+/// name are treated as virtual text.
+fn is_virtual_text_group(name: &str) -> bool {
+    name.contains("VirtualText") || name.contains("InlayHint")
+}
+
 #[derive(Debug, PartialEq)]
 pub struct HlGroupSet {
     pub name: String,
@@ -702,6 +762,9 @@ impl From<Value> for CmdlineBlockAppend {
 pub struct TablineUpdate {
     pub current: Value,
     pub tabs: Vec<(Value, String)>,
+    /// Current buffer. Nil if nvim didn't send the buffer list.
+    pub current_buffer: Value,
+    pub buffers: Vec<(Value, String)>,
 }
 
 impl From<Value> for TablineUpdate {
@@ -718,7 +781,32 @@ impl From<Value> for TablineUpdate {
             })
             .collect();
 
-        Self { current, tabs }
+        // Older nvim versions don't send the buffers.
+        let items = unwrap_array!(args);
+        let current_buffer = items.get(2).cloned().unwrap_or(Value::Nil);
+        let buffers = items
+            .get(3)
+            .and_then(Value::as_array)
+            .map(|buffers| {
+                buffers
+                    .iter()
+                    .map(|item| {
+                        let m = map_to_hash(item);
+                        (
+                            (*m.get("buffer").unwrap()).clone(),
+                            unwrap_str!(m.get("name").unwrap()).to_string(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            current,
+            tabs,
+            current_buffer,
+            buffers,
+        }
     }
 }
 
@@ -729,10 +817,10 @@ pub struct CmdlineBlockShow {
 
 impl From<Value> for CmdlineBlockShow {
     fn from(args: Value) -> Self {
-        let lines = unwrap_array!(args)
+        let lines = unwrap_array!(args[0])
             .iter()
             .map(|line| {
-                unwrap_array!(line[0])
+                unwrap_array!(line)
                     .iter()
                     .map(|v| {
                         let hl_id = unwrap_u64!(v[0]);
@@ -874,6 +962,8 @@ impl From<Value> for MsgSetPos {
 #[derive(Debug, PartialEq)]
 pub enum RedrawEvent {
     SetTitle(Vec<String>),
+    /// Icon name of the window (see `:h 'iconstring'`).
+    SetIcon(Vec<String>),
 
     GridLine(Vec<GridLineSegment>),
     GridResize(Vec<GridResize>),
@@ -889,6 +979,8 @@ pub enum RedrawEvent {
     ModeInfoSet(Vec<ModeInfoSet>),
     ModeChange(Vec<ModeChange>),
     SetBusy(bool),
+    /// If nvim has the mouse enabled, based on the 'mouse' option.
+    SetMouse(bool),
 
     Flush(),
 
@@ -920,6 +1012,7 @@ impl fmt::Display for RedrawEvent {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
             RedrawEvent::SetTitle(..) => write!(fmt, "SetTitle"),
+            RedrawEvent::SetIcon(..) => write!(fmt, "SetIcon"),
             RedrawEvent::GridLine(..) => write!(fmt, "GridLine"),
             RedrawEvent::GridResize(..) => write!(fmt, "GridResize"),
             RedrawEvent::GridCursorGoto(..) => write!(fmt, "GridCursorGoto"),
@@ -935,6 +1028,7 @@ impl fmt::Display for RedrawEvent {
             RedrawEvent::ModeInfoSet(..) => write!(fmt, "ModeInfoSet"),
             RedrawEvent::ModeChange(..) => write!(fmt, "ModeChange"),
             RedrawEvent::SetBusy(..) => write!(fmt, "SetBusy"),
+            RedrawEvent::SetMouse(..) => write!(fmt, "SetMouse"),
             RedrawEvent::Flush(..) => write!(fmt, "Flush"),
             RedrawEvent::PopupmenuShow(..) => write!(fmt, "PopupmenuShow"),
             RedrawEvent::PopupmenuHide(..) => write!(fmt, "PopupmenuHide"),
@@ -971,6 +1065,270 @@ impl fmt::Display for RedrawEvent {
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TablinePosition {
+    Top,
+    Bottom,
+}
+
+impl TablinePosition {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "top" => Ok(TablinePosition::Top),
+            "bottom" => Ok(TablinePosition::Bottom),
+            _ => Err(format!("Unknown tabline position: {}", s)),
+        }
+    }
+}
+
+/// What the tabline shows.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TablineMode {
+    Tabs,
+    Buffers,
+}
+
+impl TablineMode {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "tabs" => Ok(TablineMode::Tabs),
+            "buffers" => Ok(TablineMode::Buffers),
+            _ => Err(format!("Unknown tabline mode: {}", s)),
+        }
+    }
+}
+
+/// How the tabline's labels are formatted.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TablineLabelFormat {
+    /// The name as nvim sends it.
+    Full,
+    /// Only the last component of the path.
+    FileName,
+    /// Directories shortened to their first letter (like `pathshorten()`).
+    Shortened,
+}
+
+impl TablineLabelFormat {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "full" => Ok(TablineLabelFormat::Full),
+            "filename" => Ok(TablineLabelFormat::FileName),
+            "shortened" => Ok(TablineLabelFormat::Shortened),
+            _ => Err(format!("Unknown tabline label format: {}", s)),
+        }
+    }
+}
+
+/// Mouse buttons whose input is sent to nvim.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct MouseButtons {
+    pub left: bool,
+    pub middle: bool,
+    pub right: bool,
+    /// The extra (back and forward) buttons.
+    pub extra: bool,
+}
+
+impl Default for MouseButtons {
+    fn default() -> Self {
+        MouseButtons {
+            left: true,
+            middle: true,
+            right: true,
+            extra: false,
+        }
+    }
+}
+
+impl MouseButtons {
+    pub fn from_names(names: &[&str]) -> Result<Self, String> {
+        let mut buttons = MouseButtons {
+            left: false,
+            middle: false,
+            right: false,
+            extra: false,
+        };
+
+        for name in names {
+            match *name {
+                "left" => buttons.left = true,
+                "middle" => buttons.middle = true,
+                "right" => buttons.right = true,
+                "extra" => buttons.extra = true,
+                _ => return Err(format!("Unknown mouse button: {}", name)),
+            }
+        }
+
+        Ok(buttons)
+    }
+}
+
+/// Where mouse wheel scrolls are sent to.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ScrollTarget {
+    /// The grid and cell under the pointer.
+    Pointer,
+    /// The active grid, at its cursor.
+    Active,
+}
+
+impl ScrollTarget {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "pointer" => Ok(ScrollTarget::Pointer),
+            "active" => Ok(ScrollTarget::Active),
+            _ => Err(format!("Unknown scroll target: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum WildmenuLayout {
+    Horizontal,
+    Vertical,
+}
+
+impl WildmenuLayout {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "horizontal" => Ok(WildmenuLayout::Horizontal),
+            "vertical" => Ok(WildmenuLayout::Vertical),
+            _ => Err(format!("Unknown wildmenu layout: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum UnfocusedCursor {
+    /// Draw only the outline of the cursor.
+    Hollow,
+    /// Draw the cursor as if the window was focused.
+    Solid,
+    /// Draw the cursor normally, but don't blink it.
+    NoBlink,
+}
+
+impl UnfocusedCursor {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "hollow" => Ok(UnfocusedCursor::Hollow),
+            "solid" => Ok(UnfocusedCursor::Solid),
+            "noblink" => Ok(UnfocusedCursor::NoBlink),
+            _ => Err(format!("Unknown unfocused cursor: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FlashRegion {
+    pub grid: i64,
+    /// Ranges to flash, (row, start_col, end_col). `end_col` is exclusive.
+    pub ranges: Vec<(u64, u64, u64)>,
+    pub color: Color,
+    /// Duration of the flash in milliseconds.
+    pub duration: u64,
+}
+
+impl FlashRegion {
+    fn parse(args: &[Value]) -> Result<Self, String> {
+        let grid = try_i64!(args.get(1).ok_or("grid missing")?, "flash grid");
+        let ranges = try_array!(args.get(2).ok_or("ranges missing")?, "ranges")
+            .iter()
+            .map(|range| {
+                let range = try_array!(range, "flash range");
+                if range.len() != 3 {
+                    return Err(String::from("Invalid flash range"));
+                }
+
+                Ok((
+                    try_u64!(range[0], "flash range row"),
+                    try_u64!(range[1], "flash range start col"),
+                    try_u64!(range[2], "flash range end col"),
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let color = Color::from_hex_string(
+            try_str!(args.get(3).ok_or("color missing")?, "flash color")
+                .to_string(),
+        )?;
+        let duration =
+            try_u64!(args.get(4).ok_or("duration missing")?, "flash duration");
+
+        Ok(FlashRegion {
+            grid,
+            ranges,
+            color,
+            duration,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Winbar {
+    pub grid: i64,
+    /// Chunks of (highlight, text). Empty chunks hide the winbar.
+    pub chunks: Vec<(Highlight, String)>,
+}
+
+impl Winbar {
+    fn parse(args: &[Value]) -> Result<Self, String> {
+        let grid = try_i64!(args.get(1).ok_or("grid missing")?, "winbar grid");
+        let chunks = try_array!(args.get(2).ok_or("chunks missing")?, "chunks")
+            .iter()
+            .map(|chunk| {
+                let chunk = try_array!(chunk, "winbar chunk");
+                if chunk.len() != 2 {
+                    return Err(String::from("Invalid winbar chunk"));
+                }
+
+                // The highlight is in the same format as what
+                // `nvim_get_hl_by_name` returns.
+                let hl = chunk[1]
+                    .as_map()
+                    .ok_or("Value is not a map: winbar chunk highlight")?;
+
+                Ok((
+                    Highlight::from_map_val(hl),
+                    try_str!(chunk[0], "winbar chunk text").to_string(),
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok(Winbar { grid, chunks })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PlaceImage {
+    pub grid: i64,
+    pub row: u64,
+    pub col: u64,
+    /// Width of the image's area in cells.
+    pub width: u64,
+    /// Height of the image's area in cells.
+    pub height: u64,
+    /// Path to the image file.
+    pub path: String,
+}
+
+impl PlaceImage {
+    fn parse(args: &[Value]) -> Result<Self, String> {
+        Ok(PlaceImage {
+            grid: try_i64!(args.get(1).ok_or("grid missing")?, "image grid"),
+            row: try_u64!(args.get(2).ok_or("row missing")?, "image row"),
+            col: try_u64!(args.get(3).ok_or("col missing")?, "image col"),
+            width: try_u64!(args.get(4).ok_or("width missing")?, "image width"),
+            height: try_u64!(
+                args.get(5).ok_or("height missing")?,
+                "image height"
+            ),
+            path: try_str!(args.get(6).ok_or("path missing")?, "image path")
+                .to_string(),
+        })
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum GnvimEvent {
     CompletionMenuToggleInfo,
@@ -979,13 +1337,180 @@ pub enum GnvimEvent {
     CursorTooltipShow(String, u64, u64),
     CursorTooltipHide,
     CursorTooltipSetStyle(String),
+    CursorTooltipReloadStyle,
+    CursorTooltipSetCodeFont(String),
 
     PopupmenuWidth(u64),
     PopupmenuWidthDetails(u64),
     PopupmenuShowMenuOnAllItems(bool),
+    /// Scroll the info view by n lines.
+    PopupmenuInfoScroll(i64),
+    /// Pin the popupmenu to a cell (row, col, grid) instead of the cursor.
+    PopupmenuSetAnchor(u64, u64, i64),
+    /// Remove the anchor set with `PopupmenuSetAnchor`.
+    PopupmenuClearAnchor,
+    /// Max width of the popupmenu in pixels. Zero removes the limit.
+    PopupmenuMaxWidthPx(u64),
+    /// Animate showing and hiding the popupmenu.
+    SetPopupmenuAnimation(bool),
+    /// Pulse the background of the popupmenu's selected item.
+    PopupmenuSelectionPulse(bool),
+    /// Radius (in pixels) of the popupmenu's corners, and if a shadow is
+    /// drawn under it.
+    PopupmenuSetStyle(u64, bool),
 
     EnableCursorAnimations(bool),
 
+    /// Dim factor (0.0 - 1.0) for inactive grids.
+    SetInactiveDim(f64),
+
+    TablineSetPosition(TablinePosition),
+
+    SetVirtualTextStyle(VirtualTextStyle),
+
+    FontZoom(f64),
+    FontZoomReset,
+
+    SetInvertColors(bool),
+
+    SetModeIndicator(bool),
+
+    WildmenuSetLayout(WildmenuLayout),
+
+    SetUnfocusedCursor(UnfocusedCursor),
+
+    /// Underline thickness scale and offset.
+    SetUnderlineStyle(f64, f64),
+
+    SetCursorVisible(bool),
+
+    SetRememberGeometry(bool),
+
+    SetBoxDrawing(bool),
+
+    /// Extra space between cells, in pixels.
+    SetLetterSpacing(f64),
+    /// Briefly highlight parts of a grid (e.g. yanked text).
+    FlashRegion(FlashRegion),
+    /// Minimum contrast ratio (1.0 - 21.0) between text and its background.
+    /// Zero disables.
+    SetMinContrast(f64),
+    /// Max height of the cmdline block in rows. Zero restores the default.
+    CmdlineBlockMaxHeight(u64),
+    /// Paste the contents of the clipboard.
+    Paste,
+    /// Position text at whole device pixels.
+    SetPixelSnap(bool),
+    /// Draw the text under the cursor over the cursor, also when blinking.
+    SetCursorOverText(bool),
+    /// Which whitespace to render visible.
+    SetRenderWhitespace(RenderWhitespace),
+    /// Positions (grid, row, col) of extra cursors (e.g. for multiple
+    /// cursor plugins).
+    SetExtraCursors(Vec<(i64, u64, u64)>),
+    /// Delay (in ms) before nvim is notified about a window resize.
+    SetResizeDebounce(u32),
+    /// Cache rows scrolled out of the grids.
+    SetScrollCache(bool),
+    /// Ignore mouse input when nvim has the mouse disabled.
+    SetRespectMouseOption(bool),
+    /// Select text with the mouse on the GUI side, when nvim has the mouse
+    /// disabled.
+    SetGuiSelection(bool),
+    /// Vertical position of the cmdline, from 0.0 (top) to 1.0 (bottom).
+    CmdlineSetPosition(f64),
+    /// Show tabs or buffers in the tabline.
+    TablineMode(TablineMode),
+    /// Draw an image on top of a grid.
+    PlaceImage(PlaceImage),
+    /// Remove the images placed with `PlaceImage`.
+    ClearImages,
+    /// Enable or disable the input method.
+    SetInputMethod(bool),
+    /// Reverse the scroll direction.
+    SetNaturalScroll(bool),
+    /// Dim all lines except the given number of lines above and below the
+    /// cursor (above, below, amount). Zero amount disables the dimming.
+    SetDimInactiveLines(u64, u64, f64),
+    /// Set the minimum size of the window, in cells (cols, rows).
+    SetMinSize(u64, u64),
+    /// Set the font (in `guifont` format) of a single grid.
+    SetGridFont(i64, String),
+    /// Set the content of a GUI winbar on top of a grid's window.
+    SetWinbar(Winbar),
+    /// Animate changes to the font size.
+    SetZoomAnimation(bool),
+    /// Copy the text yanked to the `+` register to the clipboard (when
+    /// `ClipboardSetText` is sent).
+    SetClipboardSync(bool),
+    /// Text to set to the clipboard, if clipboard sync is enabled.
+    ClipboardSetText(String),
+    /// Draw a row of a grid scaled up, e.g. for banners. Takes the grid, the
+    /// row and the scale. Scale of 1 resets the row back to normal.
+    SetLineScale(i64, u64, u64),
+    /// Keys typed so far for a pending mapping. Empty string hides the
+    /// indicator.
+    SetPendingKeys(String),
+    /// Draw a border around the cmdline.
+    CmdlineSetBorder(bool),
+    /// Font (in guifont format) for just the tabline. Empty string makes
+    /// the tabline follow the grid font again.
+    TablineSetFont(String),
+    /// Keep the cursor's row at the middle of the grids by moving their
+    /// content.
+    SetTypewriterMode(bool),
+    /// Blink durations (on, off) for the cmdline's cursor, in milliseconds.
+    CmdlineSetCursorBlink(u64, u64),
+    /// Multiplier for the cells' width. Zero or one uses the font's width.
+    SetCellWidthScale(f64),
+    /// Color and width of the border around the active grid. Width of zero
+    /// disables the border.
+    SetActiveBorder(Color, f64),
+    /// Color for the area outside of the grids, left over when the window's
+    /// size isn't a multiple of the cell size. `None` uses the default
+    /// background color.
+    SetLetterboxColor(Option<Color>),
+    /// How the tabline's labels are formatted.
+    TablineSetLabelFormat(TablineLabelFormat),
+    /// Where mouse wheel scrolls are sent to.
+    SetScrollTarget(ScrollTarget),
+    /// Maximum rate of the grids' animation ticks. Zero disables the
+    /// animations.
+    SetAnimationFps(u32),
+    /// Mouse buttons whose input is sent to nvim. Input from the other
+    /// buttons is left for the GUI.
+    SetForwardedMouseButtons(MouseButtons),
+    /// Show a context menu on right clicks over the grids, instead of
+    /// sending them to nvim.
+    SetContextMenu(bool),
+    /// Items (label, keys) of the context menu.
+    ContextMenuSetItems(Vec<(String, String)>),
+    /// Color and width of the outline around the cursor. Width of zero
+    /// disables the outline.
+    SetCursorOutline(Color, f64),
+    /// Draw lines at the grids' cell boundaries, with the given color.
+    SetGridLines(bool, Color),
+    /// Draw a shadow under the text of the grids, with the given color,
+    /// x and y offsets and blur radius. Zero offsets disable the shadow.
+    SetTextShadow(Color, f64, f64, f64),
+    /// Restore gnvim's own settings (the ones set with these events) to
+    /// their defaults.
+    ResetGnvimSettings,
+    /// Redraw the grids on the next flush after the default colors change,
+    /// instead of right away.
+    SetDeferColorsRedraw(bool),
+    /// Blink on, off and wait durations (in ms) for a cursor shape,
+    /// overriding the blink from 'guicursor'. Zero on disables blinking.
+    SetCursorBlinkForShape(CursorShape, u64, u64, u64),
+    /// Current line and total lines of the buffer in the main grid, shown
+    /// as a thumb at the grid's right edge. Zero total lines hides it.
+    SetScrollIndicator(u64, u64),
+    /// Glyphs (space, tab, nbsp) for the rendered whitespace, in place of
+    /// the dots.
+    SetWhitespaceGlyphs(char, char, char),
+    /// Draw a spinner at the cursor's position while busy.
+    SetBusySpinner(bool),
+
     EnableExtTabline(bool),
     EnableExtCmdline(bool),
     EnableExtPopupmenu(bool),
@@ -995,6 +1520,11 @@ pub enum GnvimEvent {
 
 pub enum Request {
     CursorTooltipStyles,
+    GetFont,
+    /// Position and size of a cell (grid, row, col).
+    GetCellRect(i64, u64, u64),
+    /// Text of a grid's rows (grid, with highlight ids).
+    GetGridText(i64, bool),
 }
 
 /// Message type that we are sending to the UI.
@@ -1096,6 +1626,17 @@ fn parse_request(args: Vec<Value>) -> Result<Request, ()> {
 
     match cmd {
         "CursorTooltipGetStyles" => Ok(Request::CursorTooltipStyles),
+        "GetFont" => Ok(Request::GetFont),
+        "GetCellRect" => {
+            let grid = args.get(1).and_then(Value::as_i64).ok_or(())?;
+            let arg = |i: usize| args.get(i).and_then(Value::as_u64).ok_or(());
+            Ok(Request::GetCellRect(grid, arg(2)?, arg(3)?))
+        }
+        "GetGridText" => {
+            let grid = args.get(1).and_then(Value::as_i64).ok_or(())?;
+            let with_hl = args.get(2).and_then(Value::as_u64).unwrap_or(0);
+            Ok(Request::GetGridText(grid, with_hl == 1))
+        }
         _ => Err(()),
     }
 }
@@ -1115,6 +1656,11 @@ fn parse_single_redraw_event(cmd: &str, args: Vec<Value>) -> RedrawEvent {
                 .map(|v| unwrap_str!(v[0]).to_string())
                 .collect(),
         ),
+        "set_icon" => RedrawEvent::SetIcon(
+            args.into_iter()
+                .map(|v| unwrap_str!(v[0]).to_string())
+                .collect(),
+        ),
         "grid_resize" => RedrawEvent::GridResize(
             args.into_iter().map(GridResize::from).collect(),
         ),
@@ -1209,8 +1755,9 @@ fn parse_single_redraw_event(cmd: &str, args: Vec<Value>) -> RedrawEvent {
         "msg_set_pos" => RedrawEvent::MsgSetPos(
             args.into_iter().map(MsgSetPos::from).collect(),
         ),
+        "mouse_on" => RedrawEvent::SetMouse(true),
+        "mouse_off" => RedrawEvent::SetMouse(false),
 
-        "mouse_on" | "mouse_off" => RedrawEvent::Ignored(cmd.to_string()),
         _ => RedrawEvent::Unknown(cmd.to_string()),
     }
 }
@@ -1255,6 +1802,11 @@ pub(crate) fn parse_gnvim_event(
             );
             GnvimEvent::CursorTooltipSetStyle(style.to_string())
         }
+        "CursorTooltipReloadStyle" => GnvimEvent::CursorTooltipReloadStyle,
+        "CursorTooltipSetCodeFont" => GnvimEvent::CursorTooltipSetCodeFont(
+            try_str!(args.get(1).ok_or("font missing")?, "tooltip code font")
+                .to_string(),
+        ),
         "PopupmenuSetWidth" => {
             let w =
                 try_u64!(args.get(1).ok_or("width missing")?, "pmenu width");
@@ -1273,12 +1825,418 @@ pub(crate) fn parse_gnvim_event(
 
             GnvimEvent::PopupmenuShowMenuOnAllItems(b != 0)
         }
+        "PopupmenuInfoScroll" => GnvimEvent::PopupmenuInfoScroll(try_i64!(
+            args.get(1).ok_or("delta missing")?,
+            "pmenu info scroll delta"
+        )),
+        "PopupmenuSetAnchor" => GnvimEvent::PopupmenuSetAnchor(
+            try_u64!(args.get(1).ok_or("row missing")?, "pmenu anchor row"),
+            try_u64!(args.get(2).ok_or("col missing")?, "pmenu anchor col"),
+            try_i64!(args.get(3).ok_or("grid missing")?, "pmenu anchor grid"),
+        ),
+        "PopupmenuClearAnchor" => GnvimEvent::PopupmenuClearAnchor,
+        "PopupmenuSetMaxWidthPx" => GnvimEvent::PopupmenuMaxWidthPx(try_u64!(
+            args.get(1).ok_or("width missing")?,
+            "pmenu max width"
+        )),
+        "SetPopupmenuAnimation" => GnvimEvent::SetPopupmenuAnimation(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse popupmenu animation argument"
+            ) == 1,
+        ),
+        "PopupmenuSelectionPulse" => GnvimEvent::PopupmenuSelectionPulse(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse popupmenu selection pulse argument"
+            ) == 1,
+        ),
+        "PopupmenuSetStyle" => GnvimEvent::PopupmenuSetStyle(
+            try_u64!(
+                args.get(1).ok_or("border radius missing")?,
+                "pmenu border radius"
+            ),
+            try_u64!(
+                args.get(2).ok_or("shadow missing")?,
+                "failed to parse popupmenu shadow argument"
+            ) == 1,
+        ),
         "EnableCursorAnimations" => GnvimEvent::EnableCursorAnimations(
             try_u64!(
                 args.get(1).ok_or("argument missing")?,
                 "failed to parse enable cursor animations argument"
             ) == 1,
         ),
+        "SetInactiveDim" => GnvimEvent::SetInactiveDim(try_f64!(
+            args.get(1).ok_or("argument missing")?,
+            "inactive dim factor"
+        )),
+        "TablineSetPosition" => {
+            let pos = try_str!(
+                args.get(1).ok_or("position missing")?,
+                "tabline position"
+            );
+            GnvimEvent::TablineSetPosition(TablinePosition::from_string(pos)?)
+        }
+        "SetVirtualTextStyle" => {
+            let style = try_str!(
+                args.get(1).ok_or("style missing")?,
+                "virtual text style"
+            );
+            GnvimEvent::SetVirtualTextStyle(VirtualTextStyle::from_string(
+                style,
+            )?)
+        }
+        "FontZoom" => GnvimEvent::FontZoom(try_f64!(
+            args.get(1).ok_or("delta missing")?,
+            "font zoom delta"
+        )),
+        "FontZoomReset" => GnvimEvent::FontZoomReset,
+        "SetInvertColors" => GnvimEvent::SetInvertColors(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse invert colors argument"
+            ) == 1,
+        ),
+        "SetModeIndicator" => GnvimEvent::SetModeIndicator(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse mode indicator argument"
+            ) == 1,
+        ),
+        "WildmenuSetLayout" => {
+            let layout = try_str!(
+                args.get(1).ok_or("layout missing")?,
+                "wildmenu layout"
+            );
+            GnvimEvent::WildmenuSetLayout(WildmenuLayout::from_string(layout)?)
+        }
+        "SetUnfocusedCursor" => {
+            let cursor = try_str!(
+                args.get(1).ok_or("cursor missing")?,
+                "unfocused cursor"
+            );
+            GnvimEvent::SetUnfocusedCursor(UnfocusedCursor::from_string(
+                cursor,
+            )?)
+        }
+        "SetUnderlineStyle" => GnvimEvent::SetUnderlineStyle(
+            try_f64!(
+                args.get(1).ok_or("scale missing")?,
+                "underline thickness scale"
+            ),
+            try_f64!(args.get(2).ok_or("offset missing")?, "underline offset"),
+        ),
+        "SetCursorVisible" => GnvimEvent::SetCursorVisible(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse cursor visible argument"
+            ) == 1,
+        ),
+        "SetRememberGeometry" => GnvimEvent::SetRememberGeometry(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse remember geometry argument"
+            ) == 1,
+        ),
+        "SetBoxDrawing" => GnvimEvent::SetBoxDrawing(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse box drawing argument"
+            ) == 1,
+        ),
+        "SetLetterSpacing" => GnvimEvent::SetLetterSpacing(try_f64!(
+            args.get(1).ok_or("spacing missing")?,
+            "letter spacing"
+        )),
+        "FlashRegion" => GnvimEvent::FlashRegion(FlashRegion::parse(&args)?),
+        "SetMinContrast" => GnvimEvent::SetMinContrast(try_f64!(
+            args.get(1).ok_or("ratio missing")?,
+            "min contrast ratio"
+        )),
+        "CmdlineBlockMaxHeight" => GnvimEvent::CmdlineBlockMaxHeight(try_u64!(
+            args.get(1).ok_or("rows missing")?,
+            "cmdline block max height"
+        )),
+        "Paste" => GnvimEvent::Paste,
+        "SetPixelSnap" => GnvimEvent::SetPixelSnap(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse pixel snap argument"
+            ) == 1,
+        ),
+        "SetCursorOverText" => GnvimEvent::SetCursorOverText(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse cursor over text argument"
+            ) == 1,
+        ),
+        "SetRenderWhitespace" => {
+            let ws = try_str!(
+                args.get(1).ok_or("whitespace missing")?,
+                "render whitespace"
+            );
+            GnvimEvent::SetRenderWhitespace(RenderWhitespace::from_string(ws)?)
+        }
+        "SetExtraCursors" => GnvimEvent::SetExtraCursors(
+            try_array!(args.get(1).ok_or("cursors missing")?, "cursors")
+                .iter()
+                .map(|pos| {
+                    let pos = try_array!(pos, "cursor position");
+                    if pos.len() != 3 {
+                        return Err(String::from("Invalid cursor position"));
+                    }
+
+                    Ok((
+                        try_i64!(pos[0], "cursor grid"),
+                        try_u64!(pos[1], "cursor row"),
+                        try_u64!(pos[2], "cursor col"),
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()?,
+        ),
+        "SetResizeDebounce" => GnvimEvent::SetResizeDebounce(
+            u32::try_from(try_u64!(
+                args.get(1).ok_or("delay missing")?,
+                "resize debounce"
+            ))
+            .map_err(|_| "resize debounce out of range")?,
+        ),
+        "SetScrollCache" => GnvimEvent::SetScrollCache(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse scroll cache argument"
+            ) == 1,
+        ),
+        "SetRespectMouseOption" => GnvimEvent::SetRespectMouseOption(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse respect mouse option argument"
+            ) == 1,
+        ),
+        "SetGuiSelection" => GnvimEvent::SetGuiSelection(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse gui selection argument"
+            ) == 1,
+        ),
+        "CmdlineSetPosition" => GnvimEvent::CmdlineSetPosition(try_f64!(
+            args.get(1).ok_or("position missing")?,
+            "cmdline position"
+        )),
+        "TablineSetMode" => {
+            let mode =
+                try_str!(args.get(1).ok_or("mode missing")?, "tabline mode");
+            GnvimEvent::TablineMode(TablineMode::from_string(mode)?)
+        }
+        "PlaceImage" => GnvimEvent::PlaceImage(PlaceImage::parse(&args)?),
+        "ClearImages" => GnvimEvent::ClearImages,
+        "SetInputMethod" => GnvimEvent::SetInputMethod(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse input method argument"
+            ) == 1,
+        ),
+        "SetNaturalScroll" => GnvimEvent::SetNaturalScroll(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse natural scroll argument"
+            ) == 1,
+        ),
+        "SetDimInactiveLines" => GnvimEvent::SetDimInactiveLines(
+            try_u64!(args.get(1).ok_or("above missing")?, "dim lines above"),
+            try_u64!(args.get(2).ok_or("below missing")?, "dim lines below"),
+            try_f64!(args.get(3).ok_or("amount missing")?, "dim amount"),
+        ),
+        "SetMinSize" => GnvimEvent::SetMinSize(
+            try_u64!(args.get(1).ok_or("cols missing")?, "min size cols"),
+            try_u64!(args.get(2).ok_or("rows missing")?, "min size rows"),
+        ),
+        "SetGridFont" => GnvimEvent::SetGridFont(
+            try_i64!(args.get(1).ok_or("grid missing")?, "grid font grid"),
+            try_str!(args.get(2).ok_or("font missing")?, "grid font")
+                .to_string(),
+        ),
+        "SetWinbar" => GnvimEvent::SetWinbar(Winbar::parse(&args)?),
+        "SetZoomAnimation" => GnvimEvent::SetZoomAnimation(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse zoom animation argument"
+            ) == 1,
+        ),
+        "SetClipboardSync" => GnvimEvent::SetClipboardSync(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse clipboard sync argument"
+            ) == 1,
+        ),
+        "ClipboardSetText" => GnvimEvent::ClipboardSetText(
+            try_str!(args.get(1).ok_or("text missing")?, "clipboard text")
+                .to_string(),
+        ),
+        "SetLineScale" => GnvimEvent::SetLineScale(
+            try_i64!(args.get(1).ok_or("grid missing")?, "line scale grid"),
+            try_u64!(args.get(2).ok_or("row missing")?, "line scale row"),
+            try_u64!(args.get(3).ok_or("scale missing")?, "line scale"),
+        ),
+        "SetPendingKeys" => GnvimEvent::SetPendingKeys(
+            try_str!(args.get(1).ok_or("keys missing")?, "pending keys")
+                .to_string(),
+        ),
+        "CmdlineSetBorder" => GnvimEvent::CmdlineSetBorder(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse cmdline border argument"
+            ) == 1,
+        ),
+        "TablineSetFont" => GnvimEvent::TablineSetFont(
+            try_str!(args.get(1).ok_or("font missing")?, "tabline font")
+                .to_string(),
+        ),
+        "SetTypewriterMode" => GnvimEvent::SetTypewriterMode(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse typewriter mode argument"
+            ) == 1,
+        ),
+        "CmdlineSetCursorBlink" => GnvimEvent::CmdlineSetCursorBlink(
+            try_u64!(args.get(1).ok_or("on missing")?, "cmdline blink on"),
+            try_u64!(args.get(2).ok_or("off missing")?, "cmdline blink off"),
+        ),
+        "SetCellWidthScale" => GnvimEvent::SetCellWidthScale(try_f64!(
+            args.get(1).ok_or("scale missing")?,
+            "cell width scale"
+        )),
+        "SetActiveBorder" => GnvimEvent::SetActiveBorder(
+            Color::from_hex_string(
+                try_str!(args.get(1).ok_or("color missing")?, "border color")
+                    .to_string(),
+            )?,
+            try_f64!(args.get(2).ok_or("width missing")?, "border width"),
+        ),
+        "SetLetterboxColor" => {
+            let color = try_str!(
+                args.get(1).ok_or("color missing")?,
+                "letterbox color"
+            );
+            GnvimEvent::SetLetterboxColor(if color.is_empty() {
+                None
+            } else {
+                Some(Color::from_hex_string(color.to_string())?)
+            })
+        }
+        "TablineSetLabelFormat" => {
+            let format = TablineLabelFormat::from_string(try_str!(
+                args.get(1).ok_or("format missing")?,
+                "tabline label format"
+            ))?;
+            GnvimEvent::TablineSetLabelFormat(format)
+        }
+        "SetScrollTarget" => {
+            let target = ScrollTarget::from_string(try_str!(
+                args.get(1).ok_or("target missing")?,
+                "scroll target"
+            ))?;
+            GnvimEvent::SetScrollTarget(target)
+        }
+        "SetAnimationFps" => GnvimEvent::SetAnimationFps(
+            u32::try_from(try_u64!(
+                args.get(1).ok_or("fps missing")?,
+                "animation fps"
+            ))
+            .map_err(|_| "animation fps out of range")?,
+        ),
+        "SetForwardedMouseButtons" => {
+            let names = try_array!(
+                args.get(1).ok_or("buttons missing")?,
+                "mouse buttons"
+            )
+            .iter()
+            .map(|name| Ok(try_str!(name, "mouse button")))
+            .collect::<Result<Vec<&str>, String>>()?;
+            GnvimEvent::SetForwardedMouseButtons(MouseButtons::from_names(
+                &names,
+            )?)
+        }
+        "SetContextMenu" => GnvimEvent::SetContextMenu(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse context menu argument"
+            ) == 1,
+        ),
+        "ContextMenuSetItems" => GnvimEvent::ContextMenuSetItems(
+            try_array!(args.get(1).ok_or("items missing")?, "menu items")
+                .iter()
+                .map(|item| {
+                    let item = try_array!(item, "menu item");
+                    if item.len() != 2 {
+                        return Err(String::from("Invalid menu item"));
+                    }
+
+                    Ok((
+                        try_str!(item[0], "menu item label").to_string(),
+                        try_str!(item[1], "menu item keys").to_string(),
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()?,
+        ),
+        "SetCursorOutline" => GnvimEvent::SetCursorOutline(
+            Color::from_hex_string(
+                try_str!(args.get(1).ok_or("color missing")?, "outline color")
+                    .to_string(),
+            )?,
+            try_f64!(args.get(2).ok_or("width missing")?, "outline width"),
+        ),
+        "SetGridLines" => GnvimEvent::SetGridLines(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse grid lines argument"
+            ) == 1,
+            Color::from_hex_string(
+                try_str!(args.get(2).ok_or("color missing")?, "grid lines")
+                    .to_string(),
+            )?,
+        ),
+        "SetTextShadow" => GnvimEvent::SetTextShadow(
+            Color::from_hex_string(
+                try_str!(args.get(1).ok_or("color missing")?, "shadow color")
+                    .to_string(),
+            )?,
+            try_f64!(args.get(2).ok_or("dx missing")?, "shadow dx"),
+            try_f64!(args.get(3).ok_or("dy missing")?, "shadow dy"),
+            try_f64!(args.get(4).ok_or("blur missing")?, "shadow blur"),
+        ),
+        "ResetGnvimSettings" => GnvimEvent::ResetGnvimSettings,
+        "SetDeferColorsRedraw" => GnvimEvent::SetDeferColorsRedraw(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse defer colors redraw argument"
+            ) == 1,
+        ),
+        "SetCursorBlinkForShape" => GnvimEvent::SetCursorBlinkForShape(
+            CursorShape::parse(try_str!(
+                args.get(1).ok_or("shape missing")?,
+                "cursor shape"
+            ))?,
+            try_u64!(args.get(2).ok_or("blink on missing")?, "blink on"),
+            try_u64!(args.get(3).ok_or("blink off missing")?, "blink off"),
+            try_u64!(args.get(4).ok_or("blink wait missing")?, "blink wait"),
+        ),
+        "SetScrollIndicator" => GnvimEvent::SetScrollIndicator(
+            try_u64!(args.get(1).ok_or("line missing")?, "current line"),
+            try_u64!(args.get(2).ok_or("total missing")?, "total lines"),
+        ),
+        "SetWhitespaceGlyphs" => GnvimEvent::SetWhitespaceGlyphs(
+            parse_glyph(args.get(1).ok_or("space glyph missing")?, "space")?,
+            parse_glyph(args.get(2).ok_or("tab glyph missing")?, "tab")?,
+            parse_glyph(args.get(3).ok_or("nbsp glyph missing")?, "nbsp")?,
+        ),
+        "SetBusySpinner" => GnvimEvent::SetBusySpinner(
+            try_u64!(
+                args.get(1).ok_or("argument missing")?,
+                "failed to parse busy spinner argument"
+            ) == 1,
+        ),
         "EnableExtTabline" => GnvimEvent::EnableExtTabline(
             try_u64!(
                 args.get(1).ok_or("argument missing")?,
@@ -1303,6 +2261,16 @@ pub(crate) fn parse_gnvim_event(
     Ok(res)
 }
 
+/// Parses a glyph, which must be a single codepoint.
+fn parse_glyph(val: &Value, what: &str) -> Result<char, String> {
+    let s = try_str!(val, format!("{} glyph", what));
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!("Glyph is not a single character: {:?}", s)),
+    }
+}
+
 fn map_to_hash<'a>(val: &'a Value) -> HashMap<&'a str, &'a Value> {
     let mut h = HashMap::new();
     for (prop, val) in unwrap_map!(val) {
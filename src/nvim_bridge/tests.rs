@@ -16,11 +16,11 @@ mod parse_redraw_event_tests {
 
     use crate::nvim_bridge;
     use crate::nvim_bridge::{
-        Cell, CmdlineBlockAppend, CmdlinePos, CmdlineShow, CmdlineSpecialChar,
-        CompletionItem, CompletionItemKind, CursorShape, DefaultColorsSet,
-        GridCursorGoto, GridLineSegment, GridResize, GridScroll, HlAttrDefine,
-        ModeChange, ModeInfo, ModeInfoSet, OptionSet, PopupmenuShow,
-        RedrawEvent, TablineUpdate,
+        Cell, CmdlineBlockAppend, CmdlineBlockShow, CmdlinePos, CmdlineShow,
+        CmdlineSpecialChar, CompletionItem, CompletionItemKind, CursorShape,
+        DefaultColorsSet, GridCursorGoto, GridLineSegment, GridResize,
+        GridScroll, HlAttrDefine, ModeChange, ModeInfo, ModeInfoSet, OptionSet,
+        PopupmenuShow, RedrawEvent, TablineUpdate,
     };
     use crate::ui::color::{Color, Highlight};
     use rmpv::Value;
@@ -38,6 +38,18 @@ mod parse_redraw_event_tests {
         assert_eq!(expected, res);
     }
 
+    #[test]
+    fn set_icon() {
+        let expected = vec![RedrawEvent::SetIcon(vec!["my icon".to_string()])];
+
+        let res = nvim_bridge::parse_redraw_event(args!(
+            String::from("set_icon").into(),
+            Value::Array(vec!(String::from("my icon").into(),))
+        ));
+
+        assert_eq!(expected, res);
+    }
+
     #[test]
     fn grid_line() {
         let expected = vec![RedrawEvent::GridLine(vec![
@@ -243,7 +255,10 @@ mod parse_redraw_event_tests {
                     bold: true,
                     underline: true,
                     undercurl: false,
+                    strikethrough: false,
+                    nocombine: false,
                     blend: 0.0,
+                    virtual_text: false,
                 },
             },
             HlAttrDefine {
@@ -257,7 +272,10 @@ mod parse_redraw_event_tests {
                     bold: true,
                     underline: false,
                     undercurl: true,
+                    strikethrough: false,
+                    nocombine: false,
                     blend: 0.0,
+                    virtual_text: false,
                 },
             },
             HlAttrDefine {
@@ -271,7 +289,10 @@ mod parse_redraw_event_tests {
                     bold: true,
                     underline: false,
                     undercurl: true,
+                    strikethrough: false,
+                    nocombine: false,
                     blend: 0.3,
+                    virtual_text: false,
                 },
             },
             HlAttrDefine {
@@ -285,7 +306,10 @@ mod parse_redraw_event_tests {
                     bold: false,
                     underline: false,
                     undercurl: false,
+                    strikethrough: false,
+                    nocombine: false,
                     blend: 0.0,
+                    virtual_text: false,
                 },
             },
         ])];
@@ -333,6 +357,121 @@ mod parse_redraw_event_tests {
         assert_eq!(expected, res);
     }
 
+    #[test]
+    fn hl_attr_define_virtual_text() {
+        let expected = vec![RedrawEvent::HlAttrDefine(vec![
+            HlAttrDefine {
+                id: 5,
+                hl: Highlight {
+                    foreground: Some(Color::from_u64(3215)),
+                    virtual_text: true,
+                    ..Highlight::default()
+                },
+            },
+            HlAttrDefine {
+                id: 6,
+                hl: Highlight {
+                    foreground: Some(Color::from_u64(3215)),
+                    ..Highlight::default()
+                },
+            },
+        ])];
+
+        let res = nvim_bridge::parse_redraw_event(args!(
+            "hl_attr_define".into(),
+            Value::Array(vec!(
+                5.into(),
+                Value::Map(vec!(("foreground".into(), 3215.into()),)),
+                Value::Map(vec!()),
+                Value::Array(vec!(Value::Map(vec!(
+                    ("kind".into(), "syntax".into()),
+                    ("hi_name".into(), "DiagnosticVirtualTextError".into()),
+                    ("id".into(), 210.into()),
+                )))),
+            )),
+            Value::Array(vec!(
+                6.into(),
+                Value::Map(vec!(("foreground".into(), 3215.into()),)),
+                Value::Map(vec!()),
+                Value::Array(vec!(Value::Map(vec!(
+                    ("kind".into(), "syntax".into()),
+                    ("hi_name".into(), "Comment".into()),
+                    ("id".into(), 13.into()),
+                )))),
+            ))
+        ));
+
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn hl_attr_define_nocombine() {
+        // The underline of the highlight under a nocombine highlight isn't
+        // inherited.
+        let expected = vec![RedrawEvent::HlAttrDefine(vec![HlAttrDefine {
+            id: 7,
+            hl: Highlight {
+                foreground: Some(Color::from_u64(3215)),
+                nocombine: true,
+                virtual_text: true,
+                ..Highlight::default()
+            },
+        }])];
+
+        let res = nvim_bridge::parse_redraw_event(args!(
+            "hl_attr_define".into(),
+            Value::Array(vec!(
+                7.into(),
+                Value::Map(vec!(
+                    ("foreground".into(), 3215.into()),
+                    ("nocombine".into(), true.into()),
+                )),
+                Value::Map(vec!()),
+                Value::Array(vec!(
+                    Value::Map(vec!(
+                        ("kind".into(), "syntax".into()),
+                        ("hi_name".into(), "Underlined".into()),
+                        ("id".into(), 40.into()),
+                    )),
+                    Value::Map(vec!(
+                        ("kind".into(), "syntax".into()),
+                        ("hi_name".into(), "DiagnosticVirtualTextError".into()),
+                        ("id".into(), 210.into()),
+                    )),
+                )),
+            ))
+        ));
+
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn hl_attr_define_strikethrough() {
+        let expected = vec![RedrawEvent::HlAttrDefine(vec![HlAttrDefine {
+            id: 8,
+            hl: Highlight {
+                special: Some(Color::from_u64(0xff0000)),
+                strikethrough: true,
+                ..Highlight::default()
+            },
+        }])];
+
+        let res = nvim_bridge::parse_redraw_event(args!(
+            "hl_attr_define".into(),
+            Value::Array(vec!(
+                8.into(),
+                Value::Map(vec!(
+                    ("special".into(), 0xff0000.into()),
+                    ("strikethrough".into(), true.into()),
+                )),
+                Value::Map(vec!()),
+                Value::Array(vec!()),
+            ))
+        ));
+
+        assert_eq!(expected, res);
+    }
+
     #[test]
     fn option_set() {
         let expected = vec![RedrawEvent::OptionSet(vec![
@@ -358,11 +497,15 @@ mod parse_redraw_event_tests {
                     blink_on: 32,
                     cursor_shape: CursorShape::Horizontal,
                     cell_percentage: 0.32,
+                    name: "insert".into(),
+                    attr_id: 5,
                 },
                 ModeInfo {
                     blink_on: 1,
                     cursor_shape: CursorShape::Block,
                     cell_percentage: 1.0,
+                    name: String::new(),
+                    attr_id: 0,
                 },
             ],
         }])];
@@ -376,6 +519,8 @@ mod parse_redraw_event_tests {
                         ("blinkon".into(), 32.into()),
                         ("cursor_shape".into(), "horizontal".into()),
                         ("cell_percentage".into(), 32.into()),
+                        ("name".into(), "insert".into()),
+                        ("attr_id".into(), 5.into()),
                     )),
                     Value::Map(vec!(
                         ("blinkon".into(), 1.into()),
@@ -513,6 +658,8 @@ mod parse_redraw_event_tests {
                 ("bar".into(), "bar_name".into()),
                 ("ugh".into(), "ugh_name".into()),
             ],
+            current_buffer: Value::Nil,
+            buffers: vec![],
         }])];
 
         let res = nvim_bridge::parse_redraw_event(args!(
@@ -535,6 +682,43 @@ mod parse_redraw_event_tests {
         assert_eq!(expected, res);
     }
 
+    #[test]
+    fn tabline_update_buffers() {
+        let expected = vec![RedrawEvent::TablineUpdate(vec![TablineUpdate {
+            current: "foo".into(),
+            tabs: vec![("foo".into(), "foo_name".into())],
+            current_buffer: "buf2".into(),
+            buffers: vec![
+                ("buf1".into(), "buf1_name".into()),
+                ("buf2".into(), "buf2_name".into()),
+            ],
+        }])];
+
+        let res = nvim_bridge::parse_redraw_event(args!(
+            "tabline_update".into(),
+            Value::Array(vec!(
+                "foo".into(),
+                Value::Array(vec!(Value::Map(vec!(
+                    ("tab".into(), "foo".into()),
+                    ("name".into(), "foo_name".into()),
+                )),)),
+                "buf2".into(),
+                Value::Array(vec!(
+                    Value::Map(vec!(
+                        ("buffer".into(), "buf1".into()),
+                        ("name".into(), "buf1_name".into()),
+                    )),
+                    Value::Map(vec!(
+                        ("buffer".into(), "buf2".into()),
+                        ("name".into(), "buf2_name".into()),
+                    )),
+                )),
+            ))
+        ));
+
+        assert_eq!(expected, res);
+    }
+
     #[test]
     fn cmdline_show() {
         let expected = vec![RedrawEvent::CmdlineShow(vec![CmdlineShow {
@@ -623,6 +807,47 @@ mod parse_redraw_event_tests {
         assert_eq!(expected, res);
     }
 
+    #[test]
+    fn cmdline_block_show() {
+        let expected =
+            vec![RedrawEvent::CmdlineBlockShow(vec![CmdlineBlockShow {
+                lines: vec![
+                    vec![
+                        (3, "function".to_string()),
+                        (0, " ".to_string()),
+                        (7, "Foo".to_string()),
+                        (0, "()".to_string()),
+                    ],
+                    vec![
+                        (0, "  ".to_string()),
+                        (3, "echo".to_string()),
+                        (0, " ".to_string()),
+                        (5, "'bar'".to_string()),
+                    ],
+                ],
+            }])];
+
+        let res = nvim_bridge::parse_redraw_event(args!(
+            "cmdline_block_show".into(),
+            Value::Array(vec!(Value::Array(vec!(
+                Value::Array(vec!(
+                    Value::Array(vec!(3.into(), "function".into())),
+                    Value::Array(vec!(0.into(), " ".into())),
+                    Value::Array(vec!(7.into(), "Foo".into())),
+                    Value::Array(vec!(0.into(), "()".into())),
+                )),
+                Value::Array(vec!(
+                    Value::Array(vec!(0.into(), "  ".into())),
+                    Value::Array(vec!(3.into(), "echo".into())),
+                    Value::Array(vec!(0.into(), " ".into())),
+                    Value::Array(vec!(5.into(), "'bar'".into())),
+                )),
+            )),))
+        ));
+
+        assert_eq!(expected, res);
+    }
+
     #[test]
     fn cmdline_block_hide() {
         let expected = vec![RedrawEvent::CmdlineBlockHide()];
@@ -635,7 +860,7 @@ mod parse_redraw_event_tests {
 
     #[test]
     fn mouse_on() {
-        let expected = vec![RedrawEvent::Ignored("mouse_on".to_owned())];
+        let expected = vec![RedrawEvent::SetMouse(true)];
 
         let res = nvim_bridge::parse_redraw_event(args!("mouse_on".into()));
 
@@ -644,7 +869,7 @@ mod parse_redraw_event_tests {
 
     #[test]
     fn mouse_off() {
-        let expected = vec![RedrawEvent::Ignored("mouse_off".to_owned())];
+        let expected = vec![RedrawEvent::SetMouse(false)];
 
         let res = nvim_bridge::parse_redraw_event(args!("mouse_off".into()));
 
@@ -655,7 +880,14 @@ mod parse_redraw_event_tests {
 mod parse_gnvim_event_tests {
 
     use crate::nvim_bridge;
-    use crate::nvim_bridge::GnvimEvent;
+    use crate::nvim_bridge::{
+        FlashRegion, GnvimEvent, MouseButtons, PlaceImage, ScrollTarget,
+        TablineLabelFormat, TablineMode, TablinePosition, UnfocusedCursor,
+        WildmenuLayout, Winbar,
+    };
+    use crate::ui::color::{
+        Color, Highlight, RenderWhitespace, VirtualTextStyle,
+    };
     use rmpv::Value;
 
     #[test]
@@ -709,6 +941,32 @@ mod parse_gnvim_event_tests {
         assert_eq!(expected, res);
     }
 
+    #[test]
+    fn cursor_tooltip_reload_style() {
+        let expected: Result<GnvimEvent, String> =
+            Ok(GnvimEvent::CursorTooltipReloadStyle);
+
+        let res = nvim_bridge::parse_gnvim_event(vec![
+            "CursorTooltipReloadStyle".into(),
+        ]);
+
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn cursor_tooltip_set_code_font() {
+        let expected: Result<GnvimEvent, String> = Ok(
+            GnvimEvent::CursorTooltipSetCodeFont("Iosevka:h10".to_owned()),
+        );
+
+        let res = nvim_bridge::parse_gnvim_event(vec![
+            "CursorTooltipSetCodeFont".into(),
+            "Iosevka:h10".into(),
+        ]);
+
+        assert_eq!(expected, res);
+    }
+
     #[test]
     fn popupmenu_set_width() {
         let expected: Result<GnvimEvent, String> =
@@ -754,4 +1012,1670 @@ mod parse_gnvim_event_tests {
             assert_eq!(expected, res);
         }
     }
+
+    #[test]
+    fn set_inactive_dim() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetInactiveDim(0.3)),
+                vec!["SetInactiveDim".into(), 0.3.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetInactiveDim(0.0)),
+                vec!["SetInactiveDim".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn tabline_set_position() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::TablineSetPosition(TablinePosition::Top)),
+                vec!["TablineSetPosition".into(), "top".into()],
+            ),
+            (
+                Ok(GnvimEvent::TablineSetPosition(TablinePosition::Bottom)),
+                vec!["TablineSetPosition".into(), "bottom".into()],
+            ),
+            (
+                Err(String::from("Unknown tabline position: left")),
+                vec!["TablineSetPosition".into(), "left".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_virtual_text_style() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetVirtualTextStyle(VirtualTextStyle::Italic)),
+                vec!["SetVirtualTextStyle".into(), "italic".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetVirtualTextStyle(VirtualTextStyle::Dim)),
+                vec!["SetVirtualTextStyle".into(), "dim".into()],
+            ),
+            (
+                Err(String::from("Unknown virtual text style: bold")),
+                vec!["SetVirtualTextStyle".into(), "bold".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn font_zoom() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::FontZoom(1.0)),
+                vec!["FontZoom".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::FontZoom(-0.5)),
+                vec!["FontZoom".into(), (-0.5).into()],
+            ),
+            (Ok(GnvimEvent::FontZoomReset), vec!["FontZoomReset".into()]),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_invert_colors() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetInvertColors(true)),
+                vec!["SetInvertColors".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetInvertColors(false)),
+                vec!["SetInvertColors".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_mode_indicator() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetModeIndicator(true)),
+                vec!["SetModeIndicator".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetModeIndicator(false)),
+                vec!["SetModeIndicator".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn wildmenu_set_layout() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::WildmenuSetLayout(WildmenuLayout::Horizontal)),
+                vec!["WildmenuSetLayout".into(), "horizontal".into()],
+            ),
+            (
+                Ok(GnvimEvent::WildmenuSetLayout(WildmenuLayout::Vertical)),
+                vec!["WildmenuSetLayout".into(), "vertical".into()],
+            ),
+            (
+                Err(String::from("Unknown wildmenu layout: grid")),
+                vec!["WildmenuSetLayout".into(), "grid".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_unfocused_cursor() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetUnfocusedCursor(UnfocusedCursor::Hollow)),
+                vec!["SetUnfocusedCursor".into(), "hollow".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetUnfocusedCursor(UnfocusedCursor::Solid)),
+                vec!["SetUnfocusedCursor".into(), "solid".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetUnfocusedCursor(UnfocusedCursor::NoBlink)),
+                vec!["SetUnfocusedCursor".into(), "noblink".into()],
+            ),
+            (
+                Err(String::from("Unknown unfocused cursor: dashed")),
+                vec!["SetUnfocusedCursor".into(), "dashed".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_underline_style() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetUnderlineStyle(1.5, 2.0)),
+                vec!["SetUnderlineStyle".into(), 1.5.into(), 2.0.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetUnderlineStyle(3.0, -1.0)),
+                vec!["SetUnderlineStyle".into(), 3.into(), (-1).into()],
+            ),
+            (
+                Err(String::from("offset missing")),
+                vec!["SetUnderlineStyle".into(), 1.5.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_cursor_visible() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetCursorVisible(true)),
+                vec!["SetCursorVisible".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetCursorVisible(false)),
+                vec!["SetCursorVisible".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn popupmenu_info_scroll() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::PopupmenuInfoScroll(3)),
+                vec!["PopupmenuInfoScroll".into(), 3.into()],
+            ),
+            (
+                Ok(GnvimEvent::PopupmenuInfoScroll(-3)),
+                vec!["PopupmenuInfoScroll".into(), (-3).into()],
+            ),
+            (
+                Err(String::from("delta missing")),
+                vec!["PopupmenuInfoScroll".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_remember_geometry() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetRememberGeometry(true)),
+                vec!["SetRememberGeometry".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetRememberGeometry(false)),
+                vec!["SetRememberGeometry".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_box_drawing() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetBoxDrawing(true)),
+                vec!["SetBoxDrawing".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetBoxDrawing(false)),
+                vec!["SetBoxDrawing".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_letter_spacing() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetLetterSpacing(1.5)),
+                vec!["SetLetterSpacing".into(), 1.5.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetLetterSpacing(0.0)),
+                vec!["SetLetterSpacing".into(), 0.into()],
+            ),
+            (
+                Err(String::from("spacing missing")),
+                vec!["SetLetterSpacing".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn popupmenu_set_anchor() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::PopupmenuSetAnchor(5, 10, 1)),
+                vec![
+                    "PopupmenuSetAnchor".into(),
+                    5.into(),
+                    10.into(),
+                    1.into(),
+                ],
+            ),
+            (
+                Ok(GnvimEvent::PopupmenuClearAnchor),
+                vec!["PopupmenuClearAnchor".into()],
+            ),
+            (
+                Err(String::from("grid missing")),
+                vec!["PopupmenuSetAnchor".into(), 5.into(), 10.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn flash_region() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::FlashRegion(FlashRegion {
+                    grid: 1,
+                    ranges: vec![(2, 0, 10), (3, 0, 4)],
+                    color: Color::from_u64(0xff0000),
+                    duration: 300,
+                })),
+                vec![
+                    "FlashRegion".into(),
+                    1.into(),
+                    Value::Array(vec![
+                        Value::Array(vec![2.into(), 0.into(), 10.into()]),
+                        Value::Array(vec![3.into(), 0.into(), 4.into()]),
+                    ]),
+                    "#ff0000".into(),
+                    300.into(),
+                ],
+            ),
+            (
+                Err(String::from("Invalid flash range")),
+                vec![
+                    "FlashRegion".into(),
+                    1.into(),
+                    Value::Array(vec![Value::Array(vec![2.into()])]),
+                    "#ff0000".into(),
+                    300.into(),
+                ],
+            ),
+            (
+                Err(String::from("duration missing")),
+                vec![
+                    "FlashRegion".into(),
+                    1.into(),
+                    Value::Array(vec![]),
+                    "#ff0000".into(),
+                ],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_min_contrast() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetMinContrast(4.5)),
+                vec!["SetMinContrast".into(), 4.5.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetMinContrast(0.0)),
+                vec!["SetMinContrast".into(), 0.into()],
+            ),
+            (
+                Err(String::from("ratio missing")),
+                vec!["SetMinContrast".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn cmdline_block_max_height() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::CmdlineBlockMaxHeight(10)),
+                vec!["CmdlineBlockMaxHeight".into(), 10.into()],
+            ),
+            (
+                Err(String::from("rows missing")),
+                vec!["CmdlineBlockMaxHeight".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn paste() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> =
+            vec![(Ok(GnvimEvent::Paste), vec!["Paste".into()])];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_pixel_snap() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetPixelSnap(true)),
+                vec!["SetPixelSnap".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetPixelSnap(false)),
+                vec!["SetPixelSnap".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetPixelSnap".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_cursor_over_text() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetCursorOverText(true)),
+                vec!["SetCursorOverText".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetCursorOverText(false)),
+                vec!["SetCursorOverText".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetCursorOverText".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_render_whitespace() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetRenderWhitespace(RenderWhitespace::All)),
+                vec!["SetRenderWhitespace".into(), "all".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetRenderWhitespace(RenderWhitespace::None)),
+                vec!["SetRenderWhitespace".into(), "none".into()],
+            ),
+            (
+                Err(String::from("Unknown render whitespace: trailing")),
+                vec!["SetRenderWhitespace".into(), "trailing".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_extra_cursors() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetExtraCursors(vec![(1, 2, 3), (4, 5, 6)])),
+                vec![
+                    "SetExtraCursors".into(),
+                    Value::Array(vec![
+                        Value::Array(vec![1.into(), 2.into(), 3.into()]),
+                        Value::Array(vec![4.into(), 5.into(), 6.into()]),
+                    ]),
+                ],
+            ),
+            (
+                Ok(GnvimEvent::SetExtraCursors(vec![])),
+                vec!["SetExtraCursors".into(), Value::Array(vec![])],
+            ),
+            (
+                Err(String::from("Invalid cursor position")),
+                vec![
+                    "SetExtraCursors".into(),
+                    Value::Array(vec![Value::Array(vec![1.into()])]),
+                ],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_resize_debounce() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetResizeDebounce(100)),
+                vec!["SetResizeDebounce".into(), 100.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetResizeDebounce(0)),
+                vec!["SetResizeDebounce".into(), 0.into()],
+            ),
+            (
+                Err(String::from("delay missing")),
+                vec!["SetResizeDebounce".into()],
+            ),
+            (
+                Err(String::from("resize debounce out of range")),
+                vec!["SetResizeDebounce".into(), u64::MAX.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_scroll_cache() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetScrollCache(true)),
+                vec!["SetScrollCache".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetScrollCache(false)),
+                vec!["SetScrollCache".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetScrollCache".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_respect_mouse_option() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetRespectMouseOption(true)),
+                vec!["SetRespectMouseOption".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetRespectMouseOption(false)),
+                vec!["SetRespectMouseOption".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetRespectMouseOption".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_gui_selection() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetGuiSelection(true)),
+                vec!["SetGuiSelection".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetGuiSelection(false)),
+                vec!["SetGuiSelection".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetGuiSelection".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn cmdline_set_position() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::CmdlineSetPosition(0.5)),
+                vec!["CmdlineSetPosition".into(), 0.5.into()],
+            ),
+            (
+                Ok(GnvimEvent::CmdlineSetPosition(1.0)),
+                vec!["CmdlineSetPosition".into(), 1.into()],
+            ),
+            (
+                Err(String::from("position missing")),
+                vec!["CmdlineSetPosition".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_busy_spinner() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetBusySpinner(true)),
+                vec!["SetBusySpinner".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetBusySpinner(false)),
+                vec!["SetBusySpinner".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetBusySpinner".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn popupmenu_set_max_width_px() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::PopupmenuMaxWidthPx(400)),
+                vec!["PopupmenuSetMaxWidthPx".into(), 400.into()],
+            ),
+            (
+                Err(String::from("width missing")),
+                vec!["PopupmenuSetMaxWidthPx".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn tabline_set_mode() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::TablineMode(TablineMode::Tabs)),
+                vec!["TablineSetMode".into(), "tabs".into()],
+            ),
+            (
+                Ok(GnvimEvent::TablineMode(TablineMode::Buffers)),
+                vec!["TablineSetMode".into(), "buffers".into()],
+            ),
+            (
+                Err(String::from("Unknown tabline mode: windows")),
+                vec!["TablineSetMode".into(), "windows".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn place_image() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::PlaceImage(PlaceImage {
+                    grid: 1,
+                    row: 2,
+                    col: 3,
+                    width: 10,
+                    height: 5,
+                    path: String::from("/tmp/image.png"),
+                })),
+                vec![
+                    "PlaceImage".into(),
+                    1.into(),
+                    2.into(),
+                    3.into(),
+                    10.into(),
+                    5.into(),
+                    "/tmp/image.png".into(),
+                ],
+            ),
+            (
+                Err(String::from("path missing")),
+                vec![
+                    "PlaceImage".into(),
+                    1.into(),
+                    2.into(),
+                    3.into(),
+                    10.into(),
+                    5.into(),
+                ],
+            ),
+            (Ok(GnvimEvent::ClearImages), vec!["ClearImages".into()]),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_input_method() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetInputMethod(true)),
+                vec!["SetInputMethod".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetInputMethod(false)),
+                vec!["SetInputMethod".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetInputMethod".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_natural_scroll() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetNaturalScroll(true)),
+                vec!["SetNaturalScroll".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetNaturalScroll(false)),
+                vec!["SetNaturalScroll".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetNaturalScroll".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_dim_inactive_lines() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetDimInactiveLines(2, 3, 0.5)),
+                vec![
+                    "SetDimInactiveLines".into(),
+                    2.into(),
+                    3.into(),
+                    0.5.into(),
+                ],
+            ),
+            (
+                Err(String::from("amount missing")),
+                vec!["SetDimInactiveLines".into(), 2.into(), 3.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_min_size() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetMinSize(20, 5)),
+                vec!["SetMinSize".into(), 20.into(), 5.into()],
+            ),
+            (
+                Err(String::from("rows missing")),
+                vec!["SetMinSize".into(), 20.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_popupmenu_animation() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetPopupmenuAnimation(true)),
+                vec!["SetPopupmenuAnimation".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetPopupmenuAnimation(false)),
+                vec!["SetPopupmenuAnimation".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetPopupmenuAnimation".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn popupmenu_selection_pulse() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::PopupmenuSelectionPulse(true)),
+                vec!["PopupmenuSelectionPulse".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::PopupmenuSelectionPulse(false)),
+                vec!["PopupmenuSelectionPulse".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_grid_font() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetGridFont(3, String::from("monospace:h9"))),
+                vec!["SetGridFont".into(), 3.into(), "monospace:h9".into()],
+            ),
+            (
+                Err(String::from("font missing")),
+                vec!["SetGridFont".into(), 3.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_winbar() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetWinbar(Winbar {
+                    grid: 4,
+                    chunks: vec![
+                        (
+                            Highlight {
+                                foreground: Some(Color::from_u64(0xff0000)),
+                                bold: true,
+                                ..Highlight::default()
+                            },
+                            String::from("foo"),
+                        ),
+                        (Highlight::default(), String::from("bar")),
+                    ],
+                })),
+                vec![
+                    "SetWinbar".into(),
+                    4.into(),
+                    Value::Array(vec![
+                        Value::Array(vec![
+                            "foo".into(),
+                            Value::Map(vec![
+                                ("foreground".into(), 0xff0000.into()),
+                                ("bold".into(), true.into()),
+                            ]),
+                        ]),
+                        Value::Array(vec!["bar".into(), Value::Map(vec![])]),
+                    ]),
+                ],
+            ),
+            (
+                Ok(GnvimEvent::SetWinbar(Winbar {
+                    grid: 4,
+                    chunks: vec![],
+                })),
+                vec!["SetWinbar".into(), 4.into(), Value::Array(vec![])],
+            ),
+            (
+                Err(String::from("Invalid winbar chunk")),
+                vec![
+                    "SetWinbar".into(),
+                    4.into(),
+                    Value::Array(vec![Value::Array(vec!["foo".into()])]),
+                ],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_zoom_animation() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetZoomAnimation(true)),
+                vec!["SetZoomAnimation".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetZoomAnimation(false)),
+                vec!["SetZoomAnimation".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetZoomAnimation".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_clipboard_sync() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetClipboardSync(true)),
+                vec!["SetClipboardSync".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::ClipboardSetText(String::from("foo\nbar"))),
+                vec!["ClipboardSetText".into(), "foo\nbar".into()],
+            ),
+            (
+                Err(String::from("text missing")),
+                vec!["ClipboardSetText".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_line_scale() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetLineScale(2, 5, 2)),
+                vec!["SetLineScale".into(), 2.into(), 5.into(), 2.into()],
+            ),
+            (
+                Err(String::from("scale missing")),
+                vec!["SetLineScale".into(), 2.into(), 5.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_pending_keys() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetPendingKeys(String::from("<Space>f"))),
+                vec!["SetPendingKeys".into(), "<Space>f".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetPendingKeys(String::new())),
+                vec!["SetPendingKeys".into(), "".into()],
+            ),
+            (
+                Err(String::from("keys missing")),
+                vec!["SetPendingKeys".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn cmdline_set_border() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::CmdlineSetBorder(true)),
+                vec!["CmdlineSetBorder".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::CmdlineSetBorder(false)),
+                vec!["CmdlineSetBorder".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn tabline_set_font() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::TablineSetFont(String::from("Cantarell:h10"))),
+                vec!["TablineSetFont".into(), "Cantarell:h10".into()],
+            ),
+            (
+                Err(String::from("font missing")),
+                vec!["TablineSetFont".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_typewriter_mode() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetTypewriterMode(true)),
+                vec!["SetTypewriterMode".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetTypewriterMode(false)),
+                vec!["SetTypewriterMode".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn cmdline_set_cursor_blink() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::CmdlineSetCursorBlink(500, 300)),
+                vec!["CmdlineSetCursorBlink".into(), 500.into(), 300.into()],
+            ),
+            (
+                Err(String::from("off missing")),
+                vec!["CmdlineSetCursorBlink".into(), 500.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_cell_width_scale() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetCellWidthScale(1.1)),
+                vec!["SetCellWidthScale".into(), 1.1.into()],
+            ),
+            (
+                Err(String::from("scale missing")),
+                vec!["SetCellWidthScale".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_active_border() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetActiveBorder(Color::from_u64(0xff0000), 2.0)),
+                vec!["SetActiveBorder".into(), "#ff0000".into(), 2.0.into()],
+            ),
+            (
+                Err(String::from("width missing")),
+                vec!["SetActiveBorder".into(), "#ff0000".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_letterbox_color() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetLetterboxColor(Some(Color::from_u64(
+                    0x00ff00,
+                )))),
+                vec!["SetLetterboxColor".into(), "#00ff00".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetLetterboxColor(None)),
+                vec!["SetLetterboxColor".into(), "".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn tabline_set_label_format() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::TablineSetLabelFormat(
+                    TablineLabelFormat::Shortened,
+                )),
+                vec!["TablineSetLabelFormat".into(), "shortened".into()],
+            ),
+            (
+                Err(String::from("Unknown tabline label format: foo")),
+                vec!["TablineSetLabelFormat".into(), "foo".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_scroll_target() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetScrollTarget(ScrollTarget::Pointer)),
+                vec!["SetScrollTarget".into(), "pointer".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetScrollTarget(ScrollTarget::Active)),
+                vec!["SetScrollTarget".into(), "active".into()],
+            ),
+            (
+                Err(String::from("Unknown scroll target: foo")),
+                vec!["SetScrollTarget".into(), "foo".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_animation_fps() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetAnimationFps(30)),
+                vec!["SetAnimationFps".into(), 30.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetAnimationFps(0)),
+                vec!["SetAnimationFps".into(), 0.into()],
+            ),
+            (
+                Err(String::from("fps missing")),
+                vec!["SetAnimationFps".into()],
+            ),
+            (
+                Err(String::from("animation fps out of range")),
+                vec!["SetAnimationFps".into(), u64::MAX.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_forwarded_mouse_buttons() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetForwardedMouseButtons(MouseButtons {
+                    left: true,
+                    middle: false,
+                    right: false,
+                    extra: true,
+                })),
+                vec![
+                    "SetForwardedMouseButtons".into(),
+                    vec![Value::from("left"), Value::from("extra")].into(),
+                ],
+            ),
+            (
+                Ok(GnvimEvent::SetForwardedMouseButtons(MouseButtons {
+                    left: false,
+                    middle: false,
+                    right: false,
+                    extra: false,
+                })),
+                vec!["SetForwardedMouseButtons".into(), Value::Array(vec![])],
+            ),
+            (
+                Err(String::from("Unknown mouse button: foo")),
+                vec![
+                    "SetForwardedMouseButtons".into(),
+                    vec![Value::from("foo")].into(),
+                ],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_context_menu() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetContextMenu(true)),
+                vec!["SetContextMenu".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetContextMenu(false)),
+                vec!["SetContextMenu".into(), 0.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn context_menu_set_items() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::ContextMenuSetItems(vec![
+                    (String::from("Copy"), String::from("\"+y")),
+                    (String::from("Save"), String::from(":w<CR>")),
+                ])),
+                vec![
+                    "ContextMenuSetItems".into(),
+                    vec![
+                        Value::from(vec![
+                            Value::from("Copy"),
+                            Value::from("\"+y"),
+                        ]),
+                        Value::from(vec![
+                            Value::from("Save"),
+                            Value::from(":w<CR>"),
+                        ]),
+                    ]
+                    .into(),
+                ],
+            ),
+            (
+                Err(String::from("Invalid menu item")),
+                vec![
+                    "ContextMenuSetItems".into(),
+                    vec![Value::from(vec![Value::from("Copy")])].into(),
+                ],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_cursor_outline() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetCursorOutline(
+                    Color::from_u64(0x000000),
+                    1.0,
+                )),
+                vec!["SetCursorOutline".into(), "#000000".into(), 1.0.into()],
+            ),
+            (
+                Err(String::from("width missing")),
+                vec!["SetCursorOutline".into(), "#000000".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_grid_lines() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetGridLines(true, Color::from_u64(0xff00ff))),
+                vec!["SetGridLines".into(), 1.into(), "#ff00ff".into()],
+            ),
+            (
+                Ok(GnvimEvent::SetGridLines(false, Color::from_u64(0xff00ff))),
+                vec!["SetGridLines".into(), 0.into(), "#ff00ff".into()],
+            ),
+            (
+                Err(String::from("color missing")),
+                vec!["SetGridLines".into(), 1.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_text_shadow() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetTextShadow(
+                    Color::from_u64(0x000000),
+                    1.0,
+                    2.0,
+                    0.0,
+                )),
+                vec![
+                    "SetTextShadow".into(),
+                    "#000000".into(),
+                    1.0.into(),
+                    2.0.into(),
+                    0.0.into(),
+                ],
+            ),
+            (
+                Err(String::from("blur missing")),
+                vec![
+                    "SetTextShadow".into(),
+                    "#000000".into(),
+                    1.0.into(),
+                    2.0.into(),
+                ],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn reset_gnvim_settings() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![(
+            Ok(GnvimEvent::ResetGnvimSettings),
+            vec!["ResetGnvimSettings".into()],
+        )];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_defer_colors_redraw() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetDeferColorsRedraw(true)),
+                vec!["SetDeferColorsRedraw".into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetDeferColorsRedraw(false)),
+                vec!["SetDeferColorsRedraw".into(), 0.into()],
+            ),
+            (
+                Err(String::from("argument missing")),
+                vec!["SetDeferColorsRedraw".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn popupmenu_set_style() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::PopupmenuSetStyle(6, true)),
+                vec!["PopupmenuSetStyle".into(), 6.into(), 1.into()],
+            ),
+            (
+                Ok(GnvimEvent::PopupmenuSetStyle(0, false)),
+                vec!["PopupmenuSetStyle".into(), 0.into(), 0.into()],
+            ),
+            (
+                Err(String::from("shadow missing")),
+                vec!["PopupmenuSetStyle".into(), 6.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_cursor_blink_for_shape() {
+        let data: Vec<(Result<GnvimEvent, String>, Vec<Value>)> = vec![
+            (
+                Ok(GnvimEvent::SetCursorBlinkForShape(
+                    CursorShape::Vertical,
+                    400,
+                    250,
+                    700,
+                )),
+                vec![
+                    "SetCursorBlinkForShape".into(),
+                    "vertical".into(),
+                    400.into(),
+                    250.into(),
+                    700.into(),
+                ],
+            ),
+            (
+                Err(String::from("Unknown cursor shape: round")),
+                vec![
+                    "SetCursorBlinkForShape".into(),
+                    "round".into(),
+                    400.into(),
+                    250.into(),
+                    700.into(),
+                ],
+            ),
+            (
+                Err(String::from("blink wait missing")),
+                vec![
+                    "SetCursorBlinkForShape".into(),
+                    "block".into(),
+                    0.into(),
+                    0.into(),
+                ],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_scroll_indicator() {
+        let data = vec![
+            (
+                Ok(GnvimEvent::SetScrollIndicator(120, 3400)),
+                vec!["SetScrollIndicator".into(), 120.into(), 3400.into()],
+            ),
+            (
+                Ok(GnvimEvent::SetScrollIndicator(0, 0)),
+                vec!["SetScrollIndicator".into(), 0.into(), 0.into()],
+            ),
+            (
+                Err(String::from("total missing")),
+                vec!["SetScrollIndicator".into(), 120.into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
+
+    #[test]
+    fn set_whitespace_glyphs() {
+        let data = vec![
+            (
+                Ok(GnvimEvent::SetWhitespaceGlyphs('·', '→', '␣')),
+                vec![
+                    "SetWhitespaceGlyphs".into(),
+                    "·".into(),
+                    "→".into(),
+                    "␣".into(),
+                ],
+            ),
+            (
+                Err(String::from("Glyph is not a single character: \"->\"")),
+                vec![
+                    "SetWhitespaceGlyphs".into(),
+                    "·".into(),
+                    "->".into(),
+                    "␣".into(),
+                ],
+            ),
+            (
+                Err(String::from("Glyph is not a single character: \"\"")),
+                vec![
+                    "SetWhitespaceGlyphs".into(),
+                    "".into(),
+                    "→".into(),
+                    "␣".into(),
+                ],
+            ),
+            (
+                Err(String::from("nbsp glyph missing")),
+                vec!["SetWhitespaceGlyphs".into(), "·".into(), "→".into()],
+            ),
+        ];
+
+        for (expected, input) in data.into_iter() {
+            let res = nvim_bridge::parse_gnvim_event(input);
+
+            assert_eq!(expected, res);
+        }
+    }
 }
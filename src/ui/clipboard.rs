@@ -0,0 +1,104 @@
+use log::error;
+
+use crate::nvim_gio::GioNeovim;
+use crate::ui::common::{spawn_local, vim_string};
+
+/// Pastes the contents of the clipboard to nvim. Text is pasted with
+/// `nvim_paste`. For images and files, the `User GnvimPaste` autocmd is
+/// executed (see `paste_command`). Images are saved to a temporary file.
+pub fn paste(nvim: GioNeovim) {
+    let clipboard = gtk::Clipboard::get(&gdk::SELECTION_CLIPBOARD);
+
+    clipboard.request_targets(move |clipboard, targets| {
+        if gtk::targets_include_image(targets, false) {
+            clipboard.request_image(move |_, image| {
+                let path = std::env::temp_dir()
+                    .join(format!("gnvim-paste-{}.png", glib::get_real_time()));
+
+                if let Err(err) = image.savev(&path, "png", &[]) {
+                    error!("Failed to save pasted image: {}", err);
+                    return;
+                }
+
+                let path = path.to_string_lossy().into_owned();
+                run_command(nvim, paste_command("image/png", &[path]));
+            });
+        } else if gtk::targets_include_uri(targets) {
+            clipboard.request_uris(move |_, uris| {
+                let paths = uris
+                    .iter()
+                    .filter_map(|uri| glib::filename_from_uri(uri).ok())
+                    .map(|(path, _)| path.to_string_lossy().into_owned())
+                    .collect::<Vec<String>>();
+
+                if !paths.is_empty() {
+                    run_command(nvim, paste_command("text/uri-list", &paths));
+                }
+            });
+        } else {
+            clipboard.request_text(move |_, text| {
+                let text = match text {
+                    Some(text) => text.to_owned(),
+                    None => return,
+                };
+
+                spawn_local(async move {
+                    if let Err(err) = nvim.paste(&text, true, -1).await {
+                        error!("Failed to paste: {:?}", err);
+                    }
+                });
+            });
+        }
+    });
+}
+
+/// Sets `text` to the clipboard.
+pub fn set_text(text: &str) {
+    gtk::Clipboard::get(&gdk::SELECTION_CLIPBOARD).set_text(text);
+}
+
+fn run_command(nvim: GioNeovim, cmd: String) {
+    spawn_local(async move {
+        if let Err(err) = nvim.command(&cmd).await {
+            error!("Failed to paste: {:?}", err);
+        }
+    });
+}
+
+/// Returns a command that stores the pasted content's mime type and paths
+/// in `g:gnvim_paste` and executes the `User GnvimPaste` autocmd. If there
+/// is no such autocmd, the paths are pasted as text.
+fn paste_command(mime: &str, paths: &[String]) -> String {
+    let paths = paths
+        .iter()
+        .map(String::as_str)
+        .map(vim_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    format!(
+        "let g:gnvim_paste = {{'mime': {}, 'paths': [{}]}} \
+        | if exists('#User#GnvimPaste') \
+        | doautocmd User GnvimPaste \
+        | else \
+        | call nvim_paste(join(g:gnvim_paste.paths, \"\\n\"), v:true, -1) \
+        | endif",
+        vim_string(mime),
+        paths
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_command() {
+        let cmd = paste_command("image/png", &[String::from("/tmp/a.png")]);
+
+        assert!(cmd.starts_with(
+            "let g:gnvim_paste = \
+            {'mime': 'image/png', 'paths': ['/tmp/a.png']} | if"
+        ));
+    }
+}
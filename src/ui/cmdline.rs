@@ -1,3 +1,6 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
 use gtk::prelude::*;
 
 use crate::nvim_bridge;
@@ -8,6 +11,8 @@ use crate::ui::font::{Font, FontUnit};
 use crate::ui::wildmenu::Wildmenu;
 
 const MAX_WIDTH: i32 = 650;
+/// Default max height of the cmdline block, in pixels.
+const BLOCK_MAX_HEIGHT: i32 = 250;
 
 #[derive(Default)]
 pub struct CmdlineColors {
@@ -23,6 +28,10 @@ struct CmdlineBlock {
     textview: gtk::TextView,
 
     css_provider: gtk::CssProvider,
+
+    /// Max height of the block in rows. After this, the block is scrolled.
+    /// If `None`, `BLOCK_MAX_HEIGHT` is used.
+    max_rows: Rc<Cell<Option<u64>>>,
 }
 
 impl CmdlineBlock {
@@ -43,15 +52,23 @@ impl CmdlineBlock {
         scrolledwindow.add(&textview);
         frame.add(&scrolledwindow);
 
+        let max_rows = Rc::new(Cell::new(None));
+
         let scrolledwindow_weak = scrolledwindow.downgrade();
         textview.connect_size_allocate(
-            clone!(scrolledwindow_weak => move |tv, _| {
+            clone!(scrolledwindow_weak, max_rows => move |tv, _| {
                 let scrolledwindow = upgrade_weak!(scrolledwindow_weak);
                 let h = tv.get_preferred_height();
 
-                if h.1 > 250 {
-                    if scrolledwindow.get_size_request().1 == -1 {
-                        scrolledwindow.set_size_request(-1, h.1);
+                let max_height = match max_rows.get() {
+                    Some(rows) => rows as i32 * line_height(tv),
+                    None => BLOCK_MAX_HEIGHT,
+                };
+
+                if h.1 > max_height {
+                    // The max height might've changed since the cap was set.
+                    if scrolledwindow.get_size_request().1 != max_height {
+                        scrolledwindow.set_size_request(-1, max_height);
                         scrolledwindow.set_policy(
                             gtk::PolicyType::Automatic,
                             gtk::PolicyType::Automatic,
@@ -71,9 +88,15 @@ impl CmdlineBlock {
             scrolledwindow,
             textview,
             css_provider,
+            max_rows,
         }
     }
 
+    /// Sets the max height of the block in rows. Zero restores the default.
+    fn set_max_rows(&self, rows: u64) {
+        self.max_rows.set(if rows == 0 { None } else { Some(rows) });
+    }
+
     fn set_line_space(&self, space: i64) {
         let (above, below) = calc_line_space(space);
         self.textview.set_pixels_above_lines(above as i32);
@@ -222,6 +245,12 @@ struct CmdlineInput {
     cursor_pos: usize,
     /// Level from the latest `cmdline_show`.
     current_level: u64,
+
+    /// Durations (in milliseconds) the cursor is shown and hidden when
+    /// blinking. `None` leaves the blinking to gtk.
+    blink: Option<(u64, u64)>,
+    /// Source of the next blink phase change.
+    blink_source: Rc<RefCell<Option<glib::SourceId>>>,
 }
 
 impl CmdlineInput {
@@ -256,6 +285,9 @@ impl CmdlineInput {
             prompt_len: 0,
             cursor_pos: 0,
             current_level: 0,
+
+            blink: None,
+            blink_source: Rc::new(RefCell::new(None)),
         }
     }
 
@@ -373,6 +405,31 @@ impl CmdlineInput {
 
         self.cursor_pos = pos;
         self.ensure_cursor_pos();
+        self.restart_blink();
+    }
+
+    /// Sets the cursor's blink durations (in milliseconds). Zero for either
+    /// of them disables blinking.
+    fn set_cursor_blink(&mut self, on: u64, off: u64) {
+        self.blink = Some((on, off));
+        self.restart_blink();
+    }
+
+    /// Shows the cursor and starts the blinking from the beginning.
+    fn restart_blink(&self) {
+        self.stop_blink();
+        self.textview.set_cursor_visible(true);
+
+        let blink = self.blink.filter(|(on, off)| *on > 0 && *off > 0);
+        if let Some((on, off)) = blink {
+            schedule_blink(&self.textview, &self.blink_source, on, off, true);
+        }
+    }
+
+    fn stop_blink(&self) {
+        if let Some(id) = self.blink_source.borrow_mut().take() {
+            glib::source::source_remove(id);
+        }
     }
 
     fn ensure_cursor_pos(&self) {
@@ -398,6 +455,12 @@ impl CmdlineInput {
 pub struct Cmdline {
     css_provider: gtk::CssProvider,
     fixed: gtk::Fixed,
+    /// Container for the whole cmdline, positioned in `fixed`.
+    box_: gtk::Box,
+    /// Overlay where the cmdline is shown on.
+    parent: gtk::Overlay,
+    /// Vertical position of the cmdline, from 0.0 (top) to 1.0 (bottom).
+    position: Rc<Cell<f64>>,
 
     input: CmdlineInput,
     block: CmdlineBlock,
@@ -409,6 +472,8 @@ pub struct Cmdline {
     show_wildmenu: bool,
 
     colors: CmdlineColors,
+    /// If a border is drawn around the cmdline.
+    border: bool,
     /// Our font. This is inherited to input, block and wildmenu through our
     /// styles.
     font: Font,
@@ -445,23 +510,32 @@ impl Cmdline {
 
         parent.add_overlay(&fixed);
 
-        parent.connect_size_allocate(clone!(fixed, box_ => move |_, alloc| {
-            // Make sure we'll fit to the available space.
-            let width = MAX_WIDTH.min(alloc.width);
-            box_.set_size_request(width, -1);
+        let position = Rc::new(Cell::new(0.0));
 
-            let x = alloc.width / 2 - width / 2;
-            fixed.move_(&box_, x, 0);
-        }));
+        parent.connect_size_allocate(
+            clone!(fixed, box_, position => move |_, alloc| {
+                move_box(
+                    &fixed,
+                    &box_,
+                    alloc.width,
+                    alloc.height,
+                    position.get(),
+                );
+            }),
+        );
 
         Cmdline {
             css_provider,
             fixed,
+            box_,
+            parent: parent.clone(),
+            position,
             input,
             block,
             wildmenu,
             show_block: false,
             show_wildmenu: false,
+            border: false,
             font: Font::default(),
             colors: CmdlineColors::default(),
         }
@@ -492,6 +566,12 @@ impl Cmdline {
         self.set_styles(hl_defs);
     }
 
+    /// Sets if a border is drawn around the cmdline.
+    pub fn set_border(&mut self, border: bool, hl_defs: &HlDefs) {
+        self.border = border;
+        self.set_styles(hl_defs);
+    }
+
     fn set_styles(&self, hl_defs: &HlDefs) {
         if gtk::get_minor_version() < 20 {
             self.set_styles_pre20(hl_defs);
@@ -500,7 +580,20 @@ impl Cmdline {
         }
     }
 
+    /// Returns the css for the optional border and the padding of the frame.
+    /// The padding is reduced by the border's width, so the border doesn't
+    /// move the content.
+    fn border_css(&self, hl_defs: &HlDefs) -> (String, u64) {
+        if self.border {
+            let color = self.colors.fg.unwrap_or(hl_defs.default_fg);
+            (format!("border: 1px solid #{};", color.to_hex()), 5)
+        } else {
+            (String::from("border: none;"), 6)
+        }
+    }
+
     fn set_styles_post20(&self, hl_defs: &HlDefs) {
+        let (border, padding) = self.border_css(hl_defs);
         let css = format!(
             "{font_wild}
 
@@ -510,7 +603,8 @@ impl Cmdline {
 
             frame {{
                 background: #{bg};
-                padding: 6px;
+                padding: {padding}px;
+                {border}
             }}
 
             box {{
@@ -522,13 +616,16 @@ impl Cmdline {
             }}
             ",
             font_wild = self.font.as_wild_css(FontUnit::Point),
-            bg = self.colors.border.unwrap_or(hl_defs.default_bg).to_hex()
+            bg = self.colors.border.unwrap_or(hl_defs.default_bg).to_hex(),
+            padding = padding,
+            border = border,
         );
         CssProviderExt::load_from_data(&self.css_provider, css.as_bytes())
             .unwrap();
     }
 
     fn set_styles_pre20(&self, hl_defs: &HlDefs) {
+        let (border, padding) = self.border_css(hl_defs);
         let css = format!(
             "{font_wild}
 
@@ -542,12 +639,14 @@ impl Cmdline {
 
             GtkFrame {{
                 background: #{bg};
-                padding: 6px;
-                border: none;
+                padding: {padding}px;
+                {border}
                 border-radius: 0;
             }}",
             font_wild = self.font.as_wild_css(FontUnit::Pixel),
-            bg = self.colors.border.unwrap_or(hl_defs.default_bg).to_hex()
+            bg = self.colors.border.unwrap_or(hl_defs.default_bg).to_hex(),
+            padding = padding,
+            border = border,
         );
         CssProviderExt::load_from_data(&self.css_provider, css.as_bytes())
             .unwrap();
@@ -555,6 +654,7 @@ impl Cmdline {
 
     pub fn hide(&self) {
         self.fixed.hide();
+        self.input.stop_blink();
     }
 
     pub fn show(
@@ -572,6 +672,25 @@ impl Cmdline {
         if !self.show_wildmenu {
             self.wildmenu.hide();
         }
+
+        self.update_position();
+    }
+
+    /// Sets the vertical position of the cmdline, from 0.0 (top) to 1.0
+    /// (bottom).
+    pub fn set_position(&self, position: f64) {
+        self.position.set(position.max(0.0).min(1.0));
+        self.update_position();
+    }
+
+    fn update_position(&self) {
+        move_box(
+            &self.fixed,
+            &self.box_,
+            self.parent.get_allocated_width(),
+            self.parent.get_allocated_height(),
+            self.position.get(),
+        );
     }
 
     pub fn show_special_char(&mut self, ch: String, shift: bool, level: u64) {
@@ -601,6 +720,12 @@ impl Cmdline {
         self.input.set_cursor(pos as usize, level);
     }
 
+    /// Sets the cursor's blink durations (in milliseconds), independently
+    /// from the grid cursor. Zero for either of them disables blinking.
+    pub fn set_cursor_blink(&mut self, on: u64, off: u64) {
+        self.input.set_cursor_blink(on, off);
+    }
+
     pub fn show_block(
         &mut self,
         show: &nvim_bridge::CmdlineBlockShow,
@@ -615,6 +740,12 @@ impl Cmdline {
         self.show_block = false;
     }
 
+    /// Sets the max height of the cmdline block in rows. Zero restores the
+    /// default.
+    pub fn set_block_max_rows(&self, rows: u64) {
+        self.block.set_max_rows(rows);
+    }
+
     pub fn block_append(
         &mut self,
         line: nvim_bridge::CmdlineBlockAppend,
@@ -643,7 +774,63 @@ impl Cmdline {
         self.wildmenu.select(item_num);
     }
 
+    pub fn wildmenu_set_layout(&mut self, layout: nvim_bridge::WildmenuLayout) {
+        self.wildmenu.set_layout(layout);
+    }
+
     pub fn wildmenu_set_colors(&self, hl_defs: &HlDefs) {
         self.wildmenu.set_colors(hl_defs);
     }
 }
+
+/// Toggles the cursor's visibility in `textview` after `on` (when `visible`)
+/// or `off` milliseconds, and keeps doing so.
+fn schedule_blink(
+    textview: &gtk::TextView,
+    source: &Rc<RefCell<Option<glib::SourceId>>>,
+    on: u64,
+    off: u64,
+    visible: bool,
+) {
+    let interval = if visible { on } else { off };
+    let id = gtk::timeout_add(
+        interval as u32,
+        clone!(textview, source => move || {
+            textview.set_cursor_visible(!visible);
+            schedule_blink(&textview, &source, on, off, !visible);
+            Continue(false)
+        }),
+    );
+    source.replace(Some(id));
+}
+
+/// Height of a single line in `textview`, including the line space.
+fn line_height(textview: &gtk::TextView) -> i32 {
+    let metrics = textview
+        .get_pango_context()
+        .get_metrics(None, None)
+        .unwrap();
+    (metrics.get_ascent() + metrics.get_descent()) / pango::SCALE
+        + textview.get_pixels_above_lines()
+        + textview.get_pixels_below_lines()
+}
+
+/// Moves `box_` to its place in `fixed`, when the available space is
+/// `width` x `height`. `position` is the vertical position, from 0.0 (top)
+/// to 1.0 (bottom).
+fn move_box(
+    fixed: &gtk::Fixed,
+    box_: &gtk::Box,
+    width: i32,
+    height: i32,
+    position: f64,
+) {
+    // Make sure we'll fit to the available space.
+    let box_width = MAX_WIDTH.min(width);
+    box_.set_size_request(box_width, -1);
+
+    let (_, box_height) = box_.get_preferred_height();
+    let x = width / 2 - box_width / 2;
+    let y = (f64::from((height - box_height).max(0)) * position) as i32;
+    fixed.move_(box_, x, y);
+}
@@ -18,6 +18,99 @@ pub enum HlGroup {
     MsgSeparator,
 }
 
+/// How cells that belong to virtual text are rendered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VirtualTextStyle {
+    /// Render virtual text like any other text.
+    None,
+    Italic,
+    /// Blend the foreground towards the background.
+    Dim,
+}
+
+impl Default for VirtualTextStyle {
+    fn default() -> Self {
+        VirtualTextStyle::None
+    }
+}
+
+impl VirtualTextStyle {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "none" => Ok(VirtualTextStyle::None),
+            "italic" => Ok(VirtualTextStyle::Italic),
+            "dim" => Ok(VirtualTextStyle::Dim),
+            _ => Err(format!("Unknown virtual text style: {}", s)),
+        }
+    }
+}
+
+/// Which whitespace gnvim renders visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderWhitespace {
+    None,
+    /// All spaces, except the ones at the end of a row.
+    All,
+}
+
+impl Default for RenderWhitespace {
+    fn default() -> Self {
+        RenderWhitespace::None
+    }
+}
+
+impl RenderWhitespace {
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        match s {
+            "none" => Ok(RenderWhitespace::None),
+            "all" => Ok(RenderWhitespace::All),
+            _ => Err(format!("Unknown render whitespace: {}", s)),
+        }
+    }
+}
+
+/// Glyphs drawn for the whitespace, in place of the default faint dots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WhitespaceGlyphs {
+    pub space: char,
+    /// Drawn for non-breaking spaces, which aren't rendered with the dots.
+    pub nbsp: char,
+}
+
+/// Drop shadow drawn under the text of the grids.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextShadow {
+    pub color: Color,
+    /// Horizontal offset of the shadow, in pixels.
+    pub dx: f64,
+    /// Vertical offset of the shadow, in pixels.
+    pub dy: f64,
+    /// Blur radius of the shadow, in pixels. Zero gives a crisp shadow.
+    pub blur: f64,
+}
+
+impl TextShadow {
+    /// Offsets and alphas of the copies of the text to draw for the shadow,
+    /// as (dx, dy, alpha). Cairo can't blur, so a blur is approximated by
+    /// spreading translucent copies around the offset.
+    pub fn samples(&self) -> Vec<(f64, f64, f64)> {
+        if self.blur <= 0.0 {
+            return vec![(self.dx, self.dy, 1.0)];
+        }
+
+        let r = self.blur / 2.0;
+        let mut samples = vec![];
+        for i in -1..=1 {
+            for j in -1..=1 {
+                let (i, j) = (f64::from(i), f64::from(j));
+                samples.push((self.dx + i * r, self.dy + j * r, 0.25));
+            }
+        }
+
+        samples
+    }
+}
+
 #[derive(Default)]
 pub struct HlDefs {
     hl_defs: HashMap<u64, Highlight>,
@@ -27,6 +120,21 @@ pub struct HlDefs {
     pub default_fg: Color,
     pub default_bg: Color,
     pub default_sp: Color,
+
+    pub virtual_text_style: VirtualTextStyle,
+    /// If box drawing characters should be drawn with our own glyphs.
+    pub box_drawing: bool,
+    /// Minimum contrast ratio between the foreground and background colors.
+    /// Zero means no adjustment.
+    pub min_contrast: f64,
+    /// If text should be positioned at whole device pixels.
+    pub pixel_snap: bool,
+    /// Which whitespace to render visible.
+    pub render_whitespace: RenderWhitespace,
+    /// Glyphs for the rendered whitespace. `None` draws dots.
+    pub whitespace_glyphs: Option<WhitespaceGlyphs>,
+    /// Shadow drawn under the text, if any.
+    pub text_shadow: Option<TextShadow>,
 }
 
 impl HlDefs {
@@ -66,9 +174,20 @@ pub struct Highlight {
     pub bold: bool,
     pub underline: bool,
     pub undercurl: bool,
+    /// Drawn in the special color if the highlight has one, otherwise in
+    /// the foreground color.
+    pub strikethrough: bool,
+    /// If the highlight replaces the attributes under it instead of
+    /// combining with them. nvim combines the attributes before sending
+    /// them, so this only keeps gnvim from adding its own on top (e.g. the
+    /// virtual text style).
+    pub nocombine: bool,
 
     /// The blend value in range of 0..1.
     pub blend: f64,
+
+    /// If this highlight belongs to a virtual text highlight group.
+    pub virtual_text: bool,
 }
 
 impl Highlight {
@@ -93,20 +212,25 @@ impl Highlight {
         };
 
         let fontstyle = if self.italic { "italic" } else { "normal" };
+        let strikethrough_color = self.special.as_ref().unwrap_or(fg);
 
         format!(
             "<span
             foreground=\"#{fg}\"
             background=\"#{bg}\"
             underline_color=\"#{sp}\"
+            strikethrough_color=\"#{st}\"
             weight=\"{weight}\"
             font_style=\"{fontstyle}\"
+            strikethrough=\"{strikethrough}\"
             underline=\"{underline}\">{text}</span>",
             fg = fg.to_hex(),
             bg = bg.to_hex(),
             sp = sp.to_hex(),
+            st = strikethrough_color.to_hex(),
             weight = weight,
             fontstyle = fontstyle,
+            strikethrough = self.strikethrough,
             underline = underline,
             text = glib::markup_escape_text(text)
         )
@@ -166,6 +290,82 @@ impl Color {
         )
     }
 
+    /// Mixes `self` with `other`. `t` of 0.0 returns `self` and 1.0 `other`.
+    pub fn mix(&self, other: &Color, t: f64) -> Color {
+        Color {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+        }
+    }
+
+    /// Relative luminance of the color, as defined by WCAG.
+    pub fn luminance(&self) -> f64 {
+        let channel = |c: f64| {
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        0.2126 * channel(self.r)
+            + 0.7152 * channel(self.g)
+            + 0.0722 * channel(self.b)
+    }
+
+    /// WCAG contrast ratio between `self` and `other`, from 1.0 to 21.0.
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let a = self.luminance();
+        let b = other.luminance();
+
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Lightens or darkens `self` until its contrast ratio against `bg` is
+    /// at least `ratio`. If that is not possible, returns black or white,
+    /// whichever has more contrast.
+    pub fn with_min_contrast(&self, bg: &Color, ratio: f64) -> Color {
+        if self.contrast_ratio(bg) >= ratio {
+            return *self;
+        }
+
+        let white = Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        };
+        let black = Color::default();
+
+        // Move away from the background's luminance.
+        let target = if self.luminance() >= bg.luminance() {
+            white
+        } else {
+            black
+        };
+
+        if target.contrast_ratio(bg) < ratio {
+            return if white.contrast_ratio(bg) > black.contrast_ratio(bg) {
+                white
+            } else {
+                black
+            };
+        }
+
+        // Find the smallest mix of the target that is enough.
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..16 {
+            let mid = (lo + hi) / 2.0;
+            if self.mix(&target, mid).contrast_ratio(bg) >= ratio {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
+
+        self.mix(&target, hi)
+    }
+
     /// Apply the blend value to color. Returns the color in `rgba()` format.
     /// Note that the blend value is inverted.
     pub fn to_rgba(&self, blend: f64) -> String {
@@ -193,4 +393,71 @@ mod tests {
 
         assert_eq!(c.to_rgba(0.4), "rgba(255, 0, 255, 0.6)");
     }
+
+    #[test]
+    fn test_color_mix() {
+        let a = Color {
+            r: 1.0,
+            g: 0.0,
+            b: 0.5,
+        };
+        let b = Color {
+            r: 0.0,
+            g: 1.0,
+            b: 0.5,
+        };
+
+        assert_eq!(
+            a.mix(&b, 0.5),
+            Color {
+                r: 0.5,
+                g: 0.5,
+                b: 0.5,
+            }
+        );
+        assert_eq!(a.mix(&b, 0.0), a);
+    }
+
+    #[test]
+    fn test_color_min_contrast() {
+        let white = Color {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+        };
+        let black = Color::default();
+        assert!((white.contrast_ratio(&black) - 21.0).abs() < 0.001);
+
+        let bg = Color::from_u64(0x202020);
+        let fg = Color::from_u64(0x404040);
+        assert!(fg.contrast_ratio(&bg) < 4.5);
+
+        let adjusted = fg.with_min_contrast(&bg, 4.5);
+        assert!(adjusted.contrast_ratio(&bg) >= 4.5);
+        assert!(adjusted.luminance() > fg.luminance());
+
+        let fg = Color::from_u64(0xf0f0f0);
+        assert_eq!(fg.with_min_contrast(&bg, 4.5), fg);
+
+        // Darker than the background, but black isn't enough.
+        let fg = Color::from_u64(0x101010);
+        assert_eq!(fg.with_min_contrast(&bg, 4.5), white);
+    }
+
+    #[test]
+    fn test_text_shadow_samples() {
+        let mut shadow = TextShadow {
+            color: Color::default(),
+            dx: 1.0,
+            dy: 2.0,
+            blur: 0.0,
+        };
+        assert_eq!(shadow.samples(), vec![(1.0, 2.0, 1.0)]);
+
+        shadow.blur = 2.0;
+        let samples = shadow.samples();
+        assert_eq!(samples.len(), 9);
+        assert!(samples.contains(&(0.0, 1.0, 0.25)));
+        assert!(samples.contains(&(2.0, 3.0, 0.25)));
+    }
 }
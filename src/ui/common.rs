@@ -1,4 +1,8 @@
 use futures::future::Future;
+use gtk::prelude::*;
+
+use crate::ui::color::Color;
+use crate::ui::font::{Font, FontUnit};
 
 pub fn spawn_local<F: Future<Output = ()> + 'static>(f: F) {
     let c = glib::MainContext::default();
@@ -14,6 +18,11 @@ pub fn calc_line_space(space: i64) -> (i32, i32) {
     }
 }
 
+/// Quotes `s` as a vim string literal.
+pub fn vim_string(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
 /// Calculate the preferred width and x-position.
 pub fn get_preferred_horizontal_position(
     area: &gdk::Rectangle,
@@ -62,6 +71,89 @@ pub fn get_preferred_vertical_position(
     (y, height)
 }
 
+/// Small "pill" label on top of the grids, in the bottom corner of the
+/// overlay given by `halign`. Hidden until `set_visible` is called.
+pub struct OverlayPill {
+    label: gtk::Label,
+    css_provider: gtk::CssProvider,
+
+    font: Font,
+}
+
+impl OverlayPill {
+    pub fn new(parent: &gtk::Overlay, halign: gtk::Align) -> Self {
+        let label = gtk::Label::new(None);
+        label.set_halign(halign);
+        label.set_valign(gtk::Align::End);
+        if halign == gtk::Align::Start {
+            label.set_margin_start(10);
+        } else {
+            label.set_margin_end(10);
+        }
+        label.set_margin_bottom(10);
+        // Don't show the pill with `show_all` on the main window.
+        label.set_no_show_all(true);
+
+        let css_provider = gtk::CssProvider::new();
+        add_css_provider!(&css_provider, label);
+
+        parent.add_overlay(&label);
+        parent.set_overlay_pass_through(&label, true);
+
+        OverlayPill {
+            label,
+            css_provider,
+            font: Font::default(),
+        }
+    }
+
+    pub fn set_text(&self, text: &str) {
+        self.label.set_text(text);
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        if visible {
+            self.label.show();
+        } else {
+            self.label.hide();
+        }
+    }
+
+    /// Sets the font. Call `set_colors` after this to apply it.
+    pub fn set_font(&mut self, font: Font) {
+        self.font = font;
+    }
+
+    pub fn set_colors(&self, fg: &Color, bg: &Color, opacity: f64) {
+        let (selector, unit) = if gtk::get_minor_version() < 20 {
+            ("GtkLabel", FontUnit::Pixel)
+        } else {
+            ("label", FontUnit::Point)
+        };
+
+        let css = format!(
+            "{font_wild}
+
+            {selector} {{
+                color: #{fg};
+                background-color: #{bg};
+                border-radius: 10px;
+                padding: 2px 8px;
+                opacity: {opacity};
+            }}
+            ",
+            font_wild = self.font.as_wild_css(unit),
+            selector = selector,
+            fg = fg.to_hex(),
+            bg = bg.to_hex(),
+            opacity = opacity,
+        );
+
+        CssProviderExt::load_from_data(&self.css_provider, css.as_bytes())
+            .unwrap();
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -74,6 +166,12 @@ mod test {
         assert_eq!((3, 2), calc_line_space(5));
     }
 
+    #[test]
+    fn test_vim_string() {
+        assert_eq!(vim_string("foo bar"), "'foo bar'");
+        assert_eq!(vim_string("it's"), "'it''s'");
+    }
+
     #[test]
     fn test_get_preferred_vertical_position1() {
         // Case 1: there is room just fine in the obvious position.
@@ -0,0 +1,109 @@
+use std::cell::Cell;
+
+use gtk::prelude::*;
+use log::error;
+
+use crate::nvim_gio::GioNeovim;
+use crate::ui::clipboard;
+use crate::ui::common::spawn_local;
+
+/// What a context menu item does when activated.
+enum Action {
+    /// Sends keys to nvim, like the `rhs` of a mapping.
+    Keys(String),
+    /// Pastes the GUI clipboard (see `clipboard::paste`).
+    Paste,
+}
+
+/// Menu shown on right click over the grids, with actions like copy and
+/// paste. When enabled, right clicks are not sent to nvim.
+pub struct ContextMenu {
+    menu: gtk::Menu,
+    nvim: GioNeovim,
+
+    enabled: Cell<bool>,
+}
+
+impl ContextMenu {
+    pub fn new(nvim: GioNeovim) -> Self {
+        let menu = ContextMenu {
+            menu: gtk::Menu::new(),
+            nvim,
+            enabled: Cell::new(false),
+        };
+
+        menu.reset();
+        menu
+    }
+
+    /// Disables the menu and restores the default items.
+    pub fn reset(&self) {
+        self.enabled.set(false);
+        self.set_menu_items(vec![
+            (
+                String::from("Copy"),
+                // Re-select the last visual selection, so that copying works
+                // from any mode.
+                Action::Keys(String::from("<C-\\><C-n>gv\"+y")),
+            ),
+            (String::from("Paste"), Action::Paste),
+            (
+                String::from("Select All"),
+                Action::Keys(String::from("<C-\\><C-n>ggVG")),
+            ),
+        ]);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.set(enabled);
+    }
+
+    /// Replaces the menu items. Each item is a label and the keys that are
+    /// sent to nvim when the item is activated.
+    pub fn set_items(&self, items: Vec<(String, String)>) {
+        self.set_menu_items(
+            items
+                .into_iter()
+                .map(|(label, keys)| (label, Action::Keys(keys)))
+                .collect(),
+        );
+    }
+
+    /// Shows the menu at the pointer. Must be called from the handler of
+    /// the button press that opens the menu.
+    pub fn popup(&self) {
+        self.menu.popup_easy(3, gtk::get_current_event_time());
+    }
+
+    fn set_menu_items(&self, items: Vec<(String, Action)>) {
+        for child in self.menu.get_children() {
+            self.menu.remove(&child);
+        }
+
+        for (label, action) in items {
+            let item = gtk::MenuItem::with_label(&label);
+
+            let nvim = self.nvim.clone();
+            item.connect_activate(move |_| match action {
+                Action::Keys(ref keys) => {
+                    let nvim = nvim.clone();
+                    let keys = keys.clone();
+                    spawn_local(async move {
+                        if let Err(err) = nvim.input(&keys).await {
+                            error!("Failed to send context menu keys: {}", err);
+                        }
+                    });
+                }
+                Action::Paste => clipboard::paste(nvim.clone()),
+            });
+
+            self.menu.append(&item);
+        }
+
+        self.menu.show_all();
+    }
+}
@@ -45,6 +45,8 @@ lazy_static! {
 
 const MAX_WIDTH: i32 = 700;
 const MAX_HEIGHT: i32 = 300;
+/// Code highlighting style used until another one is set.
+const DEFAULT_STYLE: &str = "base16-ocean.dark";
 
 struct State {
     anchor: gdk::Rectangle,
@@ -86,6 +88,8 @@ pub struct CursorTooltip {
     fg: Color,
     bg: Color,
     font: Font,
+    /// Font for code, if it should differ from `font`.
+    code_font: Option<Font>,
 
     /// Our current syntax set.
     syntax_set: SyntaxSet,
@@ -94,6 +98,11 @@ pub struct CursorTooltip {
 
     /// Currently selected theme.
     current_theme: Theme,
+    /// Path of the last style loaded with `load_style`.
+    style_path: Option<String>,
+
+    /// The (markdown) content we're currently showing.
+    content: Option<String>,
 }
 
 impl CursorTooltip {
@@ -152,7 +161,7 @@ impl CursorTooltip {
             from_binary(include_bytes!("../../sublime-syntaxes/all.pack"));
         let theme_set = ThemeSet::load_defaults();
 
-        let current_theme = theme_set.themes["base16-ocean.dark"].clone();
+        let current_theme = theme_set.themes[DEFAULT_STYLE].clone();
 
         CursorTooltip {
             css_provider,
@@ -164,10 +173,14 @@ impl CursorTooltip {
             fg: Color::default(),
             bg: Color::default(),
             font: Font::default(),
+            code_font: None,
 
             syntax_set,
             theme_set,
             current_theme,
+            style_path: None,
+
+            content: None,
         }
     }
 
@@ -198,10 +211,21 @@ impl CursorTooltip {
         }
     }
 
+    /// Restores the default code highlighting style.
+    pub fn reset_style(&mut self) {
+        self.set_style(DEFAULT_STYLE);
+    }
+
     pub fn set_font(&mut self, font: Font) {
         self.font = font;
     }
 
+    /// Sets the font for code (both inline and code blocks). `None` uses
+    /// the same font as the rest of the tooltip.
+    pub fn set_code_font(&mut self, font: Option<Font>) {
+        self.code_font = font;
+    }
+
     pub fn hide(&self) {
         self.frame.hide();
     }
@@ -211,17 +235,31 @@ impl CursorTooltip {
     }
 
     pub fn load_style(&mut self, path: String) -> Result<(), &str> {
-        let path = Path::new(&path);
-        let theme =
-            ThemeSet::get_theme(&path).or(Err("Failed to load theme file"))?;
-
-        let name = if let Some(name) = theme.clone().name {
-            name
-        } else {
-            return Err("Failed to get theme name");
-        };
+        let (name, theme) = read_theme(&path)?;
+        self.theme_set.themes.insert(name, theme);
+        self.style_path = Some(path);
+
+        Ok(())
+    }
+
+    /// Loads the style that was last loaded with `load_style` again and
+    /// re-renders the current content (if we're visible).
+    pub fn reload_style(&mut self) -> Result<(), &str> {
+        let path = self.style_path.as_ref().ok_or("No style loaded")?;
+        let (name, theme) = read_theme(path)?;
+
+        // If the theme is in use, update it too.
+        if self.current_theme.name.as_ref() == Some(&name) {
+            self.current_theme = theme.clone();
+        }
         self.theme_set.themes.insert(name, theme);
 
+        if self.is_visible() {
+            if let Some(content) = self.content.clone() {
+                self.show(content);
+            }
+        }
+
         Ok(())
     }
 
@@ -341,6 +379,8 @@ impl CursorTooltip {
                     }}
 
                     {font}
+
+                    {code_font}
                 </style>
             </head>
             <body>
@@ -354,10 +394,20 @@ impl CursorTooltip {
             content = html,
             fg = self.fg.to_hex(),
             bg = self.bg.to_hex(),
-            font = self.font.as_wild_css(FontUnit::Point)
+            font = self.font.as_wild_css(FontUnit::Point),
+            code_font = self.code_font.as_ref().map_or(String::new(), |font| {
+                format!(
+                    "code, pre, pre * {{
+                        font-family: \"{}\";
+                        font-size: {}pt;
+                    }}",
+                    font.name, font.height,
+                )
+            }),
         );
 
         self.webview.load_html(&all, None);
+        self.content = Some(content);
     }
 
     pub fn move_to(&mut self, rect: &gdk::Rectangle) {
@@ -420,6 +470,18 @@ fn set_position(
     frame.set_size_request(width, height);
 }
 
+/// Reads a theme file from `path`. Returns the name of the theme and the
+/// theme.
+fn read_theme(path: &str) -> Result<(String, Theme), &'static str> {
+    let theme = ThemeSet::get_theme(Path::new(path))
+        .or(Err("Failed to load theme file"))?;
+
+    match theme.name.clone() {
+        Some(name) => Ok((name, theme)),
+        None => Err("Failed to get theme name"),
+    }
+}
+
 /// Once the webview has loaded its content, we need to check how much
 /// height and width does the rendered content take. After this, we can set
 /// the size of the webview's container.
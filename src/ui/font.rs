@@ -2,6 +2,8 @@ use std::fmt;
 use std::fmt::Display;
 
 const DEFAULT_HEIGHT: f32 = 14.0;
+const MIN_HEIGHT: f32 = 4.0;
+const MAX_HEIGHT: f32 = 72.0;
 
 pub enum FontUnit {
     Pixel,
@@ -64,6 +66,20 @@ impl Font {
         Ok(font)
     }
 
+    /// Returns a copy of self with height changed by `delta`. The height is
+    /// clamped between `MIN_HEIGHT` and `MAX_HEIGHT`.
+    pub fn zoom(&self, delta: f32) -> Self {
+        Font {
+            name: self.name.clone(),
+            height: (self.height + delta).max(MIN_HEIGHT).min(MAX_HEIGHT),
+        }
+    }
+
+    /// Returns self in nvim's `guifont` format.
+    pub fn as_guifont(&self) -> String {
+        format!("{}:h{}", self.name, self.height)
+    }
+
     /// Returns a CSS representation of self for a wild (`*`) CSS selector.
     /// On gtk version below 3.20 unit needs to be `FontUnit::Pixel` and
     /// with version 3.20 and up, unit needs to be `FontUnit::Point`. This is
@@ -134,6 +150,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_zoom() {
+        let font = Font {
+            name: "foo".to_string(),
+            height: 12.0,
+        };
+
+        let f = font.zoom(2.0);
+        assert_eq!(f.name, "foo");
+        assert_eq!(f.height, 14.0);
+
+        let f = font.zoom(-1.5);
+        assert_eq!(f.height, 10.5);
+
+        // Size is clamped.
+        assert_eq!(font.zoom(-100.0).height, MIN_HEIGHT);
+        assert_eq!(font.zoom(100.0).height, MAX_HEIGHT);
+    }
+
+    #[test]
+    fn test_as_guifont() {
+        let font = Font {
+            name: "Fira Code".to_string(),
+            height: 12.0,
+        };
+        assert_eq!(font.as_guifont(), "Fira Code:h12");
+
+        let font = Font {
+            name: "foo".to_string(),
+            height: 10.5,
+        };
+        assert_eq!(font.as_guifont(), "foo:h10.5");
+
+        let font = Font::from_guifont("monospace:h11").unwrap();
+        assert_eq!(font.as_guifont(), "monospace:h11");
+    }
+
     #[test]
     fn test_from_guifont() {
         // Font with proper height.
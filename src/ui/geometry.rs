@@ -0,0 +1,122 @@
+use std::fmt;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use gtk::prelude::*;
+
+/// Position and size of the main window.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Geometry {
+    /// Gets the current geometry of `window`.
+    pub fn from_window(window: &gtk::ApplicationWindow) -> Self {
+        let (x, y) = window.get_position();
+        let (width, height) = window.get_size();
+
+        Geometry {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Parses geometry in the format of `x y width height`.
+    pub fn from_string(s: &str) -> Result<Self, String> {
+        let parts = s
+            .split_whitespace()
+            .map(|p| p.parse::<i32>())
+            .collect::<Result<Vec<i32>, _>>()
+            .map_err(|err| format!("Invalid geometry: {}", err))?;
+
+        match parts.as_slice() {
+            [x, y, width, height] if *width > 0 && *height > 0 => {
+                Ok(Geometry {
+                    x: *x,
+                    y: *y,
+                    width: *width,
+                    height: *height,
+                })
+            }
+            _ => Err(format!("Invalid geometry: {}", s.trim())),
+        }
+    }
+
+    /// Applies the geometry to `window`.
+    pub fn apply(&self, window: &gtk::ApplicationWindow) {
+        window.set_default_size(self.width, self.height);
+        window.move_(self.x, self.y);
+    }
+}
+
+impl Display for Geometry {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{} {} {} {}", self.x, self.y, self.width, self.height)
+    }
+}
+
+/// Path to the file where the window geometry is stored.
+fn state_file() -> Option<PathBuf> {
+    glib::get_user_config_dir().map(|dir| dir.join("gnvim").join("geometry"))
+}
+
+/// Loads the window geometry saved with `save`. Returns `None` if there
+/// isn't any (e.g. the user hasn't opted in to remembering the geometry).
+pub fn load() -> Option<Geometry> {
+    let content = fs::read_to_string(state_file()?).ok()?;
+    Geometry::from_string(&content).ok()
+}
+
+/// Saves the window geometry so it can be restored on the next launch.
+pub fn save(geometry: &Geometry) -> io::Result<()> {
+    let path = state_file().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, "no config dir")
+    })?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, geometry.to_string())
+}
+
+/// Removes the saved window geometry.
+pub fn remove() -> io::Result<()> {
+    match state_file() {
+        Some(path) if path.exists() => fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geometry_from_string() {
+        let geometry = Geometry::from_string("10 -20 800 600\n").unwrap();
+        assert_eq!(
+            geometry,
+            Geometry {
+                x: 10,
+                y: -20,
+                width: 800,
+                height: 600,
+            }
+        );
+        assert_eq!(geometry.to_string(), "10 -20 800 600");
+
+        assert!(Geometry::from_string("").is_err());
+        assert!(Geometry::from_string("1 2 3").is_err());
+        assert!(Geometry::from_string("1 2 0 600").is_err());
+        assert!(Geometry::from_string("a b c d").is_err());
+    }
+}
@@ -0,0 +1,310 @@
+//! Draws box drawing characters (U+2500 - U+257F) with cairo primitives so
+//! that they fill the whole cell. Fonts often leave small gaps between the
+//! cells, which makes borders look broken.
+
+const FIRST: u32 = 0x2500;
+const LAST: u32 = 0x257F;
+
+const NONE: u8 = 0;
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+const UP: usize = 0;
+const RIGHT: usize = 1;
+const DOWN: usize = 2;
+const LEFT: usize = 3;
+
+/// Line weights for each character, in order of up, right, down and left.
+/// Characters that are all `NONE` are not drawn by us (e.g. dashed lines).
+#[rustfmt::skip]
+static LINES: [[u8; 4]; 128] = [
+    // ─ ━ │ ┃
+    [0, 1, 0, 1], [0, 2, 0, 2], [1, 0, 1, 0], [2, 0, 2, 0],
+    // Dashed lines.
+    [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    // ┌ ┍ ┎ ┏
+    [0, 1, 1, 0], [0, 2, 1, 0], [0, 1, 2, 0], [0, 2, 2, 0],
+    // ┐ ┑ ┒ ┓
+    [0, 0, 1, 1], [0, 0, 1, 2], [0, 0, 2, 1], [0, 0, 2, 2],
+    // └ ┕ ┖ ┗
+    [1, 1, 0, 0], [1, 2, 0, 0], [2, 1, 0, 0], [2, 2, 0, 0],
+    // ┘ ┙ ┚ ┛
+    [1, 0, 0, 1], [1, 0, 0, 2], [2, 0, 0, 1], [2, 0, 0, 2],
+    // ├ ┝ ┞ ┟
+    [1, 1, 1, 0], [1, 2, 1, 0], [2, 1, 1, 0], [1, 1, 2, 0],
+    // ┠ ┡ ┢ ┣
+    [2, 1, 2, 0], [2, 2, 1, 0], [1, 2, 2, 0], [2, 2, 2, 0],
+    // ┤ ┥ ┦ ┧
+    [1, 0, 1, 1], [1, 0, 1, 2], [2, 0, 1, 1], [1, 0, 2, 1],
+    // ┨ ┩ ┪ ┫
+    [2, 0, 2, 1], [2, 0, 1, 2], [1, 0, 2, 2], [2, 0, 2, 2],
+    // ┬ ┭ ┮ ┯
+    [0, 1, 1, 1], [0, 1, 1, 2], [0, 2, 1, 1], [0, 2, 1, 2],
+    // ┰ ┱ ┲ ┳
+    [0, 1, 2, 1], [0, 1, 2, 2], [0, 2, 2, 1], [0, 2, 2, 2],
+    // ┴ ┵ ┶ ┷
+    [1, 1, 0, 1], [1, 1, 0, 2], [1, 2, 0, 1], [1, 2, 0, 2],
+    // ┸ ┹ ┺ ┻
+    [2, 1, 0, 1], [2, 1, 0, 2], [2, 2, 0, 1], [2, 2, 0, 2],
+    // ┼ ┽ ┾ ┿
+    [1, 1, 1, 1], [1, 1, 1, 2], [1, 2, 1, 1], [1, 2, 1, 2],
+    // ╀ ╁ ╂ ╃
+    [2, 1, 1, 1], [1, 1, 2, 1], [2, 1, 2, 1], [2, 1, 1, 2],
+    // ╄ ╅ ╆ ╇
+    [2, 2, 1, 1], [1, 1, 2, 2], [1, 2, 2, 1], [2, 2, 1, 2],
+    // ╈ ╉ ╊ ╋
+    [1, 2, 2, 2], [2, 1, 2, 2], [2, 2, 2, 1], [2, 2, 2, 2],
+    // Dashed lines.
+    [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    // ═ ║ ╒ ╓
+    [0, 3, 0, 3], [3, 0, 3, 0], [0, 3, 1, 0], [0, 1, 3, 0],
+    // ╔ ╕ ╖ ╗
+    [0, 3, 3, 0], [0, 0, 1, 3], [0, 0, 3, 1], [0, 0, 3, 3],
+    // ╘ ╙ ╚ ╛
+    [1, 3, 0, 0], [3, 1, 0, 0], [3, 3, 0, 0], [1, 0, 0, 3],
+    // ╜ ╝ ╞ ╟
+    [3, 0, 0, 1], [3, 0, 0, 3], [1, 3, 1, 0], [3, 1, 3, 0],
+    // ╠ ╡ ╢ ╣
+    [3, 3, 3, 0], [1, 0, 1, 3], [3, 0, 3, 1], [3, 0, 3, 3],
+    // ╤ ╥ ╦ ╧
+    [0, 3, 1, 3], [0, 1, 3, 1], [0, 3, 3, 3], [1, 3, 0, 3],
+    // ╨ ╩ ╪ ╫
+    [3, 1, 0, 1], [3, 3, 0, 3], [1, 3, 1, 3], [3, 1, 3, 1],
+    // ╬, and the arcs ╭ ╮ ╯ (drawn separately).
+    [3, 3, 3, 3], [0, 1, 1, 0], [0, 0, 1, 1], [1, 0, 0, 1],
+    // ╰, and the diagonals ╱ ╲ ╳ (not supported).
+    [1, 1, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    // ╴ ╵ ╶ ╷
+    [0, 0, 0, 1], [1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 1, 0],
+    // ╸ ╹ ╺ ╻
+    [0, 0, 0, 2], [2, 0, 0, 0], [0, 2, 0, 0], [0, 0, 2, 0],
+    // ╼ ╽ ╾ ╿
+    [0, 2, 0, 1], [1, 0, 2, 0], [0, 1, 0, 2], [2, 0, 1, 0],
+];
+
+/// Returns the line weights for `text`, if it is a single box drawing
+/// character that we can draw.
+fn lookup(text: &str) -> Option<(char, [u8; 4])> {
+    let mut chars = text.chars();
+    let ch = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+
+    let cp = ch as u32;
+    if !(FIRST..=LAST).contains(&cp) {
+        return None;
+    }
+
+    let lines = LINES[(cp - FIRST) as usize];
+    if lines == [NONE; 4] {
+        return None;
+    }
+
+    Some((ch, lines))
+}
+
+/// Checks if `text` is a box drawing character that `draw` can draw.
+pub fn is_supported(text: &str) -> bool {
+    lookup(text).is_some()
+}
+
+/// Thickness of the lines, based on the cell width.
+struct Weights {
+    light: f64,
+    heavy: f64,
+    /// Distance of the each line in a double line from the center.
+    double_offset: f64,
+}
+
+impl Weights {
+    fn new(cell_width: f64) -> Self {
+        let light = (cell_width / 8.0).round().max(1.0);
+        Weights {
+            light,
+            heavy: light * 2.0,
+            double_offset: light,
+        }
+    }
+
+    /// Thickness of a single line with `weight`.
+    fn thickness(&self, weight: u8) -> f64 {
+        match weight {
+            HEAVY => self.heavy,
+            LIGHT | DOUBLE => self.light,
+            _ => 0.0,
+        }
+    }
+
+    /// Half of the space that a line with `weight` takes.
+    fn half_width(&self, weight: u8) -> f64 {
+        match weight {
+            DOUBLE => self.double_offset + self.light / 2.0,
+            w => self.thickness(w) / 2.0,
+        }
+    }
+}
+
+/// Draws `text` to `cr` using the current source, if `text` is a supported
+/// box drawing character. Returns false if the character wasn't drawn.
+#[allow(clippy::many_single_char_names)]
+pub fn draw(
+    cr: &cairo::Context,
+    text: &str,
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+) -> bool {
+    let (ch, lines) = match lookup(text) {
+        Some(res) => res,
+        None => return false,
+    };
+
+    let weights = Weights::new(w);
+    let cx = (x + w / 2.0).floor();
+    let cy = (y + h / 2.0).floor();
+
+    if matches!(ch, '╭' | '╮' | '╯' | '╰') {
+        draw_arc(cr, &lines, &weights, x, y, w, h, cx, cy);
+        return true;
+    }
+
+    for (dir, weight) in lines.iter().enumerate() {
+        // Lines perpendicular to this one. The first one is on the
+        // "negative" side (e.g. up or left) of the line.
+        let perp = if dir == UP || dir == DOWN {
+            [lines[LEFT], lines[RIGHT]]
+        } else {
+            [lines[UP], lines[DOWN]]
+        };
+
+        let parts = match *weight {
+            NONE => continue,
+            DOUBLE => {
+                let d = weights.double_offset;
+                vec![
+                    (-d, double_extend(&weights, perp[0], perp[1])),
+                    (d, double_extend(&weights, perp[1], perp[0])),
+                ]
+            }
+            _ => {
+                let ext = weights
+                    .half_width(perp[0])
+                    .max(weights.half_width(perp[1]))
+                    .max(weights.thickness(*weight) / 2.0);
+                vec![(0.0, ext)]
+            }
+        };
+
+        let t = weights.thickness(*weight);
+        for (offset, ext) in parts {
+            let (a, b) = (offset - t / 2.0, offset + t / 2.0);
+            let (x0, y0, x1, y1) = match dir {
+                UP => (cx + a, y, cx + b, cy + ext),
+                DOWN => (cx + a, cy - ext, cx + b, y + h),
+                RIGHT => (cx - ext, cy + a, x + w, cy + b),
+                _ => (x, cy + a, cx + ext, cy + b),
+            };
+
+            fill(cr, x0, y0, x1, y1);
+        }
+    }
+
+    true
+}
+
+/// Returns how far past the center a line of a double line should extend.
+/// `near` is the perpendicular line on the same side as the line, `far` the
+/// one on the other side.
+fn double_extend(weights: &Weights, near: u8, far: u8) -> f64 {
+    match (near, far) {
+        // Stop at the inner edge of the line next to us.
+        (DOUBLE, _) => -(weights.double_offset - weights.light / 2.0),
+        (NONE, NONE) => weights.light / 2.0,
+        // Make a corner with the line on the other side.
+        (NONE, far) => weights.half_width(far),
+        (near, _) => -weights.half_width(near),
+    }
+}
+
+/// Fills a pixel aligned rectangle.
+fn fill(cr: &cairo::Context, x0: f64, y0: f64, x1: f64, y1: f64) {
+    let (x0, y0, x1, y1) = (x0.round(), y0.round(), x1.round(), y1.round());
+    cr.rectangle(x0, y0, (x1 - x0).max(1.0), (y1 - y0).max(1.0));
+    cr.fill();
+}
+
+/// Draws one of the rounded corners.
+#[allow(clippy::too_many_arguments, clippy::many_single_char_names)]
+fn draw_arc(
+    cr: &cairo::Context,
+    lines: &[u8; 4],
+    weights: &Weights,
+    x: f64,
+    y: f64,
+    w: f64,
+    h: f64,
+    cx: f64,
+    cy: f64,
+) {
+    // Directions of the arc's arms.
+    let sx = if lines[RIGHT] != NONE { 1.0 } else { -1.0 };
+    let sy = if lines[DOWN] != NONE { 1.0 } else { -1.0 };
+
+    let vy = if sy > 0.0 { y + h } else { y };
+    let hx = if sx > 0.0 { x + w } else { x };
+    let r = (w / 2.0).min(h / 2.0);
+    // Distance of the bezier control points from the ends of the arc, for
+    // an approximation of a circle.
+    let k = r * (1.0 - 0.5523);
+
+    // Center the line on the pixel grid.
+    let lw = weights.light;
+    let (cx, cy) = if lw as i64 % 2 == 1 {
+        (cx + 0.5, cy + 0.5)
+    } else {
+        (cx, cy)
+    };
+
+    cr.save();
+    cr.set_line_width(lw);
+    cr.move_to(cx, vy);
+    cr.line_to(cx, cy + sy * r);
+    cr.curve_to(cx, cy + sy * k, cx + sx * k, cy, cx + sx * r, cy);
+    cr.line_to(hx, cy);
+    cr.stroke();
+    cr.restore();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_drawing_is_supported() {
+        assert!(is_supported("─"));
+        assert!(is_supported("┼"));
+        assert!(is_supported("╬"));
+        assert!(is_supported("╭"));
+        assert!(is_supported("╿"));
+
+        // Dashed lines and diagonals are left to the font.
+        assert!(!is_supported("┄"));
+        assert!(!is_supported("╳"));
+
+        assert!(!is_supported(""));
+        assert!(!is_supported("a"));
+        assert!(!is_supported("──"));
+    }
+
+    #[test]
+    fn test_box_drawing_lookup() {
+        assert_eq!(lookup("┌"), Some(('┌', [NONE, LIGHT, LIGHT, NONE])));
+        assert_eq!(lookup("┫"), Some(('┫', [HEAVY, NONE, HEAVY, HEAVY])));
+        assert_eq!(lookup("╔"), Some(('╔', [NONE, DOUBLE, DOUBLE, NONE])));
+        assert_eq!(lookup("╾"), Some(('╾', [NONE, LIGHT, NONE, HEAVY])));
+    }
+}
@@ -1,16 +1,65 @@
+use std::collections::{BTreeMap, HashMap};
+
 use gtk::prelude::*;
 use gtk::DrawingArea;
 
-use crate::ui::color::HlDefs;
+use crate::nvim_bridge::{CursorShape, UnfocusedCursor};
+use crate::ui::color::{Color, HlDefs};
 use crate::ui::font::Font;
-use crate::ui::grid::cursor::Cursor;
+use crate::ui::grid::cursor::{ease_out_cubic, Cursor};
 use crate::ui::grid::render;
 use crate::ui::grid::row::{Cell, Row};
+use crate::ui::grid::scroll_cache::ScrollCache;
+use crate::ui::grid::selection::Selection;
+use crate::ui::grid::surface::SurfaceProvider;
+
+/// How long (in microseconds) we need to be busy before the busy spinner
+/// is shown. This way the spinner doesn't flash on short busy periods.
+const BUSY_SPINNER_DELAY: i64 = 300_000;
+/// Duration (in microseconds) of the zoom animation.
+const ZOOM_DURATION: i64 = 150_000;
+
+/// Animated scaling of the grid after the cell size has changed.
+pub struct Zoom {
+    /// Scale at the start of the animation (old cell size relative to the
+    /// new one).
+    from: f64,
+    /// If the grid has the content drawn with the new cell size. Until then,
+    /// the old content is drawn as is.
+    pub ready: bool,
+    /// Frame time when the animation started.
+    start: Option<i64>,
+    /// Current scale.
+    pub scale: f64,
+}
+
+/// Duration (in microseconds) of the typewriter mode's scroll animation.
+const TYPEWRITER_DURATION: i64 = 200_000;
+
+/// Vertical offset of the grid's content in typewriter mode, which keeps
+/// the cursor's row at the middle of the grid.
+#[derive(Default)]
+pub struct Typewriter {
+    /// Offset at the start of the animation.
+    from: f64,
+    /// Offset the animation is heading towards.
+    to: f64,
+    /// Frame time when the animation started.
+    start: Option<i64>,
+    /// If the offset is still being animated.
+    animating: bool,
+    /// Current offset (in pixels).
+    pub offset: f64,
+}
 
 /// Context is manipulated by Grid.
 pub struct Context {
     /// Our cairo context, that is evetually drawn to the screen.
     pub cairo_context: cairo::Context,
+    /// Surface from before the latest resize. This is drawn to the screen
+    /// instead of `cairo_context` until the next flush, so we don't flash
+    /// the background while nvim is sending the new content.
+    pub resize_surface: Option<cairo::Surface>,
     /// Our cell metrics.
     pub cell_metrics: CellMetrics,
     /// Cell metrics to be updated.
@@ -22,23 +71,111 @@ pub struct Context {
     pub cursor: Cursor,
     /// Cairo context for cursor.
     pub cursor_context: cairo::Context,
+    /// Shape and blinkon of the current mode's cursor.
+    pub mode_blink: (CursorShape, u64),
+    /// Blink timings (on, off, wait) of specific cursor shapes. These
+    /// override the mode's blink.
+    pub cursor_blinks: HashMap<CursorShape, (u64, u64, u64)>,
 
     /// If the current status is busy or not. When busy, the cursor is not
     /// drawn (like when in terminal mode in inserting text).
     pub busy: bool,
+    /// If the cursor should be drawn at all.
+    pub cursor_visible: bool,
+    /// If a spinner is drawn at the cursor's position while busy.
+    pub busy_spinner: bool,
+    /// Frame time (in microseconds) when we noticed that we're busy.
+    busy_since: Option<i64>,
+    /// Rotation (in radians) of the busy spinner. `None` when the spinner
+    /// isn't shown. Updated on each tick.
+    pub spinner_angle: Option<f64>,
+    /// Default foreground color, used for the busy spinner and the scroll
+    /// indicator.
+    pub fg_color: Color,
+    /// If the text under the cursor should be drawn on top of the cursor
+    /// (with the cursor's contrasting color) also when the cursor blinks.
+    pub cursor_over_text: bool,
 
     /// If the grid that this context belongs to is active or not.
     pub active: bool,
+    /// How much to dim the grid when it is not active. Zero means no dimming.
+    pub inactive_dim: f64,
+    /// If the colors of the grid should be drawn inverted.
+    pub invert_colors: bool,
+    /// Color and width of the border drawn around the grid when it is
+    /// active. `None` disables the border.
+    pub active_border: Option<(Color, f64)>,
+    /// Color and width of the outline drawn around the cursor. `None`
+    /// disables the outline.
+    pub cursor_outline: Option<(Color, f64)>,
+    /// Color of the lines drawn at the cell boundaries. `None` disables the
+    /// lines.
+    pub grid_lines: Option<Color>,
+    /// Current line and total lines of the buffer, for the scroll
+    /// indicator. `None` hides the indicator.
+    pub scroll_indicator: Option<(u64, u64)>,
+    /// If changes to the cell size are animated.
+    pub zoom_animation: bool,
+    /// Currently running zoom animation.
+    pub zoom: Option<Zoom>,
+    /// Offset of the content in typewriter mode. `None` when the mode is
+    /// disabled.
+    pub typewriter: Option<Typewriter>,
+    /// Default background color, for the area not covered by the content
+    /// when it is offset.
+    pub bg_color: Color,
+    /// Color for the area outside of the grid. `None` uses `bg_color`.
+    pub letterbox_color: Option<Color>,
+
+    /// Lines to keep undimmed above and below the cursor, and how much to
+    /// dim the rest of the lines. `None` disables the dimming.
+    pub dim_lines: Option<(u64, u64, f64)>,
+    /// Rows that are drawn scaled up, and their scale. A scaled row covers
+    /// the rows below it.
+    pub line_scales: BTreeMap<usize, u64>,
+
+    /// If the window this grid is in has the keyboard focus.
+    pub focused: bool,
+    /// How to draw the cursor when the window doesn't have the focus.
+    pub unfocused_cursor: UnfocusedCursor,
+
+    /// Currently active flashes (see `Grid::flash`).
+    pub flashes: Vec<Flash>,
+    /// Positions (row, col) of extra cursors.
+    pub extra_cursors: Vec<(u64, u64)>,
+    /// Images drawn on top of the grid.
+    pub images: Vec<Image>,
+    /// Rows scrolled out of the grid. `None` if the cache is disabled.
+    pub scroll_cache: Option<ScrollCache>,
+
+    /// If text can be selected with the mouse on the GUI side.
+    pub gui_selection: bool,
+    /// Current GUI side selection.
+    pub selection: Option<Selection>,
 
     /// Areas to call queue_draw_area on the drawing area on flush.
     pub queue_draw_area: Vec<(f64, f64, f64, f64)>,
+    /// If the grid has changed since the last flush.
+    pub dirty: bool,
+
+    /// If the tick callback (animations, cursor blinking) should stop, e.g.
+    /// because the window isn't visible.
+    pub paused: bool,
+    /// If the tick callback is currently attached to the drawing area.
+    pub tick_active: bool,
+    /// Maximum rate (per second) of the tick callback. `None` ticks on
+    /// every frame, and zero disables the tick callback (and so the
+    /// animations).
+    pub fps: Option<u32>,
+    /// Frame time of the previous tick.
+    pub last_tick: Option<i64>,
 }
 
 impl Context {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        da: &DrawingArea,
-        win: &gdk::Window,
+        pango_context: &pango::Context,
+        surfaces: &dyn SurfaceProvider,
         font: Font,
         line_space: i64,
         cols: usize,
@@ -46,25 +183,21 @@ impl Context {
         hl_defs: &HlDefs,
         enable_cursor_animations: bool,
     ) -> Self {
-        let pango_context = da.get_pango_context();
-
         let font_desc = font.as_pango_font();
         pango_context.set_font_description(&font_desc);
 
         let mut cell_metrics = CellMetrics::default();
         cell_metrics.font = font;
         cell_metrics.line_space = line_space;
-        cell_metrics.update(&pango_context);
+        cell_metrics.update(pango_context);
 
         let w = cell_metrics.width * cols as f64;
         let h = cell_metrics.height * rows as f64;
-        let surface = win
-            .create_similar_surface(
-                cairo::Content::Color,
-                w.ceil() as i32,
-                h.ceil() as i32,
-            )
-            .unwrap();
+        let surface = surfaces.create_surface(
+            cairo::Content::Color,
+            w.ceil() as i32,
+            h.ceil() as i32,
+        );
 
         let cairo_context = cairo::Context::new(&surface);
 
@@ -78,16 +211,7 @@ impl Context {
         cairo_context.paint();
         cairo_context.restore();
 
-        let cursor_context = {
-            let surface = win
-                .create_similar_surface(
-                    cairo::Content::ColorAlpha,
-                    (cell_metrics.width * 2.0) as i32, // times two for double width chars.
-                    (cell_metrics.height + cell_metrics.ascent).ceil() as i32,
-                )
-                .unwrap();
-            cairo::Context::new(&surface)
-        };
+        let cursor_context = create_cursor_context(&cell_metrics, surfaces);
 
         let cursor = Cursor {
             disable_animation: !enable_cursor_animations,
@@ -96,56 +220,106 @@ impl Context {
 
         Context {
             cairo_context,
+            resize_surface: None,
             cell_metrics,
             cell_metrics_update: None,
             rows: vec![],
 
             cursor,
             cursor_context,
+            mode_blink: (CursorShape::default(), 0),
+            cursor_blinks: HashMap::new(),
 
             busy: false,
+            cursor_visible: true,
+            busy_spinner: false,
+            busy_since: None,
+            spinner_angle: None,
+            fg_color: Color::default(),
+            cursor_over_text: false,
             active: false,
+            inactive_dim: 0.0,
+            invert_colors: false,
+            active_border: None,
+            cursor_outline: None,
+            grid_lines: None,
+            scroll_indicator: None,
+            dim_lines: None,
+            line_scales: BTreeMap::new(),
+            zoom_animation: false,
+            zoom: None,
+            typewriter: None,
+            bg_color: hl_defs.default_bg,
+            letterbox_color: None,
+
+            focused: true,
+            unfocused_cursor: UnfocusedCursor::Hollow,
+
+            flashes: vec![],
+            extra_cursors: vec![],
+            images: vec![],
+            scroll_cache: None,
+
+            gui_selection: false,
+            selection: None,
 
             queue_draw_area: vec![],
+            dirty: true,
+
+            paused: false,
+            tick_active: false,
+            fps: None,
+            last_tick: None,
         }
     }
 
     /// Updates internals that are dependant on the drawing area.
     pub fn resize(
         &mut self,
-        da: &DrawingArea,
-        win: &gdk::Window,
+        pango_context: &pango::Context,
+        surfaces: &dyn SurfaceProvider,
         cols: usize,
         rows: usize,
         hl_defs: &HlDefs,
     ) {
+        if let Some(ref mut cache) = self.scroll_cache {
+            cache.clear();
+        }
+        self.selection = None;
+
         let prev_rows = self.rows.len();
         let prev_cols = self.rows.get(0).map(|r| r.len()).unwrap_or(0);
 
-        if self.rows.len() != rows {
+        // Rows and cells that fall outside of the grid are dropped, so they
+        // don't come back as stale content if the grid grows again.
+        if prev_rows != rows {
             self.rows.resize_with(rows, || Row::new(cols));
+            self.line_scales.retain(|row, _| *row < rows);
         }
 
-        if self.rows.get(0).unwrap().len() != cols {
+        if prev_cols != cols {
             for row in self.rows.iter_mut() {
                 row.resize(cols);
             }
         }
 
-        let pctx = da.get_pango_context();
-        pctx.set_font_description(&self.cell_metrics.font.as_pango_font());
+        let (r, c) = (rows as u64, cols as u64);
+        self.extra_cursors.retain(|(row, col)| *row < r && *col < c);
+        self.images
+            .retain(|i| i.row + i.height <= r && i.col + i.width <= c);
+
+        pango_context
+            .set_font_description(&self.cell_metrics.font.as_pango_font());
 
-        self.cell_metrics.update(&pctx);
+        self.cell_metrics.update(pango_context);
 
         let w = self.cell_metrics.width * cols as f64;
         let h = self.cell_metrics.height * rows as f64;
-        let surface = win
-            .create_similar_surface(
-                cairo::Content::Color,
-                w.ceil() as i32,
-                h.ceil() as i32,
-            )
-            .unwrap();
+        let surface = surfaces.create_surface(
+            cairo::Content::Color,
+            w.ceil() as i32,
+            h.ceil() as i32,
+        );
         let ctx = cairo::Context::new(&surface);
 
         // Fill the context with default bg color.
@@ -177,6 +351,12 @@ impl Context {
         ctx.fill();
         self.cairo_context.restore();
 
+        // Keep the surface that is currently on the screen, if there are
+        // multiple resizes before a flush.
+        if self.resize_surface.is_none() {
+            self.resize_surface = Some(s);
+        }
+
         self.cairo_context = ctx;
     }
 
@@ -187,27 +367,50 @@ impl Context {
         &mut self,
         font: Font,
         line_space: i64,
-        da: &gtk::DrawingArea,
-        win: &gdk::Window,
+        pango_context: &pango::Context,
+        surfaces: &dyn SurfaceProvider,
     ) {
-        let pango_context = da.get_pango_context();
         pango_context.set_font_description(&font.as_pango_font());
 
+        let prev_height = self.cell_metrics.height * self.zoom_scale();
+
         self.cell_metrics.font = font;
         self.cell_metrics.line_space = line_space;
-        self.cell_metrics.update(&pango_context);
-
-        self.cursor_context = {
-            let surface = win
-                .create_similar_surface(
-                    cairo::Content::ColorAlpha,
-                    (self.cell_metrics.width * 2.0).ceil() as i32, // times two for double width chars.
-                    (self.cell_metrics.height + self.cell_metrics.ascent).ceil()
-                        as i32,
-                )
-                .unwrap();
-            cairo::Context::new(&surface)
-        };
+        self.cell_metrics.update(pango_context);
+
+        let from = prev_height / self.cell_metrics.height;
+        if self.zoom_animation
+            && !self.is_static()
+            && prev_height > 0.0
+            && (from - 1.0).abs() > f64::EPSILON
+        {
+            self.zoom = Some(Zoom {
+                from,
+                ready: false,
+                start: None,
+                scale: from,
+            });
+        }
+
+        self.cursor_context =
+            create_cursor_context(&self.cell_metrics, surfaces);
+    }
+
+    /// Creates the surfaces again (e.g. because the window's scale factor
+    /// changed), and renders the content to them.
+    pub fn recreate_surfaces(
+        &mut self,
+        pango_context: &pango::Context,
+        surfaces: &dyn SurfaceProvider,
+        hl_defs: &HlDefs,
+    ) {
+        let rows = self.rows.len();
+        let cols = self.rows.get(0).map(|r| r.len()).unwrap_or(0);
+        self.resize(pango_context, surfaces, cols, rows, hl_defs);
+        self.cursor_context =
+            create_cursor_context(&self.cell_metrics, surfaces);
+
+        render::redraw(self, pango_context, hl_defs);
     }
 
     /// Returns x, y, width and height for cursor position on the screen (e.g. might be in middle
@@ -246,6 +449,9 @@ impl Context {
         ));
         self.cursor
             .goto(row as f64, col as f64, clock.get_frame_time());
+        if self.is_static() {
+            self.cursor.finish_animation();
+        }
 
         // Mark the new cursor position to be drawn.
         let (x, y, w, h) = self.get_cursor_rect();
@@ -257,11 +463,109 @@ impl Context {
         ));
     }
 
+    /// Returns true if the tick is due, according to the frame rate cap.
+    pub fn tick_due(&self, frame_time: i64) -> bool {
+        match (self.fps, self.last_tick) {
+            (Some(0), _) => false,
+            (Some(fps), Some(last)) => {
+                frame_time - last >= 1_000_000 / i64::from(fps)
+            }
+            _ => true,
+        }
+    }
+
+    /// Jumps to the end of all ongoing animations.
+    pub fn finish_animations(&mut self) {
+        self.cursor.finish_animation();
+        self.zoom = None;
+        self.flashes.clear();
+
+        if let Some(ref mut tw) = self.typewriter {
+            tw.offset = tw.to;
+            tw.animating = false;
+        }
+    }
+
+    /// If the animations are disabled, because the tick callback is (see
+    /// `fps`).
+    pub fn is_static(&self) -> bool {
+        self.fps == Some(0)
+    }
+
     pub fn tick(&mut self, da: &DrawingArea, clock: &gdk::FrameClock) {
+        let offset = self.content_offset() as i32;
+
+        // With a frame rate cap, keep the blink at the same pace as with
+        // (assumed) 60hz frames.
+        let now = clock.get_frame_time();
+        if let (Some(_), Some(last)) = (self.fps, self.last_tick) {
+            let frames = ((now - last) as f64 / (1_000_000.0 / 60.0)).round();
+            self.cursor.skip_frames((frames as u32).saturating_sub(1));
+        }
+        self.last_tick = Some(now);
+
         let (x, y, w, h) = self.get_cursor_rect();
-        da.queue_draw_area(x, y, w, h);
+        da.queue_draw_area(x, y + offset, w, h);
+
+        if !self.flashes.is_empty() {
+            for flash in self.flashes.iter_mut() {
+                flash.tick(now);
+
+                for (x, y, w, h) in flash.rects.iter() {
+                    da.queue_draw_area(
+                        x.floor() as i32,
+                        y.floor() as i32 + offset,
+                        w.ceil() as i32,
+                        h.ceil() as i32,
+                    );
+                }
+            }
+
+            // Finished flashes had their area queued for drawing once more
+            // above, so they'll get cleared.
+            self.flashes.retain(|flash| flash.alpha > 0.0);
+        }
+
+        if self.busy && self.busy_spinner {
+            let since = *self.busy_since.get_or_insert(now);
+
+            // One revolution per second.
+            self.spinner_angle = if now - since > BUSY_SPINNER_DELAY {
+                let t = (now % 1_000_000) as f64 / 1_000_000.0;
+                Some(t * 2.0 * std::f64::consts::PI)
+            } else {
+                None
+            };
+        } else {
+            self.busy_since = None;
+            self.spinner_angle = None;
+        }
+
+        if let Some(zoom) = self.zoom.as_mut().filter(|z| z.ready) {
+            let start = *zoom.start.get_or_insert(now);
+            let t = ((now - start) as f64 / ZOOM_DURATION as f64).min(1.0);
+            zoom.scale = zoom.from + (1.0 - zoom.from) * ease_out_cubic(t);
+
+            if t >= 1.0 {
+                self.zoom = None;
+            }
+
+            // The whole grid is scaled.
+            da.queue_draw();
+        }
+
+        if let Some(tw) = self.typewriter.as_mut().filter(|tw| tw.animating) {
+            let start = *tw.start.get_or_insert(now);
+            let t =
+                ((now - start) as f64 / TYPEWRITER_DURATION as f64).min(1.0);
+            tw.offset = tw.from + (tw.to - tw.from) * ease_out_cubic(t);
+            tw.animating = t < 1.0;
+
+            // The whole content moves.
+            da.queue_draw();
+        }
 
-        self.cursor.tick(clock.get_frame_time());
+        self.cursor.tick(now);
 
         // We're not blinking, so skip the blink animation phase.
         if self.cursor.blink_on == 0 {
@@ -270,28 +574,130 @@ impl Context {
 
         let (x, y, w, h) = self.get_cursor_rect();
 
-        let mut alpha = self.cursor.alpha;
-        if alpha > 1.0 {
-            alpha = 2.0 - alpha;
-        }
+        // When the text is drawn over the cursor, the cursor context has the
+        // cell in it and the alpha is applied when drawing to the screen.
+        if !self.cursor_over_text {
+            let alpha = self.cursor_alpha();
 
-        let cr = &self.cursor_context;
-        cr.save();
-        cr.rectangle(0.0, 0.0, 100.0, 100.0);
-        cr.set_operator(cairo::Operator::Source);
-        cr.set_source_rgba(
-            self.cursor.color.r,
-            self.cursor.color.g,
-            self.cursor.color.b,
-            alpha,
-        );
-        cr.fill();
-        cr.restore();
+            let cr = &self.cursor_context;
+            cr.save();
+            cr.rectangle(0.0, 0.0, 100.0, 100.0);
+            cr.set_operator(cairo::Operator::Source);
+            cr.set_source_rgba(
+                self.cursor.color.r,
+                self.cursor.color.g,
+                self.cursor.color.b,
+                alpha,
+            );
+            cr.fill();
+            cr.restore();
+        }
 
         // Don't use the queue_draw_area, because those draws will only
         // happen once nvim sends 'flush' event. This draw needs to happen
         // on each tick so the cursor blinks.
-        da.queue_draw_area(x, y, w, h);
+        da.queue_draw_area(x, y + offset, w, h);
+    }
+
+    /// Returns the current vertical offset of the content (in typewriter
+    /// mode).
+    pub fn content_offset(&self) -> f64 {
+        self.typewriter.as_ref().map_or(0.0, |tw| tw.offset)
+    }
+
+    /// Returns the (row, col) of the cell at `x`, `y` on the screen. The
+    /// position is clamped to the grid, so positions outside of it (e.g. in
+    /// the area left over from the grid or above the content in typewriter
+    /// mode) map to the nearest cell on the grid's edge. Note that `x` and
+    /// `y` are in the same (logical) units as the cell metrics, so the
+    /// device's scale factor doesn't need to be accounted for.
+    pub fn pixel_to_cell(&self, x: f64, y: f64) -> (u64, u64) {
+        let cm = &self.cell_metrics;
+        let rows = self.rows.len() as f64;
+        let cols = self.rows.get(0).map(|r| r.len()).unwrap_or(0) as f64;
+
+        let row = ((y - self.content_offset()) / cm.height).floor();
+        let col = (x / cm.width).floor();
+
+        (
+            row.min(rows - 1.0).max(0.0) as u64,
+            col.min(cols - 1.0).max(0.0) as u64,
+        )
+    }
+
+    /// Starts moving the content so that the cursor's row will be at the
+    /// middle of the grid, if typewriter mode is enabled.
+    pub fn update_typewriter(&mut self) {
+        let row = match self.cursor.get_position() {
+            Some((row, _)) => row,
+            None => return,
+        };
+        let middle = (self.rows.len() / 2) as f64;
+        let to = (middle - row) * self.cell_metrics.height;
+
+        let is_static = self.is_static();
+        let tw = match self.typewriter.as_mut() {
+            Some(tw) if (tw.to - to).abs() > f64::EPSILON => tw,
+            _ => return,
+        };
+
+        tw.from = tw.offset;
+        tw.to = to;
+        tw.start = None;
+        tw.animating = !is_static;
+
+        if is_static {
+            // Without animations, jump straight to the new offset and redraw
+            // the whole grid.
+            tw.offset = to;
+
+            let cm = &self.cell_metrics;
+            let cols = self.rows.get(0).map(|r| r.len()).unwrap_or(0) as f64;
+            self.queue_draw_area.push((
+                0.0,
+                -to,
+                cols * cm.width,
+                self.rows.len() as f64 * cm.height,
+            ));
+        }
+    }
+
+    /// Returns the scale to draw the grid with, because of a zoom animation.
+    pub fn zoom_scale(&self) -> f64 {
+        self.zoom
+            .as_ref()
+            .filter(|zoom| zoom.ready)
+            .map_or(1.0, |zoom| zoom.scale)
+    }
+
+    /// Sets the cursor's blink from the current mode, or from
+    /// `cursor_blinks` if the mode's cursor shape has a blink of its own.
+    pub fn update_cursor_blink(&mut self) {
+        let (ref shape, blink_on) = self.mode_blink;
+        let (on, off, wait) = self
+            .cursor_blinks
+            .get(shape)
+            .copied()
+            .unwrap_or((blink_on, 0, 0));
+
+        self.cursor.blink_on = on;
+        self.cursor.blink_off = off;
+        self.cursor.blink_wait = wait;
+    }
+
+    /// Returns the current alpha of the blinking cursor.
+    pub fn cursor_alpha(&self) -> f64 {
+        // Keep the cursor fully visible while we're not focused.
+        if !self.focused && self.unfocused_cursor == UnfocusedCursor::NoBlink {
+            return 1.0;
+        }
+
+        let alpha = self.cursor.alpha;
+        if alpha > 1.0 {
+            2.0 - alpha
+        } else {
+            alpha
+        }
     }
 
     pub fn cell_at_cursor(&self) -> Option<&Cell> {
@@ -303,8 +709,49 @@ impl Context {
     }
 }
 
+/// Temporary highlight on top of a grid, that fades out.
+pub struct Flash {
+    /// Areas to highlight (x, y, width, height).
+    pub rects: Vec<(f64, f64, f64, f64)>,
+    pub color: Color,
+    /// Frame time (in microseconds) when the flash started.
+    start: i64,
+    /// Duration of the flash in microseconds.
+    duration: i64,
+    /// Current alpha of the highlight. Updated on each tick.
+    pub alpha: f64,
+}
+
+impl Flash {
+    /// Alpha of the highlight when the flash starts.
+    const MAX_ALPHA: f64 = 0.5;
+
+    pub fn new(
+        rects: Vec<(f64, f64, f64, f64)>,
+        color: Color,
+        start: i64,
+        duration_ms: u64,
+    ) -> Self {
+        Flash {
+            rects,
+            color,
+            start,
+            duration: duration_ms as i64 * 1000,
+            alpha: Flash::MAX_ALPHA,
+        }
+    }
+
+    fn tick(&mut self, now: i64) {
+        let t = (now - self.start) as f64 / self.duration as f64;
+        self.alpha = (1.0 - t).max(0.0) * Flash::MAX_ALPHA;
+    }
+}
+
+/// Default scale of the underline's thickness.
+pub const DEFAULT_UNDERLINE_THICKNESS_SCALE: f64 = 2.0;
+
 /// Cell metrics tells the size (and other metrics) of the cells in a grid.
-#[derive(Default, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct CellMetrics {
     pub height: f64,
     pub width: f64,
@@ -312,11 +759,51 @@ pub struct CellMetrics {
     pub decent: f64,
     pub underline_thickness: f64,
     pub underline_position: f64,
+    /// Thickness of the strikethrough line.
+    pub strikethrough_thickness: f64,
+    /// Distance from the baseline to the top of the strikethrough line.
+    pub strikethrough_position: f64,
+
+    /// Multiplier for the font's underline thickness.
+    pub underline_thickness_scale: f64,
+    /// Extra offset (in pixels) for the underline position. Positive values
+    /// move the underline down.
+    pub underline_offset: f64,
+    /// Extra space (in pixels) between the cells.
+    pub letter_spacing: f64,
+    /// Multiplier for the font's character width, for fonts whose cells
+    /// feel too narrow or wide.
+    pub width_scale: f64,
+    /// Space in each cell in addition to the character width (because of
+    /// `letter_spacing` and `width_scale`).
+    pub extra_width: f64,
 
     pub line_space: i64,
     pub font: Font,
 }
 
+impl Default for CellMetrics {
+    fn default() -> Self {
+        CellMetrics {
+            height: 0.0,
+            width: 0.0,
+            ascent: 0.0,
+            decent: 0.0,
+            underline_thickness: 0.0,
+            underline_position: 0.0,
+            strikethrough_thickness: 0.0,
+            strikethrough_position: 0.0,
+            underline_thickness_scale: DEFAULT_UNDERLINE_THICKNESS_SCALE,
+            underline_offset: 0.0,
+            letter_spacing: 0.0,
+            width_scale: 1.0,
+            extra_width: 0.0,
+            line_space: 0,
+            font: Font::default(),
+        }
+    }
+}
+
 impl CellMetrics {
     pub fn update(&mut self, ctx: &pango::Context) {
         let fm = ctx
@@ -327,12 +814,313 @@ impl CellMetrics {
         self.ascent = (f64::from(fm.get_ascent()) / scale + extra).ceil();
         self.decent = (f64::from(fm.get_descent()) / scale + extra).ceil();
         self.height = self.ascent + self.decent;
-        self.width = f64::from(fm.get_approximate_char_width()) / scale;
+        let char_width = f64::from(fm.get_approximate_char_width()) / scale;
+        self.width =
+            (char_width * self.width_scale + self.letter_spacing).max(1.0);
+        self.extra_width = self.width - char_width;
 
         self.underline_position =
-            f64::from(fm.get_underline_position()) / scale - extra;
-        // TODO(ville): make the underline thickness a bit thicker (one 10th of the cell height?).
-        self.underline_thickness =
-            f64::from(fm.get_underline_thickness()) / scale * 2.0;
+            f64::from(fm.get_underline_position()) / scale - extra
+                + self.underline_offset;
+        self.underline_thickness = (f64::from(fm.get_underline_thickness())
+            / scale
+            * self.underline_thickness_scale)
+            .max(1.0);
+
+        self.strikethrough_position =
+            f64::from(fm.get_strikethrough_position()) / scale;
+        self.strikethrough_thickness =
+            (f64::from(fm.get_strikethrough_thickness()) / scale).max(1.0);
+    }
+}
+
+/// Image drawn on top of a grid, scaled to fit the cells it covers.
+pub struct Image {
+    pub row: u64,
+    pub col: u64,
+    /// Width in cells.
+    pub width: u64,
+    /// Height in cells.
+    pub height: u64,
+    pub pixbuf: gdk_pixbuf::Pixbuf,
+}
+
+/// Creates the context for the cursor's cell.
+fn create_cursor_context(
+    cm: &CellMetrics,
+    surfaces: &dyn SurfaceProvider,
+) -> cairo::Context {
+    let surface = surfaces.create_surface(
+        cairo::Content::ColorAlpha,
+        (cm.width * 2.0).ceil() as i32, // times two for double width chars.
+        (cm.height + cm.ascent).ceil() as i32,
+    );
+    cairo::Context::new(&surface)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::nvim_bridge::{self, GridLineSegment};
+    use crate::ui::color::Highlight;
+    use crate::ui::grid::surface::ImageSurfaceProvider;
+
+    /// Returns the (r, g, b) value of the pixel at `x`, `y` in `surface`.
+    fn pixel_at(surface: &cairo::Surface, x: f64, y: f64) -> (u8, u8, u8) {
+        let (w, h) = (x.ceil() as i32 + 1, y.ceil() as i32 + 1);
+        let mut image =
+            cairo::ImageSurface::create(cairo::Format::Rgb24, w, h).unwrap();
+        {
+            let cr = cairo::Context::new(&image);
+            cr.set_source_surface(surface, 0.0, 0.0);
+            cr.paint();
+        }
+        image.flush();
+
+        let stride = image.get_stride() as usize;
+        let data = image.get_data().unwrap();
+        let i = y as usize * stride + x as usize * 4;
+        let px = u32::from_ne_bytes([
+            data[i],
+            data[i + 1],
+            data[i + 2],
+            data[i + 3],
+        ]);
+
+        ((px >> 16) as u8, (px >> 8) as u8, px as u8)
+    }
+
+    /// Creates a context of `cols` x `rows` cells that renders to image
+    /// surfaces, and the pango context it uses.
+    fn test_context(
+        cols: usize,
+        rows: usize,
+        hl_defs: &HlDefs,
+    ) -> (pango::Context, Context) {
+        let surface =
+            cairo::ImageSurface::create(cairo::Format::Rgb24, 1, 1).unwrap();
+        let pango_context = pangocairo::functions::create_context(
+            &cairo::Context::new(&surface),
+        )
+        .unwrap();
+
+        let mut ctx = Context::new(
+            &pango_context,
+            &ImageSurfaceProvider,
+            Font::default(),
+            0,
+            cols,
+            rows,
+            hl_defs,
+            false,
+        );
+        ctx.resize(&pango_context, &ImageSurfaceProvider, cols, rows, hl_defs);
+
+        (pango_context, ctx)
+    }
+
+    #[test]
+    fn test_context_render_to_image_surface() {
+        let mut hl_defs = HlDefs::default();
+        hl_defs.insert(0, Highlight::default());
+        hl_defs.insert(
+            1,
+            Highlight {
+                background: Some(Color {
+                    r: 1.0,
+                    g: 0.0,
+                    b: 0.0,
+                }),
+                ..Highlight::default()
+            },
+        );
+
+        let (pango_context, mut ctx) = test_context(2, 1, &hl_defs);
+
+        render::put_line(
+            &mut ctx,
+            &pango_context,
+            GridLineSegment {
+                grid: 1,
+                row: 0,
+                col_start: 0,
+                cells: vec![nvim_bridge::Cell {
+                    text: String::from(" "),
+                    hl_id: 1,
+                    repeat: 1,
+                    double_width: false,
+                }],
+            },
+            &hl_defs,
+        );
+
+        let cm = &ctx.cell_metrics;
+        let target = ctx.cairo_context.get_target();
+        let y = cm.height / 2.0;
+
+        // First cell has the red background, second the default (black).
+        assert_eq!(pixel_at(&target, cm.width / 2.0, y), (255, 0, 0));
+        assert_eq!(pixel_at(&target, cm.width * 1.5, y), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_context_pixel_to_cell() {
+        let mut hl_defs = HlDefs::default();
+        hl_defs.insert(0, Highlight::default());
+
+        let (_, mut ctx) = test_context(10, 5, &hl_defs);
+
+        let (w, h) = (ctx.cell_metrics.width, ctx.cell_metrics.height);
+
+        assert_eq!(ctx.pixel_to_cell(0.0, 0.0), (0, 0));
+        assert_eq!(ctx.pixel_to_cell(w - 0.1, h - 0.1), (0, 0));
+        assert_eq!(ctx.pixel_to_cell(w, h), (1, 1));
+        assert_eq!(ctx.pixel_to_cell(w * 9.5, h * 4.5), (4, 9));
+
+        // Positions outside of the grid are clamped to its edges.
+        assert_eq!(ctx.pixel_to_cell(-5.0, -5.0), (0, 0));
+        assert_eq!(ctx.pixel_to_cell(w * 10.0, h * 5.0), (4, 9));
+        assert_eq!(ctx.pixel_to_cell(w * 20.0, h * 2.5), (2, 9));
+
+        // The content offset (from typewriter mode) moves the rows.
+        ctx.typewriter = Some(Typewriter {
+            offset: h * 2.0,
+            ..Typewriter::default()
+        });
+        assert_eq!(ctx.pixel_to_cell(0.0, h), (0, 0));
+        assert_eq!(ctx.pixel_to_cell(0.0, h * 2.5), (0, 0));
+        assert_eq!(ctx.pixel_to_cell(0.0, h * 3.5), (1, 0));
+
+        // Edges of the moved grid, and positions outside of it.
+        assert_eq!(ctx.pixel_to_cell(0.0, h * 2.0), (0, 0));
+        assert_eq!(ctx.pixel_to_cell(w * 10.0 - 0.1, h * 3.0 - 0.1), (0, 9));
+        assert_eq!(ctx.pixel_to_cell(w * 10.0 - 0.1, h * 7.0 - 0.1), (4, 9));
+        assert_eq!(ctx.pixel_to_cell(-1.0, h * 2.0 - 0.1), (0, 0));
+        assert_eq!(ctx.pixel_to_cell(w * 3.5, -h), (0, 3));
+        assert_eq!(ctx.pixel_to_cell(w * 3.5, h * 8.0), (4, 3));
+    }
+
+    #[test]
+    fn test_context_resize_cuts_double_width() {
+        let mut hl_defs = HlDefs::default();
+        hl_defs.insert(0, Highlight::default());
+
+        let (pango_context, mut ctx) = test_context(3, 1, &hl_defs);
+
+        render::put_line(
+            &mut ctx,
+            &pango_context,
+            GridLineSegment {
+                grid: 1,
+                row: 0,
+                col_start: 0,
+                cells: vec![
+                    nvim_bridge::Cell {
+                        text: String::from("a"),
+                        hl_id: 0,
+                        repeat: 1,
+                        double_width: false,
+                    },
+                    nvim_bridge::Cell {
+                        text: String::from("\u{4e00}"),
+                        hl_id: 0,
+                        repeat: 1,
+                        double_width: true,
+                    },
+                    nvim_bridge::Cell {
+                        text: String::from(""),
+                        hl_id: 0,
+                        repeat: 1,
+                        double_width: false,
+                    },
+                ],
+            },
+            &hl_defs,
+        );
+
+        // The double width character lands on the last column, without its
+        // second half.
+        ctx.resize(&pango_context, &ImageSurfaceProvider, 2, 1, &hl_defs);
+
+        let cell = ctx.rows[0].cell_at(1).unwrap();
+        assert_eq!(cell.text, " ");
+        assert!(!cell.double_width);
+    }
+
+    #[test]
+    fn test_context_resize_shrink_then_grow() {
+        let mut hl_defs = HlDefs::default();
+        hl_defs.insert(0, Highlight::default());
+        hl_defs.insert(1, Highlight::default());
+
+        let (pango_context, mut ctx) = test_context(3, 2, &hl_defs);
+
+        for row in 0..2 {
+            render::put_line(
+                &mut ctx,
+                &pango_context,
+                GridLineSegment {
+                    grid: 1,
+                    row,
+                    col_start: 0,
+                    cells: vec![nvim_bridge::Cell {
+                        text: String::from("x"),
+                        hl_id: 1,
+                        repeat: 3,
+                        double_width: false,
+                    }],
+                },
+                &hl_defs,
+            );
+        }
+        ctx.extra_cursors = vec![(0, 1), (1, 2)];
+
+        ctx.resize(&pango_context, &ImageSurfaceProvider, 2, 1, &hl_defs);
+        assert_eq!(ctx.rows.len(), 1);
+        assert_eq!(ctx.rows[0].len(), 2);
+        assert_eq!(ctx.extra_cursors, vec![(0, 1)]);
+
+        // The cells that were cut off are blank after growing back.
+        ctx.resize(&pango_context, &ImageSurfaceProvider, 3, 2, &hl_defs);
+        assert_eq!(ctx.rows.len(), 2);
+        for (row, col, text, hl_id) in [
+            (0, 0, "x", 1),
+            (0, 1, "x", 1),
+            (0, 2, " ", 0),
+            (1, 0, " ", 0),
+            (1, 1, " ", 0),
+            (1, 2, " ", 0),
+        ]
+        .iter()
+        {
+            let cell = ctx.rows[*row].cell_at(*col).unwrap();
+            assert_eq!(cell.text, *text);
+            assert_eq!(cell.hl_id, *hl_id);
+        }
+    }
+
+    #[test]
+    fn test_context_update_cursor_blink() {
+        let mut hl_defs = HlDefs::default();
+        hl_defs.insert(0, Highlight::default());
+
+        let (_, mut ctx) = test_context(1, 1, &hl_defs);
+
+        ctx.cursor_blinks
+            .insert(CursorShape::Vertical, (400, 300, 700));
+
+        // Shapes without a blink of their own use the mode's blink.
+        ctx.mode_blink = (CursorShape::Block, 250);
+        ctx.update_cursor_blink();
+        assert_eq!(ctx.cursor.blink_on, 250);
+        assert_eq!(ctx.cursor.blink_off, 0);
+        assert_eq!(ctx.cursor.blink_wait, 0);
+
+        ctx.mode_blink = (CursorShape::Vertical, 250);
+        ctx.update_cursor_blink();
+        assert_eq!(ctx.cursor.blink_on, 400);
+        assert_eq!(ctx.cursor.blink_off, 300);
+        assert_eq!(ctx.cursor.blink_wait, 700);
     }
 }
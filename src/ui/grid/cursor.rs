@@ -20,6 +20,12 @@ pub struct Cursor {
     pub alpha: f64,
     /// The duration of the blink.
     pub blink_on: u64,
+    /// The duration of the blink's hidden half. Zero uses `blink_on`.
+    pub blink_off: u64,
+    /// Delay (in ms) before the blinking starts, after the blink is reset.
+    pub blink_wait: u64,
+    /// Frames left of the `blink_wait` delay.
+    wait_frames: u64,
     /// Width of the cursor.
     pub cell_percentage: f64,
     /// Color of the cursor.
@@ -53,14 +59,48 @@ impl Cursor {
         self.animate_position(frame_time);
     }
 
+    /// Advances the blink by `frames` frames that didn't get a tick, e.g.
+    /// because of a frame rate cap.
+    pub fn skip_frames(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.blink();
+        }
+    }
+
+    /// Moves the cursor to the end of its position animation.
+    pub fn finish_animation(&mut self) {
+        if let Some(animation) = self.animation.take() {
+            self.pos = Some(animation.end);
+        }
+    }
+
+    /// Restarts the blink cycle from a fully visible cursor.
+    pub fn reset_blink(&mut self) {
+        self.alpha = 1.0;
+        // Assuming a 60hz framerate
+        self.wait_frames = self.blink_wait * 6 / 100;
+    }
+
     fn blink(&mut self) {
         // If we dont need to blink, return.
         if self.blink_on == 0 {
             return;
         }
 
+        if self.wait_frames > 0 {
+            self.wait_frames -= 1;
+            return;
+        }
+
+        // Below one, the cursor is fading in from its hidden state.
+        let duration = if self.alpha < 1.0 && self.blink_off > 0 {
+            self.blink_off
+        } else {
+            self.blink_on
+        };
+
         // Assuming a 60hz framerate
-        self.alpha += 100.0 / (6.0 * self.blink_on as f64);
+        self.alpha += 100.0 / (6.0 * duration as f64);
 
         if self.alpha > 2.0 {
             self.alpha = 0.0;
@@ -106,7 +146,7 @@ impl Cursor {
 
 /// From clutter-easing.c, based on Robert Penner's
 /// infamous easing equations, MIT license.
-fn ease_out_cubic(t: f64) -> f64 {
+pub fn ease_out_cubic(t: f64) -> f64 {
     let p = t - 1f64;
     p * p * p + 1f64
 }
@@ -135,6 +175,52 @@ mod tests {
         assert_eq!(cursor.alpha, 1.0);
     }
 
+    #[test]
+    fn test_cursor_reset_blink() {
+        let mut cursor = Cursor::default();
+        cursor.blink_on = 100;
+        cursor.alpha = 1.5;
+
+        cursor.reset_blink();
+        assert_eq!(cursor.alpha, 1.0);
+
+        // The cycle continues from the fully visible cursor.
+        cursor.blink();
+        assert_eq!(cursor.alpha, 1.1666666666666667);
+    }
+
+    #[test]
+    fn test_cursor_blink_off() {
+        let mut cursor = Cursor::default();
+        cursor.blink_on = 100;
+        cursor.blink_off = 200;
+        cursor.alpha = 1.0;
+
+        cursor.blink();
+        assert_eq!(cursor.alpha, 1.1666666666666667);
+
+        // While hidden, the cursor uses the off duration.
+        cursor.alpha = 0.5;
+        cursor.blink();
+        assert!((cursor.alpha - 0.5833333333333334).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_cursor_blink_wait() {
+        let mut cursor = Cursor::default();
+        cursor.blink_on = 100;
+        cursor.blink_wait = 50;
+        cursor.alpha = 1.5;
+
+        // 50ms is three frames at 60hz.
+        cursor.reset_blink();
+        cursor.skip_frames(3);
+        assert_eq!(cursor.alpha, 1.0);
+
+        cursor.blink();
+        assert_eq!(cursor.alpha, 1.1666666666666667);
+    }
+
     #[test]
     fn test_first_position() {
         let mut cursor = Cursor::default();
@@ -179,6 +265,29 @@ mod tests {
         assert_eq!(cursor.pos, Some((10.0, 10.0)));
     }
 
+    #[test]
+    fn test_finish_animation() {
+        let mut cursor = Cursor::default();
+
+        cursor.goto(15.0, 15.0, 1);
+        cursor.goto(10.0, 10.0, 1);
+        assert_eq!(cursor.pos, Some((15.0, 15.0)));
+
+        cursor.finish_animation();
+        assert_eq!(cursor.pos, Some((10.0, 10.0)));
+        assert!(cursor.animation.is_none());
+    }
+
+    #[test]
+    fn test_skip_frames() {
+        let mut cursor = Cursor::default();
+        cursor.blink_on = 100;
+        cursor.alpha = 1.0;
+
+        cursor.skip_frames(3);
+        assert!((cursor.alpha - 1.5).abs() < 1e-9);
+    }
+
     #[test]
     fn test_get_position() {
         let mut cursor = Cursor::default();
@@ -1,18 +1,25 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Display;
 use std::rc::Rc;
 
+use gdk::prelude::GdkContextExt;
 use gdk::{EventMask, ModifierType};
 use gtk::{DrawingArea, EventBox};
 
 use gtk::prelude::*;
 
-use crate::nvim_bridge::{GridLineSegment, ModeInfo};
-use crate::ui::color::HlDefs;
+use crate::nvim_bridge::{
+    CursorShape, GridLineSegment, ModeInfo, UnfocusedCursor,
+};
+use crate::ui::clipboard;
+use crate::ui::color::{Color, HlDefs};
 use crate::ui::font::Font;
-use crate::ui::grid::context::Context;
+use crate::ui::grid::context::{Context, Flash, Image, Typewriter};
 use crate::ui::grid::render;
+use crate::ui::grid::scroll_cache::ScrollCache;
+use crate::ui::grid::selection::Selection;
 
 pub struct GridMetrics {
     // Row count in the grid.
@@ -35,6 +42,16 @@ pub enum ScrollDirection {
     Down,
 }
 
+impl ScrollDirection {
+    /// Returns the opposite direction.
+    pub fn reversed(&self) -> Self {
+        match self {
+            ScrollDirection::Up => ScrollDirection::Down,
+            ScrollDirection::Down => ScrollDirection::Up,
+        }
+    }
+}
+
 impl Display for ScrollDirection {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -48,6 +65,10 @@ pub enum MouseButton {
     Left,
     Middle,
     Right,
+    /// The extra "back" button.
+    X1,
+    /// The extra "forward" button.
+    X2,
 }
 
 impl Display for MouseButton {
@@ -56,6 +77,8 @@ impl Display for MouseButton {
             MouseButton::Left => write!(fmt, "left"),
             MouseButton::Middle => write!(fmt, "middle"),
             MouseButton::Right => write!(fmt, "right"),
+            MouseButton::X1 => write!(fmt, "x1"),
+            MouseButton::X2 => write!(fmt, "x2"),
         }
     }
 }
@@ -91,7 +114,7 @@ impl Grid {
     ) -> Self {
         let da = DrawingArea::new();
         let ctx = Rc::new(RefCell::new(Context::new(
-            &da,
+            &da.get_pango_context(),
             win,
             font,
             line_space,
@@ -111,11 +134,9 @@ impl Grid {
         eb.add_events(EventMask::SCROLL_MASK);
         eb.add(&da);
 
-        da.add_tick_callback(clone!(ctx => move |da, clock| {
-            let mut ctx = ctx.borrow_mut();
-            ctx.tick(da, clock);
-            glib::Continue(true)
-        }));
+        add_tick_callback(&da, ctx.clone());
+
+        connect_gui_selection(&eb, &da, ctx.clone());
 
         Grid {
             id,
@@ -131,13 +152,24 @@ impl Grid {
         self.eb.clone().upcast()
     }
 
+    /// Draws the changes made since the previous flush to the screen. Does
+    /// nothing if there are no changes.
     pub fn flush(&self, hl_defs: &HlDefs) {
         let mut ctx = self.context.borrow_mut();
 
+        if !ctx.dirty {
+            return;
+        }
+        ctx.dirty = false;
+
         if let Some(cell) = ctx.cell_at_cursor() {
-            // If cursor isn't blinking, drawn the inverted cell into
-            // the cursor's cairo context.
-            if ctx.cursor.blink_on == 0 {
+            // If cursor isn't blinking (or the text should be visible over
+            // the cursor), drawn the inverted cell into the cursor's cairo
+            // context.
+            if ctx.cursor.blink_on == 0
+                || ctx.cursor_over_text
+                || ctx.is_static()
+            {
                 render::cursor_cell(
                     &ctx.cursor_context,
                     &self.da.get_pango_context(),
@@ -152,10 +184,26 @@ impl Grid {
             ctx.cursor.color = hl.foreground.unwrap_or(hl_defs.default_fg);
         }
 
+        ctx.fg_color = hl_defs.default_fg;
+        ctx.bg_color = hl_defs.default_bg;
+
+        // The grid now has content drawn with the new cell size, so the zoom
+        // animation can start.
+        if let Some(ref mut zoom) = ctx.zoom {
+            zoom.ready = true;
+        }
+
+        // Now that we have the new content, draw the whole grid from the
+        // resized surface.
+        if ctx.resize_surface.take().is_some() {
+            self.da.queue_draw();
+        }
+
+        let offset = ctx.content_offset();
         while let Some(area) = ctx.queue_draw_area.pop() {
             self.da.queue_draw_area(
                 area.0.floor() as i32,
-                area.1.floor() as i32,
+                (area.1 + offset).floor() as i32,
                 area.2.ceil() as i32,
                 area.3.ceil() as i32,
             );
@@ -178,6 +226,7 @@ impl Grid {
             row as f64,
             col as f64,
         );
+        let y = y + ctx.content_offset();
 
         let (x, y) = self
             .eb
@@ -213,8 +262,7 @@ impl Grid {
             };
 
             let pos = e.get_position();
-            let col = (pos.0 / ctx.cell_metrics.width).floor() as u64;
-            let row = (pos.1 / ctx.cell_metrics.height).floor() as u64;
+            let (row, col) = ctx.pixel_to_cell(pos.0, pos.1);
 
             f(dir, row, col)
         });
@@ -240,8 +288,7 @@ impl Grid {
             };
 
             let pos = e.get_position();
-            let col = (pos.0 / ctx.cell_metrics.width).floor() as u64;
-            let row = (pos.1 / ctx.cell_metrics.height).floor() as u64;
+            let (row, col) = ctx.pixel_to_cell(pos.0, pos.1);
 
             if drag_position.0 != col || drag_position.1 != row {
                 *drag_position = (col, row);
@@ -266,12 +313,13 @@ impl Grid {
             let button = match e.get_button() {
                 3 => MouseButton::Right,
                 2 => MouseButton::Middle,
+                8 => MouseButton::X1,
+                9 => MouseButton::X2,
                 _ => MouseButton::Left,
             };
 
             let pos = e.get_position();
-            let col = (pos.0 / ctx.cell_metrics.width).floor() as u64;
-            let row = (pos.1 / ctx.cell_metrics.height).floor() as u64;
+            let (row, col) = ctx.pixel_to_cell(pos.0, pos.1);
 
             f(button, row, col)
         });
@@ -291,12 +339,13 @@ impl Grid {
             let button = match e.get_button() {
                 3 => MouseButton::Right,
                 2 => MouseButton::Middle,
+                8 => MouseButton::X1,
+                9 => MouseButton::X2,
                 _ => MouseButton::Left,
             };
 
             let pos = e.get_position();
-            let col = (pos.0 / ctx.cell_metrics.width).floor() as u64;
-            let row = (pos.1 / ctx.cell_metrics.height).floor() as u64;
+            let (row, col) = ctx.pixel_to_cell(pos.0, pos.1);
 
             f(button, row, col)
         });
@@ -323,25 +372,61 @@ impl Grid {
 
     pub fn put_line(&self, line: GridLineSegment, hl_defs: &HlDefs) {
         let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
 
         render::put_line(&mut ctx, &self.da.get_pango_context(), line, hl_defs);
     }
 
     pub fn redraw(&self, hl_defs: &HlDefs) {
         let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
         render::redraw(&mut ctx, &self.da.get_pango_context(), hl_defs);
     }
 
-    pub fn cursor_goto(&self, row: u64, col: u64) {
+    pub fn cursor_goto(&self, row: u64, col: u64, hl_defs: &HlDefs) {
         let clock = self.da.get_frame_clock().unwrap();
         let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
+        let prev = ctx
+            .cursor
+            .get_position()
+            .map(|(r, c)| (r as usize, c as usize));
+        let prev_row = prev.map(|(r, _)| r);
         ctx.cursor_goto(row, col, &clock);
 
+        // Redraw the cells around the old and the new cursor position, so any
+        // ligature under the cursor is broken up (or restored).
+        let pango_context = self.da.get_pango_context();
+        let (row, col) = (row as usize, col as usize);
+        let new_run = ctx.rows.get(row).and_then(|r| r.run_at(col));
+        if let Some((prev_row, prev_col)) = prev {
+            let prev_run =
+                ctx.rows.get(prev_row).and_then(|r| r.run_at(prev_col));
+            // The new run is drawn below anyway.
+            if prev_row != row || prev_run != new_run {
+                render::redraw_cursor_run(
+                    &mut ctx,
+                    &pango_context,
+                    hl_defs,
+                    prev_row,
+                    prev_col,
+                );
+            }
+        }
+        render::redraw_cursor_run(&mut ctx, &pango_context, hl_defs, row, col);
+
+        // The dimmed lines follow the cursor.
+        if ctx.dim_lines.is_some() && prev_row != Some(row) {
+            self.da.queue_draw();
+        }
+
+        ctx.update_typewriter();
+
         let (x, y, width, height) = ctx.get_cursor_rect();
         if let Some(ref im_context) = self.im_context {
             let rect = gdk::Rectangle {
                 x,
-                y,
+                y: y + ctx.content_offset() as i32,
                 width,
                 height,
             };
@@ -390,17 +475,31 @@ impl Grid {
         hl_defs: &HlDefs,
     ) {
         let mut ctx = self.context.borrow_mut();
-        ctx.resize(&self.da, win, cols as usize, rows as usize, hl_defs);
+        ctx.dirty = true;
+        ctx.resize(
+            &self.da.get_pango_context(),
+            win,
+            cols as usize,
+            rows as usize,
+            hl_defs,
+        );
+        ctx.update_typewriter();
     }
 
     pub fn clear(&self, hl_defs: &HlDefs) {
         let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
 
         // Clear internal grid (rows).
         for row in ctx.rows.iter_mut() {
             row.clear();
         }
 
+        if let Some(ref mut cache) = ctx.scroll_cache {
+            cache.clear();
+        }
+        ctx.selection = None;
+
         render::clear(&self.da, &mut ctx, hl_defs)
     }
 
@@ -412,16 +511,176 @@ impl Grid {
         hl_defs: &HlDefs,
     ) {
         let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
 
-        render::scroll(&mut ctx, hl_defs, reg, rows);
+        let restored = render::scroll(&mut ctx, hl_defs, reg, rows);
+
+        let pango_context = self.da.get_pango_context();
+        for row in restored {
+            render::redraw_row(&mut ctx, &pango_context, hl_defs, row);
+        }
     }
 
     pub fn set_active(&self, active: bool) {
         let mut ctx = self.context.borrow_mut();
 
+        // The whole grid needs to be drawn again for the dim (or the
+        // border) to change.
+        if ctx.active != active
+            && (ctx.inactive_dim > 0.0 || ctx.active_border.is_some())
+        {
+            self.da.queue_draw();
+        }
+
         ctx.active = active;
     }
 
+    /// Sets the dim factor for when this grid is not active.
+    pub fn set_inactive_dim(&self, dim: f64) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.inactive_dim = dim;
+
+        if !ctx.active {
+            self.da.queue_draw();
+        }
+    }
+
+    /// Sets the dimming of the lines outside of the band around the cursor.
+    pub fn set_dim_lines(&self, dim_lines: Option<(u64, u64, f64)>) {
+        let mut ctx = self.context.borrow_mut();
+        if ctx.dim_lines != dim_lines {
+            ctx.dim_lines = dim_lines;
+            self.da.queue_draw();
+        }
+    }
+
+    /// Sets the scale `row` is drawn with. Scale of 1 (or 0) draws the row
+    /// normally.
+    pub fn set_line_scale(&self, row: u64, scale: u64) {
+        let mut ctx = self.context.borrow_mut();
+        let row = row as usize;
+        if row >= ctx.rows.len() {
+            return;
+        }
+
+        let changed = if scale > 1 {
+            ctx.line_scales.insert(row, scale) != Some(scale)
+        } else {
+            ctx.line_scales.remove(&row).is_some()
+        };
+
+        if changed {
+            self.da.queue_draw();
+        }
+    }
+
+    /// Sets the color for the area outside of the grid. `None` uses the
+    /// default background color.
+    pub fn set_letterbox_color(&self, color: Option<Color>) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.letterbox_color = color;
+        self.da.queue_draw();
+    }
+
+    /// Sets the border drawn around the grid when it is active.
+    pub fn set_active_border(&self, border: Option<(Color, f64)>) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.active_border = border;
+        self.da.queue_draw();
+    }
+
+    /// Sets the color and width of the outline around the cursor. `None`
+    /// disables the outline.
+    pub fn set_cursor_outline(&self, outline: Option<(Color, f64)>) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.cursor_outline = outline;
+
+        let (x, y, w, h) = ctx.get_cursor_rect();
+        let offset = ctx.content_offset() as i32;
+        self.da.queue_draw_area(x, y + offset, w, h);
+    }
+
+    /// Sets the color of the lines drawn at the cell boundaries. `None`
+    /// disables the lines.
+    pub fn set_grid_lines(&self, color: Option<Color>) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.grid_lines = color;
+        self.da.queue_draw();
+    }
+
+    /// Sets the current line and total lines of the buffer shown in the
+    /// grid, for the scroll indicator. `None` hides the indicator.
+    pub fn set_scroll_indicator(&self, position: Option<(u64, u64)>) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.scroll_indicator = position;
+        self.da.queue_draw();
+    }
+
+    /// Sets if the colors of the grid should be drawn inverted.
+    pub fn set_invert_colors(&self, invert: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.invert_colors = invert;
+
+        self.da.queue_draw();
+    }
+
+    /// Sets if the window this grid is in has the keyboard focus.
+    pub fn set_focused(&self, focused: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.focused = focused;
+
+        let (x, y, w, h) = ctx.get_cursor_rect();
+        let offset = ctx.content_offset() as i32;
+        self.da.queue_draw_area(x, y + offset, w, h);
+    }
+
+    /// Sets how the cursor is drawn when the window is not focused.
+    pub fn set_unfocused_cursor(&self, cursor: UnfocusedCursor) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.unfocused_cursor = cursor;
+    }
+
+    /// Highlights `ranges` (row, start_col, end_col) with `color`. The
+    /// highlight fades out over `duration` milliseconds.
+    pub fn flash(
+        &self,
+        ranges: &[(u64, u64, u64)],
+        color: Color,
+        duration: u64,
+    ) {
+        if duration == 0 || self.context.borrow().is_static() {
+            return;
+        }
+
+        let clock = match self.da.get_frame_clock() {
+            Some(clock) => clock,
+            None => return,
+        };
+
+        let mut ctx = self.context.borrow_mut();
+        let cm = &ctx.cell_metrics;
+        let rects = ranges
+            .iter()
+            .filter(|(_, start, end)| end > start)
+            .map(|(row, start, end)| {
+                let (x, y) = render::get_coords(
+                    cm.height,
+                    cm.width,
+                    *row as f64,
+                    *start as f64,
+                );
+                (x, y, (end - start) as f64 * cm.width, cm.height)
+            })
+            .collect();
+
+        ctx.flashes.push(Flash::new(
+            rects,
+            color,
+            clock.get_frame_time(),
+            duration,
+        ));
+    }
+
     /// Set a new font and line space. This will likely change the cell metrics.
     /// Use `calc_size` to receive the updated size (cols and rows) of the grid.
     pub fn update_cell_metrics(
@@ -431,7 +690,51 @@ impl Grid {
         win: &gdk::Window,
     ) {
         let mut ctx = self.context.borrow_mut();
-        ctx.update_metrics(font, line_space, &self.da, win);
+        ctx.dirty = true;
+        ctx.update_metrics(font, line_space, &self.da.get_pango_context(), win);
+    }
+
+    /// Renders the grid again to new surfaces, because the window's scale
+    /// factor changed.
+    pub fn update_scale_factor(&self, win: &gdk::Window, hl_defs: &HlDefs) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
+        ctx.recreate_surfaces(&self.da.get_pango_context(), win, hl_defs);
+    }
+
+    /// Sets the underline thickness multiplier and position offset. Call
+    /// `redraw` to make the change visible.
+    pub fn set_underline_style(&self, scale: f64, offset: f64) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
+
+        ctx.cell_metrics.underline_thickness_scale = scale;
+        ctx.cell_metrics.underline_offset = offset;
+        ctx.cell_metrics.update(&self.da.get_pango_context());
+    }
+
+    /// Sets the extra space between cells. This changes the cell metrics, so
+    /// use `calc_size` to receive the updated size of the grid.
+    pub fn set_letter_spacing(&self, spacing: f64, win: &gdk::Window) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
+        ctx.cell_metrics.letter_spacing = spacing;
+
+        let font = ctx.cell_metrics.font.clone();
+        let line_space = ctx.cell_metrics.line_space;
+        ctx.update_metrics(font, line_space, &self.da.get_pango_context(), win);
+    }
+
+    /// Sets the multiplier for the cells' width. This changes the cell
+    /// metrics, so use `calc_size` to receive the updated size of the grid.
+    pub fn set_cell_width_scale(&self, scale: f64, win: &gdk::Window) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
+        ctx.cell_metrics.width_scale = scale;
+
+        let font = ctx.cell_metrics.font.clone();
+        let line_space = ctx.cell_metrics.line_space;
+        ctx.update_metrics(font, line_space, &self.da.get_pango_context(), win);
     }
 
     /// Get the current line space value.
@@ -440,6 +743,13 @@ impl Grid {
         ctx.cell_metrics.line_space
     }
 
+    /// Returns the text of each row, along with the highlight ids of its
+    /// cells.
+    pub fn get_text(&self) -> Vec<(String, Vec<u64>)> {
+        let ctx = self.context.borrow();
+        ctx.rows.iter().map(|row| row.text_with_hl()).collect()
+    }
+
     /// Get a copy of the current font.
     pub fn get_font(&self) -> Font {
         let ctx = self.context.borrow();
@@ -448,9 +758,29 @@ impl Grid {
 
     pub fn set_mode(&self, mode: &ModeInfo) {
         let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
 
-        ctx.cursor.blink_on = mode.blink_on;
+        ctx.mode_blink = (mode.cursor_shape.clone(), mode.blink_on);
+        ctx.update_cursor_blink();
         ctx.cursor.cell_percentage = mode.cell_percentage;
+
+        // Show the new cursor shape solid right away, instead of continuing
+        // from wherever the blink was.
+        ctx.cursor.reset_blink();
+    }
+
+    /// Sets the blink timings (on, off, wait) of specific cursor shapes,
+    /// overriding the blink from the mode.
+    pub fn set_cursor_blinks(
+        &self,
+        blinks: HashMap<CursorShape, (u64, u64, u64)>,
+    ) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
+
+        ctx.cursor_blinks = blinks;
+        ctx.update_cursor_blink();
+        ctx.cursor.reset_blink();
     }
 
     pub fn set_busy(&self, busy: bool) {
@@ -459,38 +789,607 @@ impl Grid {
         ctx.busy = busy;
     }
 
+    /// Sets if changes to the cell size (e.g. font zoom) are animated.
+    pub fn set_zoom_animation(&self, enable: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.zoom_animation = enable;
+        if !enable {
+            ctx.zoom = None;
+        }
+    }
+
+    /// Sets typewriter mode, where the content is moved so that the cursor's
+    /// row stays at the middle of the grid.
+    pub fn set_typewriter_mode(&self, enable: bool) {
+        let mut ctx = self.context.borrow_mut();
+        if enable == ctx.typewriter.is_some() {
+            return;
+        }
+
+        ctx.typewriter = if enable {
+            Some(Typewriter::default())
+        } else {
+            None
+        };
+        ctx.update_typewriter();
+        self.da.queue_draw();
+    }
+
+    /// Sets if a spinner is drawn at the cursor's position while nvim is
+    /// busy.
+    pub fn set_busy_spinner(&self, enable: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.busy_spinner = enable;
+    }
+
+    /// Sets if the cursor should be drawn or not.
+    pub fn set_cursor_visible(&self, visible: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.cursor_visible = visible;
+
+        let (x, y, w, h) = ctx.get_cursor_rect();
+        let offset = ctx.content_offset() as i32;
+        self.da.queue_draw_area(x, y + offset, w, h);
+    }
+
+    /// Sets the positions (row, col) of extra cursors drawn in this grid.
+    pub fn set_extra_cursors(&self, cursors: Vec<(u64, u64)>) {
+        let mut ctx = self.context.borrow_mut();
+
+        if ctx.extra_cursors.is_empty() && cursors.is_empty() {
+            return;
+        }
+
+        ctx.extra_cursors = cursors;
+        self.da.queue_draw();
+    }
+
+    /// Draws `pixbuf` on top of the grid, scaled to fit the area of `width`
+    /// x `height` cells starting from (`row`, `col`).
+    pub fn place_image(
+        &self,
+        row: u64,
+        col: u64,
+        width: u64,
+        height: u64,
+        pixbuf: gdk_pixbuf::Pixbuf,
+    ) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.images.push(Image {
+            row,
+            col,
+            width,
+            height,
+            pixbuf,
+        });
+        self.da.queue_draw();
+    }
+
+    /// Removes the images placed with `place_image`.
+    pub fn clear_images(&self) {
+        let mut ctx = self.context.borrow_mut();
+        if !ctx.images.is_empty() {
+            ctx.images.clear();
+            self.da.queue_draw();
+        }
+    }
+
+    /// Sets if the text under the cursor is drawn over the cursor also when
+    /// the cursor blinks. Call `flush` to make the change visible.
+    pub fn set_cursor_over_text(&self, over_text: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.dirty = true;
+        ctx.cursor_over_text = over_text;
+    }
+
+    /// Sets if rows scrolled out of the grid are cached, so they can be
+    /// drawn right away when scrolled back in.
+    pub fn set_scroll_cache(&self, enable: bool) {
+        let mut ctx = self.context.borrow_mut();
+        if enable == ctx.scroll_cache.is_some() {
+            return;
+        }
+
+        ctx.scroll_cache = if enable {
+            Some(ScrollCache::default())
+        } else {
+            None
+        };
+    }
+
+    /// Sets if text can be selected with the mouse on the GUI side. The
+    /// selected text is copied to the clipboard.
+    pub fn set_gui_selection(&self, enable: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.gui_selection = enable;
+
+        if !enable && ctx.selection.take().is_some() {
+            self.da.queue_draw();
+        }
+    }
+
+    /// Pauses or resumes the animations and the cursor blinking. When
+    /// resumed, the grid is redrawn right away.
+    pub fn set_paused(&self, paused: bool) {
+        let mut ctx = self.context.borrow_mut();
+        ctx.paused = paused;
+
+        if !paused {
+            if !ctx.tick_active && !ctx.is_static() {
+                drop(ctx);
+                add_tick_callback(&self.da, self.context.clone());
+            }
+
+            self.da.queue_draw();
+        }
+    }
+
+    /// Sets the maximum rate (per second) of the animation ticks. `None`
+    /// ticks on every frame, and zero disables the animations (e.g. the
+    /// cursor doesn't blink).
+    pub fn set_animation_fps(&self, fps: Option<u32>, hl_defs: &HlDefs) {
+        let mut ctx = self.context.borrow_mut();
+        if ctx.fps == fps {
+            return;
+        }
+
+        ctx.fps = fps;
+        ctx.last_tick = None;
+
+        if ctx.is_static() {
+            // The tick callback removes itself on the next frame. Leave the
+            // cursor fully visible, and have it drawn with its cell.
+            ctx.finish_animations();
+            ctx.cursor.reset_blink();
+            ctx.dirty = true;
+            drop(ctx);
+
+            self.flush(hl_defs);
+            self.da.queue_draw();
+        } else if !ctx.tick_active && !ctx.paused {
+            drop(ctx);
+            add_tick_callback(&self.da, self.context.clone());
+        }
+    }
+
     pub fn enable_cursor_animations(&self, enable: bool) {
         let mut ctx = self.context.borrow_mut();
         ctx.cursor.disable_animation = !enable;
     }
 }
 
+/// Adds the tick callback that drives the animations and the cursor
+/// blinking. The callback removes itself when the context is paused or the
+/// animations are disabled.
+fn add_tick_callback(da: &DrawingArea, ctx: Rc<RefCell<Context>>) {
+    ctx.borrow_mut().tick_active = true;
+
+    da.add_tick_callback(move |da, clock| {
+        let mut ctx = ctx.borrow_mut();
+        if ctx.paused || ctx.is_static() {
+            ctx.tick_active = false;
+            return glib::Continue(false);
+        }
+
+        if ctx.tick_due(clock.get_frame_time()) {
+            ctx.tick(da, clock);
+        }
+        glib::Continue(true)
+    });
+}
+
+/// Connects the mouse events for the GUI side selection (see
+/// `Grid::set_gui_selection`).
+fn connect_gui_selection(
+    eb: &EventBox,
+    da: &DrawingArea,
+    ctx: Rc<RefCell<Context>>,
+) {
+    eb.connect_button_press_event(clone!(ctx, da => move |_, e| {
+        let mut ctx = ctx.borrow_mut();
+        if !ctx.gui_selection || e.get_button() != 1 {
+            return Inhibit(false);
+        }
+
+        let pos = e.get_position();
+        let (row, col) = ctx.pixel_to_cell(pos.0, pos.1);
+
+        ctx.selection = Some(Selection::new(row, col));
+        da.queue_draw();
+
+        Inhibit(false)
+    }));
+
+    eb.connect_motion_notify_event(clone!(ctx, da => move |_, e| {
+        let mut ctx = ctx.borrow_mut();
+        if !ctx.gui_selection
+            || !e.get_state().contains(ModifierType::BUTTON1_MASK)
+        {
+            return Inhibit(false);
+        }
+
+        let pos = e.get_position();
+        let (row, col) = ctx.pixel_to_cell(pos.0, pos.1);
+
+        if let Some(ref mut selection) = ctx.selection {
+            if selection.end != (row, col) {
+                selection.end = (row, col);
+                da.queue_draw();
+            }
+        }
+
+        Inhibit(false)
+    }));
+
+    eb.connect_button_release_event(move |_, e| {
+        let mut ctx = ctx.borrow_mut();
+        if !ctx.gui_selection || e.get_button() != 1 {
+            return Inhibit(false);
+        }
+
+        let selection = match ctx.selection {
+            Some(selection) => selection,
+            None => return Inhibit(false),
+        };
+
+        if selection.is_empty() {
+            // Just a click, not a selection.
+            ctx.selection = None;
+            da.queue_draw();
+        } else {
+            let text = selection.text(&ctx.rows);
+            clipboard::set_text(&text);
+        }
+
+        Inhibit(false)
+    });
+}
+
 /// Handler for grid's drawingarea's draw event. Draws the internal cairo
 /// context (`ctx`) surface to the `cr`.
 fn drawingarea_draw(cr: &cairo::Context, ctx: &mut Context) {
-    let surface = ctx.cairo_context.get_target();
+    let surface = ctx
+        .resize_surface
+        .clone()
+        .unwrap_or_else(|| ctx.cairo_context.get_target());
     surface.flush();
 
+    // Fill the area outside of the grid, left over when the size of the
+    // drawing area isn't a multiple of the cell size.
+    let letterbox = ctx.letterbox_color.unwrap_or(ctx.bg_color);
+    cr.save();
+    cr.set_source_rgb(letterbox.r, letterbox.g, letterbox.b);
+    cr.paint();
+    cr.restore();
+
+    // In typewriter mode, the content is moved vertically, so fill the area
+    // it leaves uncovered.
+    if ctx.typewriter.is_some() {
+        let cm = &ctx.cell_metrics;
+        let cols = ctx.rows.get(0).map(|r| r.len()).unwrap_or(0) as f64;
+        let rows = ctx.rows.len() as f64;
+        let bg = &ctx.bg_color;
+        cr.save();
+        cr.set_source_rgb(bg.r, bg.g, bg.b);
+        cr.rectangle(0.0, 0.0, cols * cm.width, rows * cm.height);
+        cr.fill();
+        cr.restore();
+    }
+
+    // While zooming, everything is drawn scaled.
+    let zoom_scale = ctx.zoom_scale();
+    cr.save();
+    cr.translate(0.0, ctx.content_offset());
+    cr.scale(zoom_scale, zoom_scale);
+
     cr.save();
     cr.set_source_surface(&surface, 0.0, 0.0);
     cr.paint();
     cr.restore();
 
-    // If we're not "busy", draw the cursor.
-    if !ctx.busy && ctx.active {
+    // Scaled rows are drawn on top of the rows below them, leaving out the
+    // columns that don't fit.
+    for (row, scale) in ctx.line_scales.iter() {
+        let cm = &ctx.cell_metrics;
+        let scale = *scale as f64;
+        let cols = ctx.rows.get(0).map(|r| r.len()).unwrap_or(0) as f64;
+        let rows = ctx.rows.len() as f64;
+        let (x1, y1, x2, y2) = render::get_rect(
+            cm.height,
+            cm.width,
+            *row as f64,
+            (*row as f64 + scale).min(rows),
+            0.0,
+            cols,
+        );
+
+        cr.save();
+        cr.rectangle(x1, y1, x2 - x1, y2 - y1);
+        cr.clip();
+        cr.translate(x1, y1);
+        cr.scale(scale, scale);
+        cr.set_source_surface(&surface, -x1, -y1);
+        // Only the original row should be scaled, not the ones below it.
+        cr.rectangle(0.0, 0.0, x2 - x1, cm.height);
+        cr.fill();
+        cr.restore();
+    }
+
+    for image in ctx.images.iter() {
+        let cm = &ctx.cell_metrics;
+        let (x1, y1, x2, y2) = render::get_rect(
+            cm.height,
+            cm.width,
+            image.row as f64,
+            (image.row + image.height) as f64,
+            image.col as f64,
+            (image.col + image.width) as f64,
+        );
+        let (w, h) = (x2 - x1, y2 - y1);
+
+        // Scale the image to fit the area, keeping its aspect ratio.
+        let pw = f64::from(image.pixbuf.get_width());
+        let ph = f64::from(image.pixbuf.get_height());
+        let scale = (w / pw).min(h / ph);
+
+        cr.save();
+        cr.rectangle(x1, y1, w, h);
+        cr.clip();
+        cr.translate(x1, y1);
+        cr.scale(scale, scale);
+        cr.set_source_pixbuf(&image.pixbuf, 0.0, 0.0);
+        cr.paint();
+        cr.restore();
+    }
+
+    // Faint lines at the cell boundaries, to see how the content aligns to
+    // the cells.
+    if let Some(color) = ctx.grid_lines {
+        let cm = &ctx.cell_metrics;
+        let rows = ctx.rows.len();
+        let cols = ctx.rows.get(0).map(|r| r.len()).unwrap_or(0);
+        let (w, h) = (cols as f64 * cm.width, rows as f64 * cm.height);
+
+        cr.save();
+        cr.set_line_width(1.0);
+        cr.set_source_rgba(color.r, color.g, color.b, 0.3);
+        for col in 0..=cols {
+            let x = (col as f64 * cm.width).floor() + 0.5;
+            cr.move_to(x, 0.0);
+            cr.line_to(x, h);
+        }
+        for row in 0..=rows {
+            let y = (row as f64 * cm.height).floor() + 0.5;
+            cr.move_to(0.0, y);
+            cr.line_to(w, y);
+        }
+        cr.stroke();
+        cr.restore();
+    }
+
+    // Thin thumb at the right edge, showing where the current line is in
+    // the buffer.
+    if let Some((line, total)) = ctx.scroll_indicator {
+        let cm = &ctx.cell_metrics;
+        let rows = ctx.rows.len() as f64;
+        let cols = ctx.rows.get(0).map(|r| r.len()).unwrap_or(0) as f64;
+        let (w, h) = (cols * cm.width, rows * cm.height);
+        // Lines are 1-based, the first line puts the thumb at the top and
+        // the last one at the bottom.
+        let line = line.max(1).min(total) - 1;
+        let pos = line as f64 / (total - 1).max(1) as f64;
+        let thumb = (h * rows / total as f64).max(6.0).min(h);
+        let y = ((h - thumb) * pos).floor();
+        let color = &ctx.fg_color;
+
+        cr.save();
+        cr.set_source_rgba(color.r, color.g, color.b, 0.4);
+        cr.rectangle(w - 4.0, y, 3.0, thumb);
+        cr.fill();
+        cr.restore();
+    }
+
+    if let (Some((above, below, amount)), Some((row, _))) =
+        (ctx.dim_lines, ctx.cursor.get_position())
+    {
+        let cm = &ctx.cell_metrics;
+        let rows = ctx.rows.len() as f64;
+        let cols = ctx.rows.get(0).map(|r| r.len()).unwrap_or(0) as f64;
+        let first = (row - above as f64).max(0.0);
+        let last = (row + below as f64 + 1.0).min(rows);
+
+        cr.save();
+        cr.set_source_rgba(0.0, 0.0, 0.0, amount);
+        for (top, bot) in [(0.0, first), (last, rows)].iter() {
+            let (x1, y1, x2, y2) =
+                render::get_rect(cm.height, cm.width, *top, *bot, 0.0, cols);
+            cr.rectangle(x1, y1, x2 - x1, y2 - y1);
+        }
+        cr.fill();
+        cr.restore();
+    }
+
+    if !ctx.active && ctx.inactive_dim > 0.0 {
+        cr.save();
+        cr.set_source_rgba(0.0, 0.0, 0.0, ctx.inactive_dim);
+        cr.paint();
+        cr.restore();
+    }
+
+    for flash in ctx.flashes.iter() {
+        cr.save();
+        cr.set_source_rgba(
+            flash.color.r,
+            flash.color.g,
+            flash.color.b,
+            flash.alpha,
+        );
+        for (x, y, w, h) in flash.rects.iter() {
+            cr.rectangle(*x, *y, *w, *h);
+        }
+        cr.fill();
+        cr.restore();
+    }
+
+    // Extra cursors are drawn as semi transparent, non blinking blocks.
+    if !ctx.extra_cursors.is_empty() {
+        let cm = &ctx.cell_metrics;
+        let color = &ctx.cursor.color;
+
+        cr.save();
+        cr.set_source_rgba(color.r, color.g, color.b, 0.5);
+        for (row, col) in ctx.extra_cursors.iter() {
+            let (x, y) = render::get_coords(
+                cm.height,
+                cm.width,
+                *row as f64,
+                *col as f64,
+            );
+            cr.rectangle(
+                x.floor(),
+                y.floor(),
+                (cm.width * ctx.cursor.cell_percentage).ceil(),
+                cm.height.ceil(),
+            );
+        }
+        cr.fill();
+        cr.restore();
+    }
+
+    if let Some(ref selection) = ctx.selection {
+        let cm = &ctx.cell_metrics;
+        let color = &ctx.cursor.color;
+        let cols = ctx.rows.get(0).map(|r| r.len()).unwrap_or(0) as u64;
+
+        cr.save();
+        cr.set_source_rgba(color.r, color.g, color.b, 0.3);
+        for (row, start, end) in selection.ranges(cols) {
+            let (x1, y1, x2, y2) = render::get_rect(
+                cm.height,
+                cm.width,
+                row as f64,
+                (row + 1) as f64,
+                start as f64,
+                end as f64,
+            );
+            cr.rectangle(x1, y1, x2 - x1, y2 - y1);
+        }
+        cr.fill();
+        cr.restore();
+    }
+
+    // If we're not "busy" and the cursor isn't hidden, draw the cursor.
+    if !ctx.busy && ctx.active && ctx.cursor_visible {
         let (x, y, w, h) = ctx.get_cursor_rect();
 
+        if !ctx.focused && ctx.unfocused_cursor == UnfocusedCursor::Hollow {
+            // Only draw the outline of the cell.
+            let color = &ctx.cursor.color;
+            cr.save();
+            cr.set_line_width(1.0);
+            cr.rectangle(
+                f64::from(x) + 0.5,
+                f64::from(y) + 0.5,
+                f64::from(w) - 1.0,
+                f64::from(h) - 1.0,
+            );
+            cr.set_source_rgb(color.r, color.g, color.b);
+            cr.stroke();
+            cr.restore();
+        } else {
+            cr.save();
+            cr.rectangle(
+                f64::from(x),
+                f64::from(y),
+                f64::from(w) * ctx.cursor.cell_percentage,
+                f64::from(h),
+            );
+            let surface = ctx.cursor_context.get_target();
+            surface.flush();
+            cr.set_source_surface(&surface, x.into(), y.into());
+            if ctx.cursor_over_text && ctx.cursor.blink_on != 0 {
+                cr.clip();
+                cr.paint_with_alpha(ctx.cursor_alpha());
+            } else {
+                cr.fill();
+            }
+            cr.restore();
+
+            // The outline is drawn inside of the cursor's rect, so it gets
+            // cleared along with the cursor.
+            if let Some((color, width)) = ctx.cursor_outline {
+                let alpha = if ctx.cursor.blink_on == 0 {
+                    1.0
+                } else {
+                    ctx.cursor_alpha()
+                };
+                let w = f64::from(w) * ctx.cursor.cell_percentage;
+                let h = f64::from(h);
+                let width = width.min(w / 2.0).min(h / 2.0);
+
+                cr.save();
+                cr.set_line_width(width);
+                cr.rectangle(
+                    f64::from(x) + width / 2.0,
+                    f64::from(y) + width / 2.0,
+                    w - width,
+                    h - width,
+                );
+                cr.set_source_rgba(color.r, color.g, color.b, alpha);
+                cr.stroke();
+                cr.restore();
+            }
+        }
+    }
+
+    // While busy, a spinner is drawn in place of the cursor.
+    if let Some(angle) = ctx.spinner_angle.filter(|_| ctx.active) {
+        let (x, y, w, h) = ctx.get_cursor_rect();
+        let (w, h) = (f64::from(w), f64::from(h));
+        let color = &ctx.fg_color;
+        let radius = (w.min(h) / 2.0 - 1.5).max(1.0);
+
+        cr.save();
+        cr.set_line_width(1.5);
+        cr.set_source_rgb(color.r, color.g, color.b);
+        cr.arc(
+            f64::from(x) + w / 2.0,
+            f64::from(y) + h / 2.0,
+            radius,
+            angle,
+            angle + 1.5 * std::f64::consts::PI,
+        );
+        cr.stroke();
+        cr.restore();
+    }
+
+    if let Some((color, width)) = ctx.active_border.filter(|_| ctx.active) {
+        let cm = &ctx.cell_metrics;
+        let cols = ctx.rows.get(0).map(|r| r.len()).unwrap_or(0) as f64;
+        let rows = ctx.rows.len() as f64;
+
+        // Keep the whole border inside of the grid.
         cr.save();
+        cr.set_line_width(width);
+        cr.set_source_rgb(color.r, color.g, color.b);
         cr.rectangle(
-            f64::from(x),
-            f64::from(y),
-            f64::from(w) * ctx.cursor.cell_percentage,
-            f64::from(h),
+            width / 2.0,
+            width / 2.0,
+            cols * cm.width - width,
+            rows * cm.height - width,
         );
-        let surface = ctx.cursor_context.get_target();
-        surface.flush();
-        cr.set_source_surface(&surface, x.into(), y.into());
-        cr.fill();
+        cr.stroke();
         cr.restore();
     }
+
+    // Invert everything we've drawn so far, including the cursor.
+    if ctx.invert_colors {
+        cr.save();
+        cr.set_operator(cairo::Operator::Difference);
+        cr.set_source_rgb(1.0, 1.0, 1.0);
+        cr.paint();
+        cr.restore();
+    }
+
+    cr.restore();
 }
@@ -1,8 +1,13 @@
+mod box_drawing;
 mod context;
 mod cursor;
 #[allow(clippy::module_inception)]
 mod grid;
 mod render;
 mod row;
+mod scroll_cache;
+mod selection;
+mod surface;
 
-pub use self::grid::{Grid, GridMetrics};
+pub use self::context::DEFAULT_UNDERLINE_THICKNESS_SCALE;
+pub use self::grid::{Grid, GridMetrics, MouseButton};
@@ -5,8 +5,11 @@ use pango::Attribute;
 use crate::nvim_bridge::GridLineSegment;
 use crate::ui::color::Highlight;
 use crate::ui::color::HlDefs;
+use crate::ui::color::RenderWhitespace;
+use crate::ui::color::VirtualTextStyle;
+use crate::ui::grid::box_drawing;
 use crate::ui::grid::context::{CellMetrics, Context};
-use crate::ui::grid::row::{Cell, Segment};
+use crate::ui::grid::row::{Cell, Row, Segment};
 
 /// Renders text to `cr`.
 ///
@@ -16,6 +19,8 @@ use crate::ui::grid::row::{Cell, Segment};
 /// * `hl` - The highlighting to use.
 /// * `hl_defs` - Global hl defs. Used to get default values.
 /// * `text` - The text to render.
+/// * `offsets` - Byte offsets in `text` where each cell starts. A cell can
+///               contain more than one character (e.g. combining marks).
 /// * `x` - Target x coordinate for `cr`.
 /// * `y` - Target y coordinate for `cr`.
 /// * `w` - Target width for `cr`.
@@ -28,6 +33,7 @@ fn render_text(
     hl: &Highlight,
     hl_defs: &HlDefs,
     text: &str,
+    offsets: &[usize],
     x: f64,
     y: f64,
     w: f64,
@@ -45,46 +51,128 @@ fn render_text(
         )
     };
 
+    let vt_style = if hl.virtual_text && !hl.nocombine {
+        hl_defs.virtual_text_style
+    } else {
+        VirtualTextStyle::None
+    };
+
+    let fg = if vt_style == VirtualTextStyle::Dim {
+        fg.mix(&bg, 0.5)
+    } else {
+        fg
+    };
+
+    let fg = if hl_defs.min_contrast > 0.0 {
+        fg.with_min_contrast(&bg, hl_defs.min_contrast)
+    } else {
+        fg
+    };
+
     cr.save();
     cr.set_source_rgb(bg.r, bg.g, bg.b);
     cr.rectangle(x, y, w, h);
     cr.fill();
     cr.restore();
 
+    if hl_defs.box_drawing {
+        cr.save();
+        cr.set_source_rgb(fg.r, fg.g, fg.b);
+        let drawn = box_drawing::draw(cr, text, x, y, w, h);
+        cr.restore();
+
+        if drawn {
+            return;
+        }
+    }
+
     let attrs = pango::AttrList::new();
 
     if hl.bold {
         let attr = Attribute::new_weight(pango::Weight::Bold).unwrap();
         attrs.insert(attr);
     }
-    if hl.italic {
+    if hl.italic || vt_style == VirtualTextStyle::Italic {
         let attr = Attribute::new_style(pango::Style::Italic).unwrap();
         attrs.insert(attr);
     }
 
-    cr.save();
-    cr.set_source_rgb(fg.r, fg.g, fg.b);
-
     let items =
         pango::itemize(pango_context, text, 0, text.len() as i32, &attrs, None);
 
-    let mut x_offset = 0.0;
+    // Column (relative to `x`) of the cell that the text at byte `offset`
+    // belongs to.
+    let col_at = |offset: usize| {
+        offsets.iter().rposition(|o| *o <= offset).unwrap_or(0) as f64
+    };
+
+    // Shape the text first, so the same glyphs can be drawn for the shadow
+    // and the text itself. Each run is (x, font, glyphs).
+    let mut runs = vec![];
     for item in items {
         let a = item.analysis();
         let item_offset = item.offset() as usize;
-        let mut glyphs = pango::GlyphString::new();
+        let item_end = item_offset + item.length() as usize;
+        let item_text = &text[item_offset..item_end];
+        let x_offset = col_at(item_offset) * cm.width;
+
+        if cm.extra_width == 0.0 {
+            let mut glyphs = pango::GlyphString::new();
+            pango::shape(item_text, &a, &mut glyphs);
+            runs.push((x + x_offset, a.font(), glyphs));
+        } else {
+            // With letter spacing (or scaled cell width), the text of each
+            // cell is placed in the middle of its cell.
+            let cells = offsets
+                .iter()
+                .enumerate()
+                .filter(|(_, o)| item_offset <= **o && **o < item_end);
+            for (i, start) in cells {
+                let end = offsets
+                    .get(i + 1)
+                    .map(|o| (*o).min(item_end))
+                    .unwrap_or(item_end);
+                if *start == end {
+                    continue;
+                }
+
+                let mut glyphs = pango::GlyphString::new();
+                pango::shape(&text[*start..end], &a, &mut glyphs);
+
+                let ch_x = i as f64 * cm.width;
+                runs.push((x + ch_x + cm.extra_width / 2.0, a.font(), glyphs));
+            }
+        }
+    }
 
-        pango::shape(
-            &text[item_offset..item_offset + item.length() as usize],
-            &a,
-            &mut glyphs,
-        );
+    if let Some(ref shadow) = hl_defs.text_shadow {
+        // Keep the shadow inside the segment, so it doesn't linger on the
+        // neighbouring cells when they are redrawn.
+        cr.save();
+        cr.rectangle(x, y, w, h);
+        cr.clip();
+
+        let c = shadow.color;
+        for (dx, dy, alpha) in shadow.samples() {
+            cr.set_source_rgba(c.r, c.g, c.b, alpha);
+            for (run_x, font, glyphs) in runs.iter_mut() {
+                cr.move_to(
+                    snap(cr, hl_defs, *run_x + dx),
+                    snap(cr, hl_defs, y + cm.ascent + dy),
+                );
+                pangocairo::functions::show_glyph_string(&cr, &*font, glyphs);
+            }
+        }
+
+        cr.restore();
+    }
 
-        cr.move_to(x + x_offset, y + cm.ascent);
-        pangocairo::functions::show_glyph_string(&cr, &a.font(), &mut glyphs);
+    cr.save();
+    cr.set_source_rgb(fg.r, fg.g, fg.b);
 
-        x_offset += f64::from(item.num_chars()) * cm.width;
-        //x_offset += f64::from(glyphs.get_width());
+    for (run_x, font, glyphs) in runs.iter_mut() {
+        cr.move_to(snap(cr, hl_defs, *run_x), snap(cr, hl_defs, y + cm.ascent));
+        pangocairo::functions::show_glyph_string(&cr, &*font, glyphs);
     }
 
     // Since we can't (for some reason) use pango attributes to draw
@@ -105,10 +193,30 @@ fn render_text(
         cr.rectangle(x, y, w, cm.underline_thickness);
         cr.fill();
     }
+    if hl.strikethrough {
+        // Unlike the underline, the strikethrough is in the text's color,
+        // unless the highlight has its own special color.
+        let color = hl.special.unwrap_or(fg);
+        cr.set_source_rgb(color.r, color.g, color.b);
+        let y = y + cm.ascent - cm.strikethrough_position;
+        cr.rectangle(x, y, w, cm.strikethrough_thickness);
+        cr.fill();
+    }
 
     cr.restore();
 }
 
+/// Rounds `v` to whole device pixels of `cr`'s target, if pixel snapping
+/// is enabled.
+fn snap(cr: &cairo::Context, hl_defs: &HlDefs, v: f64) -> f64 {
+    if !hl_defs.pixel_snap {
+        return v;
+    }
+
+    let (scale, _) = cr.get_target().get_device_scale();
+    (v * scale).round() / scale
+}
+
 /// Draws (inverted) cell to `cr`.
 pub fn cursor_cell(
     cr: &cairo::Context,
@@ -130,16 +238,19 @@ pub fn cursor_cell(
     };
     let h = cm.height;
 
-    render_text(cr, pango_context, cm, &hl, hl_defs, &cell.text, x, y, w, h);
+    let text = &cell.text;
+    render_text(cr, pango_context, cm, &hl, hl_defs, text, &[0], x, y, w, h);
 }
 
 /// Renders `segments` to `cr`.
+#[allow(clippy::too_many_arguments)]
 fn put_segments(
     cr: &cairo::Context,
     pango_context: &pango::Context,
     queue_draw_area: &mut Vec<(f64, f64, f64, f64)>,
     cm: &CellMetrics,
     hl_defs: &HlDefs,
+    r: &Row,
     segments: Vec<Segment>,
     row: usize,
 ) {
@@ -155,32 +266,204 @@ fn put_segments(
         let h = ch.ceil();
 
         let text = &seg.text;
-        render_text(cr, pango_context, cm, &hl, hl_defs, &text, x, y, w, h);
+        let offsets = r.cell_offsets(seg.start, seg.len);
+        render_text(
+            cr,
+            pango_context,
+            cm,
+            &hl,
+            hl_defs,
+            text,
+            &offsets,
+            x,
+            y,
+            w,
+            h,
+        );
 
         queue_draw_area.push((x, y, w, h));
     }
 }
 
+/// Returns the columns in `segments` where whitespace should be rendered,
+/// with the glyph to draw there (`None` for a dot).
+fn whitespace_cols(
+    hl_defs: &HlDefs,
+    row: &Row,
+    segments: &[Segment],
+) -> Vec<(usize, Option<char>)> {
+    let glyphs = hl_defs.whitespace_glyphs;
+    match hl_defs.render_whitespace {
+        RenderWhitespace::None => vec![],
+        RenderWhitespace::All => {
+            let mut cols = vec![];
+            for seg in segments {
+                let (start, end) = (seg.start, seg.start + seg.len);
+                for col in row.inner_spaces(start, end) {
+                    cols.push((col, glyphs.map(|g| g.space)));
+                }
+                // Non-breaking spaces are only rendered with a glyph.
+                if let Some(glyphs) = glyphs {
+                    for col in row.nbsps(start, end) {
+                        cols.push((col, Some(glyphs.nbsp)));
+                    }
+                }
+            }
+            cols
+        }
+    }
+}
+
+/// Draws a faint dot, or the given glyph, in the middle of the cells at
+/// `cols`.
+fn put_whitespace(
+    cr: &cairo::Context,
+    cm: &CellMetrics,
+    hl_defs: &HlDefs,
+    row: usize,
+    cols: &[(usize, Option<char>)],
+) {
+    if cols.is_empty() {
+        return;
+    }
+
+    let color = hl_defs.default_fg.mix(&hl_defs.default_bg, 0.6);
+    let size = (cm.width / 6.0).max(1.0).round();
+
+    cr.save();
+    cr.set_source_rgb(color.r, color.g, color.b);
+    for (col, _) in cols.iter().filter(|(_, glyph)| glyph.is_none()) {
+        let (x, y) = get_coords(cm.height, cm.width, row as f64, *col as f64);
+        cr.rectangle(
+            (x + (cm.width - size) / 2.0).round(),
+            (y + (cm.height - size) / 2.0).round(),
+            size,
+            size,
+        );
+    }
+    cr.fill();
+
+    if let Some(layout) = pangocairo::functions::create_layout(cr) {
+        layout.set_font_description(Some(&cm.font.as_pango_font()));
+        for (col, glyph) in cols.iter() {
+            let glyph = match glyph {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+            layout.set_text(&glyph.to_string());
+
+            let (w, h) = layout.get_pixel_size();
+            let (x, y) =
+                get_coords(cm.height, cm.width, row as f64, *col as f64);
+            cr.move_to(
+                (x + (cm.width - f64::from(w)) / 2.0).round(),
+                (y + (cm.height - f64::from(h)) / 2.0).round(),
+            );
+            pangocairo::functions::show_layout(cr, &layout);
+        }
+    }
+    cr.restore();
+}
+
+/// Box drawing characters are drawn separately, so they need to be in their
+/// own segments.
+fn is_box_drawing(cell: &Cell) -> bool {
+    box_drawing::is_supported(&cell.text)
+}
+
+/// Returns the column of the cursor, if the cursor is on `row`. Cells at this
+/// column are rendered as a separate segment so that ligatures don't hide
+/// the character under the cursor.
+fn cursor_split(context: &Context, row: usize) -> Option<usize> {
+    context
+        .cursor
+        .get_position()
+        .filter(|pos| pos.0 as usize == row)
+        .map(|pos| pos.1 as usize)
+}
+
 pub fn redraw(
     context: &mut Context,
     pango_context: &pango::Context,
     hl_defs: &HlDefs,
 ) {
-    for (i, row) in context.rows.iter_mut().enumerate() {
-        let segments = row.as_segments(0, row.len);
+    for i in 0..context.rows.len() {
+        redraw_row(context, pango_context, hl_defs, i);
+    }
+}
 
-        put_segments(
-            &context.cairo_context,
-            pango_context,
-            &mut context.queue_draw_area,
-            &context.cell_metrics,
-            hl_defs,
-            segments,
-            i,
-        );
+/// Renders the whole `row` again.
+pub fn redraw_row(
+    context: &mut Context,
+    pango_context: &pango::Context,
+    hl_defs: &HlDefs,
+    row: usize,
+) {
+    let split_at = cursor_split(context, row);
+
+    if let Some(r) = context.rows.get(row) {
+        let segments = r.as_segments_split(0, r.len, split_at);
+        redraw_segments(context, pango_context, hl_defs, row, segments);
+    }
+}
+
+/// Renders again the run of cells with the same highlight around `col` on
+/// `row`, so that a ligature in it is broken up at the cursor (or restored).
+/// Runs of a single cell can't have ligatures, so those are skipped.
+pub fn redraw_cursor_run(
+    context: &mut Context,
+    pango_context: &pango::Context,
+    hl_defs: &HlDefs,
+    row: usize,
+    col: usize,
+) {
+    let split_at = cursor_split(context, row);
+
+    if let Some(r) = context.rows.get(row) {
+        let (start, end) = match r.run_at(col) {
+            Some((start, end)) if start < end => (start, end),
+            _ => return,
+        };
+
+        let segments = r.as_segments_split(start, end, split_at);
+        redraw_segments(context, pango_context, hl_defs, row, segments);
     }
 }
 
+/// Renders `segments` of `row`, with the whitespace on top of them.
+fn redraw_segments(
+    context: &mut Context,
+    pango_context: &pango::Context,
+    hl_defs: &HlDefs,
+    row: usize,
+    mut segments: Vec<Segment>,
+) {
+    let r = &context.rows[row];
+    if hl_defs.box_drawing {
+        segments = r.isolate_cells(segments, is_box_drawing);
+    }
+
+    let whitespace = whitespace_cols(hl_defs, r, &segments);
+
+    put_segments(
+        &context.cairo_context,
+        pango_context,
+        &mut context.queue_draw_area,
+        &context.cell_metrics,
+        hl_defs,
+        r,
+        segments,
+        row,
+    );
+    put_whitespace(
+        &context.cairo_context,
+        &context.cell_metrics,
+        hl_defs,
+        row,
+        &whitespace,
+    );
+}
+
 /// Renders `line` to `context.cairo_context`.
 pub fn put_line(
     context: &mut Context,
@@ -189,17 +472,42 @@ pub fn put_line(
     hl_defs: &HlDefs,
 ) {
     let row = line.row as usize;
-    let mut affected_segments = context
+    let split_at = cursor_split(context, row);
+    let r = context
         .rows
         .get_mut(row)
-        .unwrap_or_else(|| panic!("Failed to get row {}", line.row))
-        .update(line);
+        .unwrap_or_else(|| panic!("Failed to get row {}", line.row));
+    let mut affected_segments = r.update(line);
+
+    // The change might make spaces earlier on the row visible (or hide them),
+    // so draw the whole row.
+    if hl_defs.render_whitespace != RenderWhitespace::None {
+        affected_segments = r.as_segments(0, r.len);
+    }
+
+    // If the cursor is within the affected area, break up the segment
+    // under it.
+    if let Some(col) = split_at {
+        let start = affected_segments.first().map(|seg| seg.start);
+        let end = affected_segments.last().map(|seg| seg.start + seg.len - 1);
+        if let (Some(start), Some(end)) = (start, end) {
+            if start <= col && col <= end {
+                affected_segments = r.as_segments_split(start, end, split_at);
+            }
+        }
+    }
+
+    if hl_defs.box_drawing {
+        affected_segments = r.isolate_cells(affected_segments, is_box_drawing);
+    }
 
     // NOTE(ville): I haven't noticed any cases where a character is overflowing
     //              to the left. Probably doesn't apply to languages that goes
     //              from right to left, instead of left to right.
     // Rendering the segments in reversed order fixes issues when some character
     // is overflowing to the right.
+    let whitespace = whitespace_cols(hl_defs, r, &affected_segments);
+
     affected_segments.reverse();
     put_segments(
         &context.cairo_context,
@@ -207,9 +515,17 @@ pub fn put_line(
         &mut context.queue_draw_area,
         &context.cell_metrics,
         hl_defs,
+        r,
         affected_segments,
         row,
     );
+    put_whitespace(
+        &context.cairo_context,
+        &context.cell_metrics,
+        hl_defs,
+        row,
+        &whitespace,
+    );
 }
 
 /// Clears whole `da` with `hl_defs.default_bg`.
@@ -230,7 +546,14 @@ pub fn clear(da: &DrawingArea, ctx: &mut Context, hl_defs: &HlDefs) {
 }
 
 /// Scrolls contents in `ctx.cairo_context` and `ctx.rows`, based on `reg`.
-pub fn scroll(ctx: &mut Context, hl_defs: &HlDefs, reg: [u64; 4], count: i64) {
+/// Returns the rows that were restored from the scroll cache, and need to
+/// be redrawn.
+pub fn scroll(
+    ctx: &mut Context,
+    hl_defs: &HlDefs,
+    reg: [u64; 4],
+    count: i64,
+) -> Vec<usize> {
     let cr = &ctx.cairo_context;
     let cm = &ctx.cell_metrics;
     let bg = &hl_defs.default_bg;
@@ -252,6 +575,10 @@ pub fn scroll(ctx: &mut Context, hl_defs: &HlDefs, reg: [u64; 4], count: i64) {
         (src_top, src_bot, dst_top, dst_bot, src_top, dst_top)
     };
 
+    if let Some(ref mut cache) = ctx.scroll_cache {
+        cache.store(&ctx.rows, reg, count);
+    }
+
     // Modify the rows stored data of the rows.
     let mut src = vec![];
     for i in src_top as usize..src_bot as usize {
@@ -275,6 +602,14 @@ pub fn scroll(ctx: &mut Context, hl_defs: &HlDefs, reg: [u64; 4], count: i64) {
             .clear_range(left as usize, right as usize);
     }
 
+    let mut restored = vec![];
+    if let Some(ref mut cache) = ctx.scroll_cache {
+        for (i, cells) in cache.take(reg, count) {
+            ctx.rows.get_mut(i).unwrap().insert_at(left as usize, cells);
+            restored.push(i);
+        }
+    }
+
     // Draw move the scrolled part on the cairo surface.
     cr.save();
 
@@ -322,6 +657,8 @@ pub fn scroll(ctx: &mut Context, hl_defs: &HlDefs, reg: [u64; 4], count: i64) {
     ctx.queue_draw_area.push((x1, y1, w, h));
 
     cr.restore();
+
+    restored
 }
 
 pub fn get_rect(
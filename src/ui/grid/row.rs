@@ -63,6 +63,14 @@ impl Row {
         self.len
     }
 
+    /// Returns the row's text and the highlight id of each cell.
+    pub fn text_with_hl(&self) -> (String, Vec<u64>) {
+        let text = self.cells.iter().map(|c| c.text.as_str()).collect();
+        let hl_ids = self.cells.iter().map(|c| c.hl_id).collect();
+
+        (text, hl_ids)
+    }
+
     /// Clears (resets) the row.
     pub fn clear(&mut self) {
         self.cells = Row::create_empty_cells(self.len).into_boxed_slice();
@@ -78,6 +86,18 @@ impl Row {
 
         self.cells = n.into_boxed_slice();
         self.len = self.cells.len();
+
+        self.clear_double_width_at_end();
+    }
+
+    /// Replaces a double width character in the last cell with a space. Such
+    /// a character has lost its second half (e.g. when the row is truncated)
+    /// and would be drawn past the end of the row.
+    fn clear_double_width_at_end(&mut self) {
+        if let Some(cell) = self.cells.last_mut().filter(|c| c.double_width) {
+            cell.text = String::from(" ");
+            cell.double_width = false;
+        }
     }
 
     /// Clears range from `from` to `to`.
@@ -124,24 +144,41 @@ impl Row {
 
         let mut offset = col_start;
         for cell in line.cells.iter() {
-            for r in 0..cell.repeat as usize {
-                self.cells[offset + r] = Cell {
-                    // TODO(ville): Avoid clone here?
-                    text: cell.text.clone(),
-                    hl_id: cell.hl_id,
-                    double_width: cell.double_width,
-                };
+            let end = offset + cell.repeat as usize;
+
+            // Fill the whole run at once. `clone_from` reuses the existing
+            // allocations of the cells' text.
+            for c in self.cells[offset..end].iter_mut() {
+                c.text.clone_from(&cell.text);
+                c.hl_id = cell.hl_id;
+                c.double_width = cell.double_width;
             }
 
-            offset += cell.repeat as usize;
+            offset = end;
         }
 
         assert_eq!(self.cells.len(), self.len);
 
+        if offset == self.len {
+            self.clear_double_width_at_end();
+        }
+
         self.as_segments(range_start, offset)
     }
 
     pub fn as_segments(&self, cell_start: usize, end: usize) -> Vec<Segment> {
+        self.as_segments_split(cell_start, end, None)
+    }
+
+    /// Same as `as_segments`, but the cell at `split_at` (if any) is always
+    /// placed in its own segment. This is used to break up ligatures under
+    /// the cursor.
+    pub fn as_segments_split(
+        &self,
+        cell_start: usize,
+        end: usize,
+        split_at: Option<usize>,
+    ) -> Vec<Segment> {
         let base_hl = self.cells[cell_start].hl_id;
         let base = if let Some((i, _)) = self
             .cells
@@ -168,7 +205,10 @@ impl Row {
             }
 
             if let Some(ref mut seg) = segs.last_mut() {
-                if seg.hl_id == cell.hl_id {
+                if seg.hl_id == cell.hl_id
+                    && split_at != Some(i)
+                    && split_at != Some(seg.start)
+                {
                     seg.text.push_str(&cell.text);
                     seg.len += 1;
 
@@ -189,6 +229,108 @@ impl Row {
 
         segs
     }
+
+    /// Returns the first and last column of the run of cells around `col`
+    /// that have the same highlight as `col`.
+    pub fn run_at(&self, col: usize) -> Option<(usize, usize)> {
+        let hl_id = self.cells.get(col)?.hl_id;
+
+        let start = self.cells[..col]
+            .iter()
+            .rposition(|c| c.hl_id != hl_id)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let end = self.cells[col..]
+            .iter()
+            .position(|c| c.hl_id != hl_id)
+            .map(|i| col + i - 1)
+            .unwrap_or(self.len - 1);
+
+        Some((start, end))
+    }
+
+    /// Returns the byte offsets where each of the `len` cells starting from
+    /// `start` begin in the text of a segment made of them. A cell's text
+    /// can be empty (the second half of a double width character) or contain
+    /// more than one character (e.g. combining marks).
+    pub fn cell_offsets(&self, start: usize, len: usize) -> Vec<usize> {
+        let mut offset = 0;
+        self.cells[start..start + len]
+            .iter()
+            .map(|cell| {
+                let o = offset;
+                offset += cell.text.len();
+                o
+            })
+            .collect()
+    }
+
+    /// Returns the columns between `start` and `end` (exclusive) that have a
+    /// space, which is followed by some other text later on the row. Spaces
+    /// at the end of the row are not included, because those can't be told
+    /// apart from empty cells.
+    pub fn inner_spaces(&self, start: usize, end: usize) -> Vec<usize> {
+        let last = match self.cells.iter().rposition(|c| c.text != " ") {
+            Some(last) => last,
+            None => return vec![],
+        };
+
+        (start..end.min(last))
+            .filter(|i| self.cells[*i].text == " ")
+            .collect()
+    }
+
+    /// Returns the columns between `start` and `end` that contain a
+    /// non-breaking space.
+    pub fn nbsps(&self, start: usize, end: usize) -> Vec<usize> {
+        (start..end.min(self.len))
+            .filter(|i| self.cells[*i].text == "\u{a0}")
+            .collect()
+    }
+
+    /// Splits `segments` so that each cell for which `isolate` returns true
+    /// is placed in its own segment.
+    pub fn isolate_cells<F>(
+        &self,
+        segments: Vec<Segment>,
+        isolate: F,
+    ) -> Vec<Segment>
+    where
+        F: Fn(&Cell) -> bool,
+    {
+        let mut segs: Vec<Segment> = vec![];
+
+        for seg in segments {
+            let cells = &self.cells[seg.start..seg.start + seg.len];
+            if !cells.iter().any(&isolate) {
+                segs.push(seg);
+                continue;
+            }
+
+            let mut prev_isolated = true;
+            for (i, cell) in cells.iter().enumerate() {
+                let isolated = isolate(cell);
+
+                if !isolated && !prev_isolated {
+                    if let Some(ref mut last) = segs.last_mut() {
+                        last.text.push_str(&cell.text);
+                        last.len += 1;
+                        continue;
+                    }
+                }
+
+                segs.push(Segment {
+                    text: cell.text.clone(),
+                    hl_id: seg.hl_id,
+                    start: seg.start + i,
+                    len: 1,
+                });
+                prev_isolated = isolated;
+            }
+        }
+
+        segs
+    }
 }
 
 #[cfg(all(feature = "unstable", test))]
@@ -362,6 +504,25 @@ mod benches {
         });
     }
 
+    #[bench]
+    fn bench_row_update_repeat(b: &mut Bencher) {
+        let mut row = Row::new(500);
+
+        b.iter(move || {
+            row.update(GridLineSegment {
+                grid: 0,
+                row: 0,
+                col_start: 0,
+                cells: vec![nvim_bridge::Cell {
+                    text: String::from("-"),
+                    hl_id: 1,
+                    repeat: 500,
+                    double_width: false,
+                }],
+            });
+        });
+    }
+
     #[bench]
     fn bench_row_clear_range(b: &mut Bencher) {
         let mut row = Row::new(10);
@@ -622,6 +783,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_row_update_repeat() {
+        let mut row = Row::new(500);
+
+        let segments = row.update(GridLineSegment {
+            grid: 0,
+            row: 0,
+            col_start: 2,
+            cells: vec![
+                nvim_bridge::Cell {
+                    text: String::from("-"),
+                    hl_id: 1,
+                    repeat: 497,
+                    double_width: false,
+                },
+                nvim_bridge::Cell {
+                    text: String::from("x"),
+                    hl_id: 2,
+                    repeat: 1,
+                    double_width: false,
+                },
+            ],
+        });
+
+        // The segment before col_start is included, since it has the same
+        // highlight as the col_start had before the update.
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].text, "  ");
+        assert_eq!(segments[0].start, 0);
+        assert_eq!(segments[1].text, "-".repeat(497));
+        assert_eq!(segments[1].start, 2);
+        assert_eq!(segments[1].len, 497);
+        assert_eq!(segments[2].text, "x");
+        assert_eq!(segments[2].start, 499);
+
+        assert_eq!(row.cell_at(0).unwrap().text, " ");
+        assert_eq!(row.cell_at(1).unwrap().text, " ");
+        assert_eq!(row.cell_at(498).unwrap().text, "-");
+        assert_eq!(row.cell_at(498).unwrap().hl_id, 1);
+        assert_eq!(row.cell_at(499).unwrap().text, "x");
+    }
+
     /*
     #[test]
     fn test_rope_cell_at() {
@@ -1004,6 +1207,49 @@ mod tests {
         assert_eq!(second.len, 4);
     }
 
+    #[test]
+    fn test_row_as_segments_split() {
+        let mut row = Row::new(4);
+        row.insert_at(
+            0,
+            vec![
+                Cell {
+                    text: "=".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "=".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: ">".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "a".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+            ],
+        );
+
+        let segments = row.as_segments_split(0, row.len, Some(1));
+
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].text, "=");
+        assert_eq!(segments[0].start, 0);
+        assert_eq!(segments[0].len, 1);
+        assert_eq!(segments[1].text, "=");
+        assert_eq!(segments[1].start, 1);
+        assert_eq!(segments[1].len, 1);
+        assert_eq!(segments[2].text, ">a");
+        assert_eq!(segments[2].start, 2);
+        assert_eq!(segments[2].len, 2);
+    }
+
     #[test]
     fn test_row_grow() {
         let mut row = Row::new(10);
@@ -1027,4 +1273,339 @@ mod tests {
             String::from(" ").repeat(5)
         );
     }
+
+    #[test]
+    fn test_row_truncate_double_width() {
+        let mut row = Row::new(6);
+        row.update(GridLineSegment {
+            grid: 0,
+            row: 0,
+            col_start: 0,
+            cells: vec![
+                nvim_bridge::Cell {
+                    text: String::from("a"),
+                    hl_id: 0,
+                    repeat: 1,
+                    double_width: false,
+                },
+                nvim_bridge::Cell {
+                    text: String::from("\u{4e00}"),
+                    hl_id: 1,
+                    repeat: 1,
+                    double_width: true,
+                },
+                nvim_bridge::Cell {
+                    text: String::from(""),
+                    hl_id: 1,
+                    repeat: 1,
+                    double_width: false,
+                },
+            ],
+        });
+
+        // Cutting the double width character in half leaves a space in its
+        // place, so it isn't drawn past the last column.
+        row.resize(2);
+        assert_eq!(row.len, 2);
+        let cell = row.cell_at(1).unwrap();
+        assert_eq!(cell.text, " ");
+        assert_eq!(cell.hl_id, 1);
+        assert!(!cell.double_width);
+
+        let segments = row.as_segments(0, row.len);
+        assert_eq!(segments.last().unwrap().text, " ");
+
+        // Same if the character is put on the last column.
+        row.update(GridLineSegment {
+            grid: 0,
+            row: 0,
+            col_start: 1,
+            cells: vec![nvim_bridge::Cell {
+                text: String::from("\u{4e00}"),
+                hl_id: 1,
+                repeat: 1,
+                double_width: true,
+            }],
+        });
+        assert_eq!(row.cell_at(1).unwrap().text, " ");
+
+        // Keeping both halves keeps the character.
+        let mut row = Row::new(3);
+        row.update(GridLineSegment {
+            grid: 0,
+            row: 0,
+            col_start: 1,
+            cells: vec![
+                nvim_bridge::Cell {
+                    text: String::from("\u{4e00}"),
+                    hl_id: 1,
+                    repeat: 1,
+                    double_width: true,
+                },
+                nvim_bridge::Cell {
+                    text: String::from(""),
+                    hl_id: 1,
+                    repeat: 1,
+                    double_width: false,
+                },
+            ],
+        });
+        row.resize(3);
+        assert_eq!(row.cell_at(1).unwrap().text, "\u{4e00}");
+        assert!(row.cell_at(1).unwrap().double_width);
+    }
+
+    #[test]
+    fn test_row_combining_chars() {
+        let mut row = Row::new(4);
+
+        // "é" as "e" followed by a combining acute accent.
+        row.update(GridLineSegment {
+            grid: 0,
+            row: 0,
+            col_start: 0,
+            cells: vec![
+                nvim_bridge::Cell {
+                    text: String::from("a"),
+                    hl_id: 0,
+                    repeat: 1,
+                    double_width: false,
+                },
+                nvim_bridge::Cell {
+                    text: String::from("e\u{301}"),
+                    hl_id: 0,
+                    repeat: 1,
+                    double_width: false,
+                },
+                nvim_bridge::Cell {
+                    text: String::from("b"),
+                    hl_id: 0,
+                    repeat: 1,
+                    double_width: false,
+                },
+            ],
+        });
+
+        assert_eq!(row.cell_at(1).unwrap().text, "e\u{301}");
+        assert_eq!(row.cell_at(2).unwrap().text, "b");
+
+        let segments = row.as_segments(0, row.len);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].text, "ae\u{301}b ");
+        assert_eq!(segments[0].len, 4);
+
+        // The combining mark doesn't start a cell of its own.
+        assert_eq!(row.cell_offsets(0, 4), vec![0, 1, 4, 5]);
+    }
+
+    #[test]
+    fn test_row_isolate_cells() {
+        let mut row = Row::new(5);
+        row.insert_at(
+            0,
+            vec![
+                Cell {
+                    text: "a".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "b".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "─".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "─".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "c".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+            ],
+        );
+
+        let segments = row.as_segments(0, row.len);
+        assert_eq!(segments.len(), 1);
+
+        let segments = row.isolate_cells(segments, |c| c.text == "─");
+
+        assert_eq!(segments.len(), 4);
+        assert_eq!(segments[0].text, "ab");
+        assert_eq!(segments[0].start, 0);
+        assert_eq!(segments[0].len, 2);
+        assert_eq!(segments[1].text, "─");
+        assert_eq!(segments[1].start, 2);
+        assert_eq!(segments[1].len, 1);
+        assert_eq!(segments[2].text, "─");
+        assert_eq!(segments[2].start, 3);
+        assert_eq!(segments[2].len, 1);
+        assert_eq!(segments[3].text, "c");
+        assert_eq!(segments[3].start, 4);
+        assert_eq!(segments[3].len, 1);
+    }
+
+    #[test]
+    fn test_row_inner_spaces() {
+        let mut row = Row::new(8);
+        row.insert_at(
+            1,
+            vec![
+                Cell {
+                    text: "a".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: " ".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: " ".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "b".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+            ],
+        );
+
+        assert_eq!(row.inner_spaces(0, row.len), vec![0, 2, 3]);
+        assert_eq!(row.inner_spaces(3, row.len), vec![3]);
+        assert_eq!(row.inner_spaces(5, row.len), Vec::<usize>::new());
+        assert_eq!(Row::new(3).inner_spaces(0, 3), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_row_run_at() {
+        let mut row = Row::new(8);
+        row.insert_at(
+            2,
+            vec![
+                Cell {
+                    text: "-".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: ">".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "x".to_string(),
+                    hl_id: 2,
+                    double_width: false,
+                },
+            ],
+        );
+
+        assert_eq!(row.run_at(0), Some((0, 1)));
+        assert_eq!(row.run_at(3), Some((2, 3)));
+        assert_eq!(row.run_at(4), Some((4, 4)));
+        assert_eq!(row.run_at(7), Some((5, 7)));
+        assert_eq!(row.run_at(8), None);
+    }
+
+    #[test]
+    fn test_row_nbsps() {
+        let mut row = Row::new(6);
+        row.insert_at(
+            1,
+            vec![
+                Cell {
+                    text: "\u{a0}".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "a".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+                Cell {
+                    text: "\u{a0}".to_string(),
+                    hl_id: 1,
+                    double_width: false,
+                },
+            ],
+        );
+
+        assert_eq!(row.nbsps(0, row.len), vec![1, 3]);
+        assert_eq!(row.nbsps(2, row.len), vec![3]);
+        assert_eq!(row.nbsps(4, 10), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_row_update_carries_hl_id() {
+        use rmpv::Value;
+
+        let mut row = Row::new(6);
+
+        // Only the first cell specifies the highlight, the rest of the cells
+        // should reuse it.
+        let line = GridLineSegment::from(Value::Array(vec![
+            1.into(),
+            0.into(),
+            0.into(),
+            Value::Array(vec![
+                Value::Array(vec!["a".into(), 3.into()]),
+                Value::Array(vec!["b".into()]),
+                Value::Array(vec!["c".into()]),
+                Value::Array(vec!["-".into(), 5.into(), 2.into()]),
+                Value::Array(vec!["d".into()]),
+            ]),
+        ]));
+        row.update(line);
+
+        let hl_ids = (0..row.len)
+            .map(|col| row.cell_at(col).unwrap().hl_id)
+            .collect::<Vec<u64>>();
+        assert_eq!(hl_ids, vec![3, 3, 3, 5, 5, 5]);
+    }
+
+    #[test]
+    fn test_row_text_with_hl() {
+        let mut row = Row::new(4);
+        row.update(GridLineSegment {
+            grid: 0,
+            row: 0,
+            col_start: 0,
+            cells: vec![
+                nvim_bridge::Cell {
+                    text: String::from("a"),
+                    hl_id: 1,
+                    repeat: 1,
+                    double_width: false,
+                },
+                nvim_bridge::Cell {
+                    text: String::from("全"),
+                    hl_id: 2,
+                    repeat: 1,
+                    double_width: true,
+                },
+                nvim_bridge::Cell {
+                    text: String::from(""),
+                    hl_id: 2,
+                    repeat: 1,
+                    double_width: false,
+                },
+            ],
+        });
+
+        let (text, hl_ids) = row.text_with_hl();
+        assert_eq!(text, "a全 ");
+        assert_eq!(hl_ids, vec![1, 2, 2, 0]);
+    }
 }
@@ -0,0 +1,174 @@
+use crate::ui::grid::row::{Cell, Row};
+
+/// Max number of rows stored in each direction.
+const MAX_ROWS: usize = 256;
+
+/// Cache for the rows that have been scrolled out of a scroll region. When
+/// the region is scrolled back, the cached rows can be drawn right away,
+/// instead of showing empty rows until nvim sends the content for them.
+#[derive(Default)]
+pub struct ScrollCache {
+    /// The scroll region the cache is for.
+    reg: [u64; 4],
+    /// Rows that have been scrolled out above the region. The last row is
+    /// the one closest to the region.
+    above: Vec<Vec<Cell>>,
+    /// Rows that have been scrolled out below the region. The last row is
+    /// the one closest to the region.
+    below: Vec<Vec<Cell>>,
+}
+
+impl ScrollCache {
+    pub fn clear(&mut self) {
+        self.above.clear();
+        self.below.clear();
+    }
+
+    /// Stores the rows that are about to be scrolled out of `reg`. Call
+    /// before the scroll is applied to `rows`.
+    pub fn store(&mut self, rows: &[Row], reg: [u64; 4], count: i64) {
+        if self.reg != reg {
+            self.clear();
+            self.reg = reg;
+        }
+
+        let (top, bot, left, right) = unpack(reg);
+        let n = (count.abs() as usize).min(bot - top);
+
+        if count > 0 {
+            for row in rows[top..top + n].iter() {
+                self.above.push(row.copy_range(left, right));
+            }
+            truncate(&mut self.above);
+        } else {
+            for row in rows[bot - n..bot].iter().rev() {
+                self.below.push(row.copy_range(left, right));
+            }
+            truncate(&mut self.below);
+        }
+    }
+
+    /// Returns the cached cells for the rows that were cleared by scrolling
+    /// `reg` by `count`, as (row, cells). The cells start at the region's
+    /// left column.
+    pub fn take(
+        &mut self,
+        reg: [u64; 4],
+        count: i64,
+    ) -> Vec<(usize, Vec<Cell>)> {
+        if self.reg != reg {
+            return vec![];
+        }
+
+        let (top, bot, _, _) = unpack(reg);
+        let n = (count.abs() as usize).min(bot - top);
+
+        let mut res = vec![];
+        if count > 0 {
+            for i in bot - n..bot {
+                match self.below.pop() {
+                    Some(cells) => res.push((i, cells)),
+                    None => break,
+                }
+            }
+        } else {
+            for i in (top..top + n).rev() {
+                match self.above.pop() {
+                    Some(cells) => res.push((i, cells)),
+                    None => break,
+                }
+            }
+        }
+
+        res
+    }
+}
+
+fn unpack(reg: [u64; 4]) -> (usize, usize, usize, usize) {
+    (
+        reg[0] as usize,
+        reg[1] as usize,
+        reg[2] as usize,
+        reg[3] as usize,
+    )
+}
+
+/// Drops the rows furthest away from the region, if there are too many.
+fn truncate(rows: &mut Vec<Vec<Cell>>) {
+    if rows.len() > MAX_ROWS {
+        rows.drain(..rows.len() - MAX_ROWS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_with_text(text: &str) -> Row {
+        let mut row = Row::new(2);
+        row.insert_at(
+            0,
+            text.chars()
+                .map(|c| Cell {
+                    text: c.to_string(),
+                    hl_id: 0,
+                    double_width: false,
+                })
+                .collect(),
+        );
+        row
+    }
+
+    fn text(cells: &[Cell]) -> String {
+        cells.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_scroll_cache() {
+        let reg = [0, 4, 0, 2];
+        let rows: Vec<Row> = vec!["aa", "bb", "cc", "dd"]
+            .into_iter()
+            .map(row_with_text)
+            .collect();
+
+        let mut cache = ScrollCache::default();
+
+        // Scroll down by two. Nothing to restore at the bottom.
+        cache.store(&rows, reg, 2);
+        assert!(cache.take(reg, 2).is_empty());
+
+        // Scroll back up, the rows cleared at the top are restored.
+        cache.store(&rows, reg, -2);
+        let restored = cache.take(reg, -2);
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].0, 1);
+        assert_eq!(text(&restored[0].1), "bb");
+        assert_eq!(restored[1].0, 0);
+        assert_eq!(text(&restored[1].1), "aa");
+
+        // Scrolling a different region resets the cache.
+        cache.store(&rows, reg, 2);
+        cache.store(&rows, [0, 3, 0, 2], -1);
+        assert!(cache.take([0, 3, 0, 2], -1).is_empty());
+    }
+
+    #[test]
+    fn test_scroll_cache_restore_below() {
+        let reg = [0, 3, 0, 2];
+        let rows: Vec<Row> = vec!["aa", "bb", "cc"]
+            .into_iter()
+            .map(row_with_text)
+            .collect();
+
+        let mut cache = ScrollCache::default();
+
+        cache.store(&rows, reg, -1);
+        assert!(cache.take(reg, -1).is_empty());
+
+        cache.store(&rows, reg, 1);
+        let restored = cache.take(reg, 1);
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].0, 2);
+        assert_eq!(text(&restored[0].1), "cc");
+    }
+}
@@ -0,0 +1,114 @@
+use crate::ui::grid::row::Row;
+
+/// Text selection made with the mouse on the GUI side, without involving
+/// nvim. Positions are (row, col).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Selection {
+    /// Where the selection was started.
+    pub start: (u64, u64),
+    /// Where the selection currently ends.
+    pub end: (u64, u64),
+}
+
+impl Selection {
+    pub fn new(row: u64, col: u64) -> Self {
+        Selection {
+            start: (row, col),
+            end: (row, col),
+        }
+    }
+
+    /// If the selection covers only the cell it was started from.
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the selected cells as (row, start col, end col) for each
+    /// selected row. The end col is exclusive. The selection wraps around
+    /// lines like in a terminal, so all but the first and last rows are
+    /// selected fully.
+    pub fn ranges(&self, cols: u64) -> Vec<(u64, u64, u64)> {
+        let (first, last) = if self.start <= self.end {
+            (self.start, self.end)
+        } else {
+            (self.end, self.start)
+        };
+
+        (first.0..=last.0)
+            .map(|row| {
+                let start = if row == first.0 { first.1 } else { 0 };
+                let end = if row == last.0 { last.1 + 1 } else { cols };
+                (row, start.min(cols), end.min(cols))
+            })
+            .collect()
+    }
+
+    /// Returns the selected text from `rows`. Trailing whitespace is removed
+    /// from each line.
+    pub fn text(&self, rows: &[Row]) -> String {
+        let cols = rows.get(0).map(|r| r.len()).unwrap_or(0) as u64;
+
+        self.ranges(cols)
+            .into_iter()
+            .filter_map(|(row, start, end)| {
+                let row = rows.get(row as usize)?;
+                let line = (start..end)
+                    .filter_map(|col| row.cell_at(col as usize))
+                    .map(|cell| cell.text.as_str())
+                    .collect::<String>();
+                Some(line.trim_end().to_string())
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::grid::row::Cell;
+
+    fn row_with_text(text: &str) -> Row {
+        let mut row = Row::new(5);
+        row.insert_at(
+            0,
+            text.chars()
+                .map(|c| Cell {
+                    text: c.to_string(),
+                    hl_id: 0,
+                    double_width: false,
+                })
+                .collect(),
+        );
+        row
+    }
+
+    #[test]
+    fn test_selection_ranges() {
+        let mut selection = Selection::new(1, 3);
+        assert_eq!(selection.ranges(5), vec![(1, 3, 4)]);
+
+        selection.end = (3, 1);
+        assert_eq!(selection.ranges(5), vec![(1, 3, 5), (2, 0, 5), (3, 0, 2)]);
+
+        // Selecting backwards.
+        selection.end = (0, 4);
+        assert_eq!(selection.ranges(5), vec![(0, 4, 5), (1, 0, 4)]);
+    }
+
+    #[test]
+    fn test_selection_text() {
+        let rows: Vec<Row> = vec!["foo", "bar b", "baz"]
+            .into_iter()
+            .map(row_with_text)
+            .collect();
+
+        let mut selection = Selection::new(0, 1);
+        selection.end = (2, 1);
+        assert_eq!(selection.text(&rows), "oo\nbar b\nba");
+
+        let mut selection = Selection::new(1, 4);
+        selection.end = (1, 2);
+        assert_eq!(selection.text(&rows), "r b");
+    }
+}
@@ -0,0 +1,56 @@
+use gtk::prelude::*;
+
+/// Creates the surfaces that the grid's context draws to.
+pub trait SurfaceProvider {
+    /// Creates a new surface for `content`, of size `width` x `height`.
+    fn create_surface(
+        &self,
+        content: cairo::Content,
+        width: i32,
+        height: i32,
+    ) -> cairo::Surface;
+}
+
+/// Surfaces that are similar to the window's surface, for drawing to the
+/// screen. The surfaces are in device pixels (with the window's scale
+/// factor), but are drawn to in logical pixels.
+impl SurfaceProvider for gdk::Window {
+    fn create_surface(
+        &self,
+        content: cairo::Content,
+        width: i32,
+        height: i32,
+    ) -> cairo::Surface {
+        let surface =
+            self.create_similar_surface(content, width, height).unwrap();
+
+        let scale = f64::from(self.get_scale_factor());
+        surface.set_device_scale(scale, scale);
+
+        surface
+    }
+}
+
+/// Image surfaces, which are not tied to any window. Used to render without
+/// a display in tests.
+#[cfg(test)]
+pub struct ImageSurfaceProvider;
+
+#[cfg(test)]
+impl SurfaceProvider for ImageSurfaceProvider {
+    fn create_surface(
+        &self,
+        content: cairo::Content,
+        width: i32,
+        height: i32,
+    ) -> cairo::Surface {
+        let format = match content {
+            cairo::Content::Color => cairo::Format::Rgb24,
+            _ => cairo::Format::ARgb32,
+        };
+
+        let surface = cairo::ImageSurface::create(format, width, height)
+            .expect("Failed to create image surface");
+        (*surface).clone()
+    }
+}
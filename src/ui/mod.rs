@@ -47,16 +47,23 @@ macro_rules! upgrade_weak {
     };
 }
 
+mod clipboard;
 mod cmdline;
 pub mod color;
 mod common;
+mod context_menu;
 #[cfg(feature = "libwebkit2gtk")]
 mod cursor_tooltip;
 mod font;
+mod geometry;
 mod grid;
+mod mode_indicator;
+mod pending_keys;
 mod popupmenu;
 mod state;
 mod tabline;
+#[cfg(not(feature = "libwebkit2gtk"))]
+mod text_tooltip;
 #[allow(clippy::module_inception)]
 mod ui;
 mod wildmenu;
@@ -0,0 +1,69 @@
+use crate::nvim_bridge::ModeInfo;
+use crate::ui::color::{Color, HlDefs};
+use crate::ui::common::OverlayPill;
+use crate::ui::font::Font;
+
+/// Small "pill" on top of the grids which displays the name of the current
+/// mode.
+pub struct ModeIndicator {
+    pill: OverlayPill,
+
+    /// If the indicator should be shown at all.
+    enabled: bool,
+
+    /// If we have received a mode to display.
+    has_mode: bool,
+    /// Highlight id of the current mode. Used for the colors.
+    attr_id: u64,
+}
+
+impl ModeIndicator {
+    pub fn new(parent: &gtk::Overlay) -> Self {
+        ModeIndicator {
+            pill: OverlayPill::new(parent, gtk::Align::End),
+            enabled: false,
+            has_mode: false,
+            attr_id: 0,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.pill.set_visible(enabled && self.has_mode);
+    }
+
+    pub fn set_mode(&mut self, mode: &ModeInfo, hl_defs: &HlDefs) {
+        self.has_mode = true;
+        self.attr_id = mode.attr_id;
+        self.pill.set_text(&mode.name);
+        self.set_colors(hl_defs);
+
+        if self.enabled {
+            self.pill.set_visible(true);
+        }
+    }
+
+    pub fn set_font(&mut self, font: Font, hl_defs: &HlDefs) {
+        self.pill.set_font(font);
+        self.set_colors(hl_defs);
+    }
+
+    pub fn set_colors(&mut self, hl_defs: &HlDefs) {
+        let (fg, bg) = self.get_colors(hl_defs);
+        self.pill.set_colors(&fg, &bg, 1.0);
+    }
+
+    /// Returns the (fg, bg) colors for the indicator. If the mode has a
+    /// highlight, use that. Otherwise, use inverted default colors.
+    fn get_colors(&self, hl_defs: &HlDefs) -> (Color, Color) {
+        let hl = hl_defs.get(&self.attr_id).filter(|_| self.attr_id != 0);
+
+        match hl {
+            Some(hl) => (
+                hl.foreground.unwrap_or(hl_defs.default_bg),
+                hl.background.unwrap_or(hl_defs.default_fg),
+            ),
+            None => (hl_defs.default_bg, hl_defs.default_fg),
+        }
+    }
+}
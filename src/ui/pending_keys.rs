@@ -0,0 +1,33 @@
+use crate::ui::color::HlDefs;
+use crate::ui::common::OverlayPill;
+use crate::ui::font::Font;
+
+/// Small "pill" on top of the grids which displays the keys typed so far
+/// when a mapping is pending (e.g. after the leader key).
+pub struct PendingKeys {
+    pill: OverlayPill,
+}
+
+impl PendingKeys {
+    pub fn new(parent: &gtk::Overlay) -> Self {
+        PendingKeys {
+            pill: OverlayPill::new(parent, gtk::Align::Start),
+        }
+    }
+
+    /// Sets the pending keys. Empty `keys` hides the indicator.
+    pub fn set_keys(&self, keys: &str) {
+        self.pill.set_text(keys);
+        self.pill.set_visible(!keys.is_empty());
+    }
+
+    pub fn set_font(&mut self, font: Font, hl_defs: &HlDefs) {
+        self.pill.set_font(font);
+        self.set_colors(hl_defs);
+    }
+
+    pub fn set_colors(&self, hl_defs: &HlDefs) {
+        self.pill
+            .set_colors(&hl_defs.default_bg, &hl_defs.default_fg, 0.85);
+    }
+}
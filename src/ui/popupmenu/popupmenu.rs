@@ -6,7 +6,7 @@ use log::error;
 
 use crate::nvim_bridge::CompletionItem;
 use crate::nvim_gio::GioNeovim;
-use crate::ui::color::{Highlight, HlDefs, HlGroup};
+use crate::ui::color::{Color, Highlight, HlDefs, HlGroup};
 use crate::ui::common::{
     calc_line_space, get_preferred_horizontal_position,
     get_preferred_vertical_position, spawn_local,
@@ -21,6 +21,20 @@ const MAX_HEIGHT: i32 = 500;
 /// Fixed width of completion menu.
 const DEFAULT_WIDTH_NO_DETAILS: i32 = 430;
 const DEFAULT_WIDTH_WITH_DETAILS: i32 = 660;
+/// Duration (in microseconds) of the show/hide animation.
+const ANIMATION_DURATION: i64 = 100_000;
+/// Duration (in microseconds) of one pulse of the selected item.
+const PULSE_PERIOD: i64 = 1_200_000;
+
+/// Fade in or out of the popupmenu.
+struct Fade {
+    /// Frame time when the fade started. Set on the first tick.
+    start: Option<i64>,
+    /// Opacity at the start of the fade.
+    from: f64,
+    /// If we're fading in (showing) or out (hiding).
+    show: bool,
+}
 
 #[derive(Default)]
 pub struct PmenuColors {
@@ -42,6 +56,20 @@ struct State {
 
     width_no_details: i32,
     width_with_details: i32,
+    /// Upper limit for the width, regardless of the widths above.
+    max_width: Option<i32>,
+
+    /// Currently running fade, if any.
+    fade: Option<Fade>,
+    /// If the tick callback for the fade is attached.
+    fade_tick_active: bool,
+
+    /// If the selected item's background pulses.
+    pulse: bool,
+    /// If the tick callback for the pulse is attached.
+    pulse_tick_active: bool,
+    /// Background color of the selected item.
+    pulse_color: Color,
 }
 
 impl State {
@@ -60,6 +88,12 @@ impl State {
             current_width: DEFAULT_WIDTH_NO_DETAILS,
             width_no_details: DEFAULT_WIDTH_NO_DETAILS,
             width_with_details: DEFAULT_WIDTH_WITH_DETAILS,
+            max_width: None,
+            fade: None,
+            fade_tick_active: false,
+            pulse: false,
+            pulse_tick_active: false,
+            pulse_color: Color::default(),
         }
     }
 }
@@ -78,6 +112,8 @@ pub struct Popupmenu {
     list: gtk::ListBox,
     /// Style provider for all internal widgets.
     css_provider: gtk::CssProvider,
+    /// Style provider for the selected item's pulse.
+    pulse_provider: gtk::CssProvider,
 
     /// Flag telling if the info label is shown.
     info_shown: bool,
@@ -85,6 +121,12 @@ pub struct Popupmenu {
     info_label: gtk::Label,
     /// Flag telling if the menu label should be shown on inactive items too.
     show_menu_on_all_items: bool,
+    /// If showing and hiding is animated.
+    animate: bool,
+    /// Radius (in pixels) of the popupmenu's corners.
+    border_radius: u64,
+    /// If a shadow is drawn under the popupmenu.
+    shadow: bool,
 
     state: Rc<RefCell<State>>,
     items: LazyLoader,
@@ -290,9 +332,13 @@ impl Popupmenu {
         Popupmenu {
             items: LazyLoader::new(list.clone(), css_provider.clone()),
             show_menu_on_all_items: false,
+            animate: false,
+            border_radius: 0,
+            shadow: false,
             box_,
             layout,
             css_provider,
+            pulse_provider: gtk::CssProvider::new(),
             list,
             scrolled_list,
             scrolled_info,
@@ -358,14 +404,34 @@ impl Popupmenu {
         self.ensure_container_width();
     }
 
+    /// Scrolls the info view by `delta` lines. Negative values scroll up.
+    pub fn scroll_info(&self, delta: i64) {
+        let metrics = self
+            .info_label
+            .get_pango_context()
+            .get_metrics(None, None)
+            .unwrap();
+        let line_height =
+            f64::from(metrics.get_ascent() + metrics.get_descent())
+                / f64::from(pango::SCALE)
+                + self.line_space as f64;
+
+        let adj = self.scrolled_info.get_vadjustment().unwrap();
+        let max = adj.get_upper() - adj.get_page_size();
+        let value = adj.get_value() + delta as f64 * line_height;
+        adj.set_value(value.min(max).max(adj.get_lower()));
+    }
+
     fn ensure_container_width(&mut self) {
         let mut state = self.state.borrow_mut();
 
-        state.current_width = if self.info_shown {
+        let width = if self.info_shown {
             state.width_with_details
         } else {
             state.width_no_details
         };
+        state.current_width =
+            state.max_width.map_or(width, |max| width.min(max));
 
         self.box_.set_size_request(state.current_width, MAX_HEIGHT);
     }
@@ -386,15 +452,159 @@ impl Popupmenu {
         self.ensure_container_width();
     }
 
+    /// Restores the default widths, with and without the details.
+    pub fn reset_widths(&mut self) {
+        self.set_width(DEFAULT_WIDTH_NO_DETAILS);
+        self.set_width_details(DEFAULT_WIDTH_WITH_DETAILS);
+    }
+
+    /// Sets the max width (in pixels) of the popupmenu. Completion items that
+    /// don't fit are ellipsized. Zero removes the limit.
+    pub fn set_max_width(&mut self, w: i32) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.max_width = if w > 0 { Some(w) } else { None };
+        }
+        self.ensure_container_width();
+    }
+
+    /// Sets if showing and hiding the popupmenu is animated.
+    pub fn set_animation(&mut self, animate: bool) {
+        self.animate = animate;
+    }
+
     /// Hides the popupmenu.
     pub fn hide(&mut self) {
-        self.layout.hide();
+        if self.animate && self.layout.is_visible() {
+            self.fade(false);
+        } else {
+            self.state.borrow_mut().fade = None;
+            self.layout.hide();
+        }
     }
 
     /// Shows the popupmenu.
     pub fn show(&mut self) {
+        if self.animate {
+            self.fade(true);
+        } else {
+            self.state.borrow_mut().fade = None;
+            self.layout.set_opacity(1.0);
+        }
+
         self.layout.show();
         self.box_.check_resize();
+
+        self.start_pulse();
+    }
+
+    /// Sets if the background of the selected item pulses.
+    pub fn set_selection_pulse(&mut self, pulse: bool) {
+        self.state.borrow_mut().pulse = pulse;
+
+        if self.layout.is_visible() {
+            self.start_pulse();
+        }
+    }
+
+    /// Starts pulsing the selected item's background, if enabled. The pulse
+    /// stops once the popupmenu is hidden.
+    fn start_pulse(&self) {
+        let mut state = self.state.borrow_mut();
+        if !state.pulse || state.pulse_tick_active {
+            return;
+        }
+        state.pulse_tick_active = true;
+
+        let selector = if gtk::get_minor_version() < 20 {
+            "GtkListBoxRow:selected,
+            GtkListBoxRow:selected > GtkGrid,
+            GtkListBoxRow:selected > GtkGrid > GtkLabel"
+        } else {
+            "row:selected, row:selected > grid, row:selected > grid > label"
+        };
+
+        let state = self.state.clone();
+        let provider = self.pulse_provider.clone();
+        self.layout.add_tick_callback(move |layout, clock| {
+            let mut state = state.borrow_mut();
+            if !state.pulse || !layout.is_visible() {
+                state.pulse_tick_active = false;
+                CssProviderExt::load_from_data(&provider, b"").unwrap();
+                return Continue(false);
+            }
+
+            let t = (clock.get_frame_time() % PULSE_PERIOD) as f64
+                / PULSE_PERIOD as f64;
+            let alpha = 0.8 + 0.2 * (t * 2.0 * std::f64::consts::PI).cos();
+
+            let color = &state.pulse_color;
+            let css = format!(
+                "{selector} {{
+                    background-color: rgba({r}, {g}, {b}, {a});
+                }}",
+                selector = selector,
+                r = (color.r * 255.0) as u8,
+                g = (color.g * 255.0) as u8,
+                b = (color.b * 255.0) as u8,
+                a = alpha,
+            );
+            CssProviderExt::load_from_data(&provider, css.as_bytes()).unwrap();
+
+            Continue(true)
+        });
+    }
+
+    /// Starts fading in (`show`) or out the popupmenu, from its current
+    /// opacity. The layout is hidden at the end of a fade out.
+    fn fade(&self, show: bool) {
+        let from = if self.layout.is_visible() {
+            self.layout.get_opacity()
+        } else {
+            0.0
+        };
+        self.layout.set_opacity(from);
+
+        let mut state = self.state.borrow_mut();
+        state.fade = Some(Fade {
+            start: None,
+            from,
+            show,
+        });
+
+        if state.fade_tick_active {
+            return;
+        }
+        state.fade_tick_active = true;
+
+        let state = self.state.clone();
+        self.layout.add_tick_callback(move |layout, clock| {
+            let mut state = state.borrow_mut();
+            let fade = match state.fade.as_mut() {
+                Some(fade) => fade,
+                None => {
+                    state.fade_tick_active = false;
+                    return Continue(false);
+                }
+            };
+
+            let now = clock.get_frame_time();
+            let start = *fade.start.get_or_insert(now);
+            let t = ((now - start) as f64 / ANIMATION_DURATION as f64).min(1.0);
+            let to = if fade.show { 1.0 } else { 0.0 };
+            layout.set_opacity(fade.from + (to - fade.from) * t);
+
+            if t < 1.0 {
+                return Continue(true);
+            }
+
+            if !fade.show {
+                layout.hide();
+            }
+            state.fade = None;
+            state.fade_tick_active = false;
+            Continue(false)
+        });
     }
 
     /// Sets the anchor point for popupmenu.
@@ -426,6 +636,7 @@ impl Popupmenu {
         let info_label = self.info_label.clone();
         let info_shown = self.info_shown;
         let show_kind = self.items.get_show_kind();
+        let pulse_provider = self.pulse_provider.clone();
 
         let show_menu_on_all_items = self.show_menu_on_all_items;
 
@@ -435,6 +646,11 @@ impl Popupmenu {
             if let Some(prev) = items.get(state.selected as usize) {
                 prev.info.set_visible(false);
                 prev.menu.set_visible(show_menu_on_all_items);
+                set_provider_recursive(
+                    prev.row.upcast_ref(),
+                    &pulse_provider,
+                    false,
+                );
 
                 if show_kind {
                     // Update the `kind` icon with default fg color.
@@ -471,6 +687,11 @@ impl Popupmenu {
 
                 item.row.grab_focus();
                 list.select_row(Some(&item.row));
+                set_provider_recursive(
+                    item.row.upcast_ref(),
+                    &pulse_provider,
+                    true,
+                );
 
                 {
                     let id = Rc::new(RefCell::new(None));
@@ -522,6 +743,10 @@ impl Popupmenu {
     }
 
     pub fn set_colors(&mut self, hl_defs: &HlDefs) {
+        self.state.borrow_mut().pulse_color = hl_defs
+            .get_hl_group(&HlGroup::PmenuSel)
+            .and_then(|hl| hl.background)
+            .unwrap_or(hl_defs.default_bg);
         self.colors = PmenuColors {
             hl: hl_defs
                 .get_hl_group(&HlGroup::Pmenu)
@@ -570,6 +795,9 @@ impl Popupmenu {
 
             #info-label, list {{
                 border: 1px solid #{normal_fg};
+                border-radius: {radius}px;
+                padding-top: {corner}px;
+                padding-bottom: {corner}px;
             }}
 
             row {{
@@ -583,6 +811,8 @@ impl Popupmenu {
             }}
 
             box {{
+                border-radius: {radius}px;
+                {shadow}
             }}
             ",
             font_wild = self.font.as_wild_css(FontUnit::Point),
@@ -606,6 +836,15 @@ impl Popupmenu {
                 .to_hex(),
             above = above.max(0),
             below = below.max(0),
+            radius = self.border_radius,
+            // Keep the first and last rows, which have square corners,
+            // away from the rounded ones.
+            corner = (self.border_radius + 1) / 2,
+            shadow = if self.shadow {
+                "box-shadow: 0px 2px 6px rgba(0, 0, 0, 0.5);"
+            } else {
+                ""
+            },
         );
         CssProviderExt::load_from_data(&self.css_provider, css.as_bytes())
             .unwrap();
@@ -624,7 +863,7 @@ impl Popupmenu {
             }}
 
             GtkViewport {{
-                border-radius: 0px;
+                border-radius: {radius}px;
             }}
 
             #info-label, GtkViewport {{
@@ -664,6 +903,7 @@ impl Popupmenu {
                 .to_hex(),
             above = above.max(0),
             below = below.max(0),
+            radius = self.border_radius,
         );
         CssProviderExt::load_from_data(&self.css_provider, css.as_bytes())
             .unwrap();
@@ -673,6 +913,43 @@ impl Popupmenu {
         self.font = font;
         self.set_styles(hl_defs);
     }
+
+    /// Sets the radius (in pixels) of the popupmenu's corners, and if a
+    /// shadow is drawn under it.
+    pub fn set_style(
+        &mut self,
+        border_radius: u64,
+        shadow: bool,
+        hl_defs: &HlDefs,
+    ) {
+        self.border_radius = border_radius;
+        self.shadow = shadow;
+        self.set_styles(hl_defs);
+    }
+}
+
+/// Adds (or removes) `provider` to `widget` and all of its descendants. The
+/// provider takes precedence over our other styles.
+fn set_provider_recursive(
+    widget: &gtk::Widget,
+    provider: &gtk::CssProvider,
+    add: bool,
+) {
+    let ctx = widget.get_style_context();
+    if add {
+        ctx.add_provider(
+            provider,
+            gtk::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+        );
+    } else {
+        ctx.remove_provider(provider);
+    }
+
+    if let Some(container) = widget.downcast_ref::<gtk::Container>() {
+        for child in container.get_children() {
+            set_provider_recursive(&child, provider, add);
+        }
+    }
 }
 
 fn ensure_row_visible(list: &gtk::ListBox, row: &gtk::ListBoxRow) {
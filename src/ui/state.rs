@@ -1,35 +1,53 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::rc::Rc;
 
 use gtk::prelude::*;
 
 use log::{debug, error, warn};
-use nvim_rs::{Tabpage, Window as NvimWindow};
+use nvim_rs::{Buffer, Tabpage, Window as NvimWindow};
 
 use crate::nvim_bridge::{
     CmdlineBlockAppend, CmdlineBlockShow, CmdlinePos, CmdlineShow,
-    CmdlineSpecialChar, DefaultColorsSet, GnvimEvent, GridCursorGoto,
-    GridLineSegment, GridResize, GridScroll, HlAttrDefine, HlGroupSet,
-    ModeChange, ModeInfo, ModeInfoSet, MsgSetPos, Notify, OptionSet,
-    PopupmenuShow, RedrawEvent, TablineUpdate, WindowExternalPos,
-    WindowFloatPos, WindowPos,
+    CmdlineSpecialChar, CursorShape, DefaultColorsSet, GnvimEvent,
+    GridCursorGoto, GridLineSegment, GridResize, GridScroll, HlAttrDefine,
+    HlGroupSet, ModeChange, ModeInfo, ModeInfoSet, MouseButtons, MsgSetPos,
+    Notify, OptionSet, PlaceImage, PopupmenuShow, RedrawEvent, ScrollTarget,
+    TablineLabelFormat, TablineMode, TablinePosition, TablineUpdate,
+    UnfocusedCursor, WildmenuLayout, Winbar, WindowExternalPos, WindowFloatPos,
+    WindowPos,
 };
 use crate::nvim_gio::GioNeovim;
+use crate::ui::clipboard;
 use crate::ui::cmdline::Cmdline;
-use crate::ui::color::{HlDefs, HlGroup};
+use crate::ui::color::{
+    Color, HlDefs, HlGroup, RenderWhitespace, TextShadow, VirtualTextStyle,
+    WhitespaceGlyphs,
+};
 use crate::ui::common::spawn_local;
+use crate::ui::context_menu::ContextMenu;
 #[cfg(feature = "libwebkit2gtk")]
 use crate::ui::cursor_tooltip::{CursorTooltip, Gravity};
 use crate::ui::font::Font;
-use crate::ui::grid::{Grid, GridMetrics};
+use crate::ui::geometry::{self, Geometry};
+use crate::ui::grid::{
+    Grid, GridMetrics, MouseButton, DEFAULT_UNDERLINE_THICKNESS_SCALE,
+};
+use crate::ui::mode_indicator::ModeIndicator;
+use crate::ui::pending_keys::PendingKeys;
 use crate::ui::popupmenu::Popupmenu;
 use crate::ui::tabline::Tabline;
+#[cfg(not(feature = "libwebkit2gtk"))]
+use crate::ui::text_tooltip::TextTooltip;
 use crate::ui::window::{MsgWindow, Window};
 
 pub(crate) type Windows = HashMap<i64, Window>;
 pub(crate) type Grids = HashMap<i64, Grid>;
 
+/// Default delay (in milliseconds) before nvim is resized to the window.
+pub(crate) const DEFAULT_RESIZE_DEBOUNCE: u32 = 30;
+
 pub(crate) struct ResizeOptions {
     pub font: Font,
     pub line_space: i64,
@@ -38,6 +56,8 @@ pub(crate) struct ResizeOptions {
 /// Internal structure for `UI` to work on.
 pub(crate) struct UIState {
     pub css_provider: gtk::CssProvider,
+    /// Top level widget, containing the tabline and the overlay.
+    pub main_box: gtk::Box,
     pub windows: Windows,
     /// Container for non-floating windows.
     pub windows_container: gtk::Fixed,
@@ -62,10 +82,21 @@ pub(crate) struct UIState {
     pub popupmenu: Popupmenu,
     pub cmdline: Cmdline,
     pub tabline: Tabline,
+    pub mode_indicator: ModeIndicator,
+    pub pending_keys: PendingKeys,
+    /// Menu for right clicks on the grids. Shared with the grids' event
+    /// handlers.
+    pub context_menu: Rc<ContextMenu>,
     #[cfg(feature = "libwebkit2gtk")]
     pub cursor_tooltip: CursorTooltip,
+    /// Plain text tooltip, in place of the webkit one.
+    #[cfg(not(feature = "libwebkit2gtk"))]
+    pub cursor_tooltip: TextTooltip,
 
     pub wildmenu_shown: bool,
+    /// Cell (row, col, grid) the popupmenu is pinned to, if any. Overrides
+    /// the position given by `popupmenu_show`.
+    pub popupmenu_anchor: Option<(u64, u64, i64)>,
 
     /// Overlay contains our grid(s) and popupmenu.
     #[allow(unused)]
@@ -73,17 +104,110 @@ pub(crate) struct UIState {
 
     /// Source id for delayed call to ui_try_resize.
     pub resize_source_id: Rc<RefCell<Option<glib::SourceId>>>,
+    /// Delay (in ms) before nvim is notified about a resize of the window.
+    pub resize_debounce: Rc<Cell<u32>>,
     /// Resize options that is some if a resize should be send to nvim on flush.
     pub resize_on_flush: Option<ResizeOptions>,
 
     /// Flag for flush to update GUI colors on components that depend on
     /// highlight defs and groups.
     pub hl_changed: bool,
+    /// If the grids' redraw on `default_colors_set` is deferred to the next
+    /// flush, so a burst of color changes (e.g. from `:colorscheme`) is
+    /// drawn only once.
+    pub defer_colors_redraw: bool,
+    /// If the grids need to be redrawn on the next flush.
+    pub redraw_on_flush: bool,
 
     pub font: Font,
     pub line_space: i64,
+    /// Fonts set for specific grids. Other grids use `font`.
+    pub grid_fonts: HashMap<i64, Font>,
 
     pub enable_cursor_animations: bool,
+    /// Dim factor for inactive grids.
+    pub inactive_dim: f64,
+    /// If the grids' colors are drawn inverted.
+    pub invert_colors: bool,
+    /// If our window has the keyboard focus.
+    pub focused: bool,
+    /// How to draw the cursor when our window is not focused.
+    pub unfocused_cursor: UnfocusedCursor,
+    /// Multiplier for the underline thickness.
+    pub underline_thickness_scale: f64,
+    /// Offset (in pixels) for the underline position.
+    pub underline_offset: f64,
+    /// If the cursor is drawn.
+    pub cursor_visible: bool,
+    /// If the window geometry should be saved when the window is closed.
+    pub remember_geometry: bool,
+    /// Minimum size of the window, in cells (cols, rows).
+    pub min_size: Option<(u64, u64)>,
+    /// Extra space (in pixels) between the cells.
+    pub letter_spacing: f64,
+    /// Multiplier for the cells' width.
+    pub cell_width_scale: f64,
+    /// Color and width of the border around the active grid.
+    pub active_border: Option<(Color, f64)>,
+    /// Color and width of the outline around the cursor.
+    pub cursor_outline: Option<(Color, f64)>,
+    /// Color of the lines at the grids' cell boundaries.
+    pub grid_lines: Option<Color>,
+    /// Color for the area outside of the grids.
+    pub letterbox_color: Option<Color>,
+    /// If the text under the cursor is drawn over the cursor.
+    pub cursor_over_text: bool,
+    /// If rows scrolled out of the grids are cached.
+    pub scroll_cache: bool,
+    /// If nvim has the mouse enabled (see `:h 'mouse'`).
+    pub mouse_on: bool,
+    /// If mouse input should be ignored when nvim has the mouse disabled.
+    pub respect_mouse_option: bool,
+    /// If mouse input is sent to nvim. Shared with the grids' event
+    /// handlers.
+    pub mouse_enabled: Rc<Cell<bool>>,
+    /// If the scroll direction is reversed. Shared with the grids' event
+    /// handlers.
+    pub natural_scroll: Rc<Cell<bool>>,
+    /// Where mouse wheel scrolls are sent to. Shared with the grids' event
+    /// handlers.
+    pub scroll_target: Rc<Cell<ScrollTarget>>,
+    /// Grid, row and col of the active grid's cursor. Shared with the
+    /// grids' event handlers.
+    pub active_cursor: Rc<Cell<(i64, u64, u64)>>,
+    /// Cursor position (grid, row, col) that is applied on the next flush,
+    /// so the cursor moves together with the content.
+    pub pending_cursor: Option<(i64, u64, u64)>,
+    /// Mouse buttons whose input is sent to nvim. Shared with the grids'
+    /// event handlers.
+    pub forwarded_buttons: Rc<Cell<MouseButtons>>,
+    /// If text can be selected with the mouse on the GUI side when nvim has
+    /// the mouse disabled.
+    pub gui_selection: bool,
+    /// If a spinner is drawn at the cursor's position while nvim is busy.
+    pub busy_spinner: bool,
+    /// If yanks to the `+` register are copied to the clipboard by us.
+    pub clipboard_sync: bool,
+    /// If changes to the font size are animated.
+    pub zoom_animation: bool,
+    /// If the grids keep the cursor's row at their middle.
+    pub typewriter_mode: bool,
+    /// Lines to keep undimmed around the cursor and how much to dim the rest.
+    pub dim_lines: Option<(u64, u64, f64)>,
+    /// If the grids' animations are paused, because the window isn't
+    /// visible.
+    pub paused: bool,
+    /// Maximum rate of the grids' animation ticks. `None` ticks on every
+    /// frame.
+    pub animation_fps: Option<u32>,
+    /// Blink timings (on, off, wait) of specific cursor shapes, overriding
+    /// the blink from the modes.
+    pub cursor_blinks: HashMap<CursorShape, (u64, u64, u64)>,
+    /// Input method context for the keyboard input.
+    pub im_context: gtk::IMMulticontext,
+    /// If the input method is used for the keyboard input. Shared with the
+    /// key event handlers.
+    pub input_method: Rc<Cell<bool>>,
 }
 
 impl UIState {
@@ -100,7 +224,7 @@ impl UIState {
                 });
             }
             Notify::GnvimEvent(event) => match event {
-                Ok(event) => self.handle_gnvim_event(&event, nvim),
+                Ok(event) => self.handle_gnvim_event(window, &event, nvim),
                 Err(err) => {
                     let nvim = nvim.clone();
                     let msg = format!(
@@ -121,6 +245,14 @@ impl UIState {
         window.set_title(title);
     }
 
+    /// Sets the icon name of the window, which is used e.g. by taskbars. An
+    /// empty `icon` makes the window manager use the title instead.
+    fn set_icon(&mut self, window: &gtk::ApplicationWindow, icon: &str) {
+        if let Some(win) = window.get_window() {
+            win.set_icon_name(Some(icon).filter(|icon| !icon.is_empty()));
+        }
+    }
+
     fn grid_cursor_goto(
         &mut self,
         GridCursorGoto {
@@ -131,7 +263,7 @@ impl UIState {
     ) {
         // Gird cursor goto sets the current cursor to grid_id,
         // so we'll need to handle that here...
-        let grid = if grid_id != self.current_grid {
+        if grid_id != self.current_grid {
             // ...so if the grid_id is not same as the self tells us,
             // set the previous current grid to inactive self.
             let grid = self.grids.get(&self.current_grid).unwrap();
@@ -141,13 +273,13 @@ impl UIState {
             // And set the new current grid to active.
             let grid = self.grids.get(&grid_id).unwrap();
             grid.set_active(true);
-            grid
-        } else {
-            self.grids.get(&grid_id).unwrap()
-        };
+        }
 
-        // And after all that, set the current grid's cursor position.
-        grid.cursor_goto(row, col);
+        // And after all that, record the current grid's cursor position.
+        // The cursor is moved on flush, so it isn't drawn at its new
+        // position before the grid's content is (or vice versa).
+        self.pending_cursor = Some((grid_id, row, col));
+        self.active_cursor.set((grid_id, row, col));
     }
 
     fn grid_resize(
@@ -178,10 +310,15 @@ impl UIState {
                 self.popupmenu.set_base_metrics(grid.get_grid_metrics());
             }
         } else {
+            let font = self
+                .grid_fonts
+                .get(&e.grid)
+                .cloned()
+                .unwrap_or_else(|| self.font.clone());
             let grid = Grid::new(
                 e.grid,
                 &window.get_window().unwrap(),
-                self.font.clone(),
+                font,
                 self.line_space,
                 e.width as usize,
                 e.height as usize,
@@ -189,11 +326,45 @@ impl UIState {
                 self.enable_cursor_animations,
             );
 
+            grid.set_cursor_blinks(self.cursor_blinks.clone());
             if let Some(ref mode) = self.current_mode {
                 grid.set_mode(&mode);
             }
+            grid.set_inactive_dim(self.inactive_dim);
+            grid.set_invert_colors(self.invert_colors);
+            grid.set_focused(self.focused);
+            grid.set_unfocused_cursor(self.unfocused_cursor);
+            grid.set_underline_style(
+                self.underline_thickness_scale,
+                self.underline_offset,
+            );
+            grid.set_cursor_visible(self.cursor_visible);
+            grid.set_letter_spacing(self.letter_spacing, &win);
+            grid.set_cell_width_scale(self.cell_width_scale, &win);
+            grid.set_cursor_over_text(self.cursor_over_text);
+            grid.set_scroll_cache(self.scroll_cache);
+            grid.set_gui_selection(self.gui_selection && !self.mouse_on);
+            grid.set_busy_spinner(self.busy_spinner);
+            grid.set_dim_lines(self.dim_lines);
+            grid.set_zoom_animation(self.zoom_animation);
+            grid.set_typewriter_mode(self.typewriter_mode);
+            grid.set_active_border(self.active_border);
+            grid.set_cursor_outline(self.cursor_outline);
+            grid.set_grid_lines(self.grid_lines);
+            grid.set_letterbox_color(self.letterbox_color);
+            grid.set_paused(self.paused);
+            grid.set_animation_fps(self.animation_fps, &self.hl_defs);
             grid.resize(&win, e.width, e.height, &self.hl_defs);
-            attach_grid_events(&grid, nvim.clone());
+            attach_grid_events(
+                &grid,
+                nvim.clone(),
+                self.mouse_enabled.clone(),
+                self.natural_scroll.clone(),
+                self.scroll_target.clone(),
+                self.active_cursor.clone(),
+                self.forwarded_buttons.clone(),
+                self.context_menu.clone(),
+            );
             self.grids.insert(e.grid, grid);
         }
     }
@@ -208,7 +379,125 @@ impl UIState {
         grid.clear(&self.hl_defs);
     }
 
+    /// Sets the content of the winbar of `winbar.grid`'s window.
+    fn set_winbar(&mut self, winbar: &Winbar) {
+        let font = match self.grids.get(&winbar.grid) {
+            Some(grid) => grid.get_font(),
+            None => {
+                warn!("Can't set winbar, no grid {}", winbar.grid);
+                return;
+            }
+        };
+
+        let window = match self.windows.get_mut(&winbar.grid) {
+            Some(window) => window,
+            None => {
+                warn!("Can't set winbar, grid {} has no window", winbar.grid);
+                return;
+            }
+        };
+
+        if winbar.chunks.is_empty() {
+            window.set_winbar(None);
+            return;
+        }
+
+        let hl_defs = &self.hl_defs;
+        let markup: String = winbar
+            .chunks
+            .iter()
+            .map(|(hl, text)| {
+                hl.pango_markup(
+                    text,
+                    &hl_defs.default_fg,
+                    &hl_defs.default_bg,
+                    &hl_defs.default_sp,
+                )
+            })
+            .collect();
+        let markup = format!(
+            "<span font_desc=\"{}\">{}</span>",
+            glib::markup_escape_text(&font.as_pango_font().to_string()),
+            markup
+        );
+
+        window.set_winbar(Some(&markup));
+    }
+
+    /// Sets the tabline's own font. An empty `guifont` uses the global font.
+    fn tabline_set_font(&mut self, guifont: &str) {
+        let font = if guifont.is_empty() {
+            None
+        } else {
+            match Font::from_guifont(guifont) {
+                Ok(font) => Some(font),
+                Err(_) => {
+                    error!("Invalid font for tabline: {}", guifont);
+                    return;
+                }
+            }
+        };
+
+        self.tabline.set_own_font(font, &self.hl_defs);
+    }
+
+    /// Sets the font of a single grid and resizes the grid to fit in its
+    /// current size. An empty `guifont` resets the grid to the global font.
+    fn set_grid_font(
+        &mut self,
+        grid_id: i64,
+        guifont: &str,
+        nvim: &GioNeovim,
+        window: &gtk::ApplicationWindow,
+    ) {
+        if guifont.is_empty() {
+            self.grid_fonts.remove(&grid_id);
+        } else {
+            match Font::from_guifont(guifont) {
+                Ok(font) => {
+                    self.grid_fonts.insert(grid_id, font);
+                }
+                Err(_) => {
+                    error!("Invalid font for grid {}: {}", grid_id, guifont);
+                    return;
+                }
+            }
+        }
+
+        // Grids that don't exist yet get the font when they're created.
+        let grid = match self.grids.get(&grid_id) {
+            Some(grid) => grid,
+            None => return,
+        };
+
+        // Without a font of its own, the grid uses the same font as the
+        // base grid (which might be zoomed).
+        let font = match self.grid_fonts.get(&grid_id) {
+            Some(font) => font.clone(),
+            None => match self.grids.get(&1) {
+                Some(base) if !self.grid_fonts.contains_key(&1) => {
+                    base.get_font()
+                }
+                _ => self.font.clone(),
+            },
+        };
+
+        let win = window.get_window().unwrap();
+        grid.update_cell_metrics(font, grid.get_line_space(), &win);
+
+        let (cols, rows) = grid.calc_size();
+        let nvim = nvim.clone();
+        spawn_local(async move {
+            if let Err(err) = nvim.ui_try_resize_grid(grid_id, cols, rows).await
+            {
+                error!("Failed to resize grid({}): {}", grid_id, err);
+            }
+        });
+    }
+
     fn grid_destroy(&mut self, grid: &i64) {
+        self.grid_fonts.remove(grid);
+
         // Drop grid.
         if self.grids.remove(grid).is_none() {
             warn!(
@@ -255,8 +544,12 @@ impl UIState {
             hl.special = Some(sp);
         }
 
-        for grid in self.grids.values() {
-            grid.redraw(&self.hl_defs);
+        if self.defer_colors_redraw {
+            self.redraw_on_flush = true;
+        } else {
+            for grid in self.grids.values() {
+                grid.redraw(&self.hl_defs);
+            }
         }
 
         #[cfg(feature = "libwebkit2gtk")]
@@ -297,6 +590,76 @@ impl UIState {
         self.hl_changed = true;
     }
 
+    /// Returns the pending resize options, or the current ones of the base
+    /// grid if there is nothing pending.
+    fn take_resize_options(&mut self) -> ResizeOptions {
+        self.resize_on_flush.take().unwrap_or_else(|| {
+            let grid = self.grids.get(&1).unwrap();
+            ResizeOptions {
+                font: grid.get_font(),
+                line_space: grid.get_line_space(),
+            }
+        })
+    }
+
+    /// Changes the font size by `delta`. If `delta` is `None`, the font is
+    /// reset to the one set through `guifont`.
+    fn font_zoom(
+        &mut self,
+        delta: Option<f64>,
+        nvim: &GioNeovim,
+        window: &gtk::ApplicationWindow,
+    ) {
+        let mut opts = self.take_resize_options();
+
+        opts.font = match delta {
+            Some(delta) => opts.font.zoom(delta as f32),
+            None => self.font.clone(),
+        };
+
+        self.resize_on_flush = Some(opts);
+        self.flush(nvim, window);
+    }
+
+    fn set_letter_spacing(
+        &mut self,
+        spacing: f64,
+        nvim: &GioNeovim,
+        window: &gtk::ApplicationWindow,
+    ) {
+        self.letter_spacing = spacing;
+
+        let win = window.get_window().unwrap();
+        for grid in self.grids.values() {
+            grid.set_letter_spacing(spacing, &win);
+        }
+
+        // Our cell size changed, so nvim needs to be resized.
+        let opts = self.take_resize_options();
+        self.resize_on_flush = Some(opts);
+        self.flush(nvim, window);
+    }
+
+    fn set_cell_width_scale(
+        &mut self,
+        scale: f64,
+        nvim: &GioNeovim,
+        window: &gtk::ApplicationWindow,
+    ) {
+        // Zero (or anything invalid) keeps the font's own width.
+        self.cell_width_scale = if scale > 0.0 { scale } else { 1.0 };
+
+        let win = window.get_window().unwrap();
+        for grid in self.grids.values() {
+            grid.set_cell_width_scale(self.cell_width_scale, &win);
+        }
+
+        // Our cell size changed, so nvim needs to be resized.
+        let opts = self.take_resize_options();
+        self.resize_on_flush = Some(opts);
+        self.flush(nvim, window);
+    }
+
     fn option_set(&mut self, opt: OptionSet) {
         match opt {
             OptionSet::GuiFont(font) => {
@@ -304,14 +667,7 @@ impl UIState {
 
                 self.font = font.clone();
 
-                let mut opts =
-                    self.resize_on_flush.take().unwrap_or_else(|| {
-                        let grid = self.grids.get(&1).unwrap();
-                        ResizeOptions {
-                            font: grid.get_font(),
-                            line_space: grid.get_line_space(),
-                        }
-                    });
+                let mut opts = self.take_resize_options();
 
                 opts.font = font;
 
@@ -319,14 +675,7 @@ impl UIState {
             }
             OptionSet::LineSpace(val) => {
                 self.line_space = val;
-                let mut opts =
-                    self.resize_on_flush.take().unwrap_or_else(|| {
-                        let grid = self.grids.get(&1).unwrap();
-                        ResizeOptions {
-                            font: grid.get_font(),
-                            line_space: grid.get_line_space(),
-                        }
-                    });
+                let mut opts = self.take_resize_options();
 
                 opts.line_space = val;
 
@@ -372,6 +721,8 @@ impl UIState {
         for grid in self.grids.values() {
             grid.set_mode(mode);
         }
+
+        self.mode_indicator.set_mode(mode, &self.hl_defs);
     }
 
     fn set_busy(&mut self, busy: bool) {
@@ -380,7 +731,157 @@ impl UIState {
         }
     }
 
+    /// Renders the grids again for the window's new scale factor, so they
+    /// stay sharp (e.g. when moving the window to a HiDPI monitor).
+    pub fn update_scale_factor(&mut self, window: &gtk::ApplicationWindow) {
+        let win = match window.get_window() {
+            Some(win) => win,
+            None => return,
+        };
+
+        for grid in self.grids.values() {
+            grid.update_scale_factor(&win, &self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    /// Pauses the grids' animations (e.g. when the window is minimized), or
+    /// resumes them.
+    pub fn set_paused(&mut self, paused: bool) {
+        if self.paused == paused {
+            return;
+        }
+
+        self.paused = paused;
+        self.grids.values().for_each(|g| g.set_paused(paused));
+    }
+
+    fn place_image(&self, image: &PlaceImage) {
+        let grid = match self.grids.get(&image.grid) {
+            Some(grid) => grid,
+            None => {
+                error!(
+                    "Can't place image on non-existing grid ({})",
+                    image.grid
+                );
+                return;
+            }
+        };
+
+        match gdk_pixbuf::Pixbuf::from_file(&image.path) {
+            Ok(pixbuf) => grid.place_image(
+                image.row,
+                image.col,
+                image.width,
+                image.height,
+                pixbuf,
+            ),
+            Err(err) => {
+                error!("Failed to load image '{}': {}", image.path, err)
+            }
+        }
+    }
+
+    /// Enables or disables the input method (e.g. to avoid composing text
+    /// in normal mode).
+    fn set_input_method(&mut self, enable: bool) {
+        if self.input_method.get() == enable {
+            return;
+        }
+
+        self.input_method.set(enable);
+
+        if enable {
+            if self.focused {
+                self.im_context.focus_in();
+            }
+        } else {
+            self.im_context.focus_out();
+            self.im_context.reset();
+        }
+    }
+
+    fn set_busy_spinner(&mut self, enable: bool) {
+        self.busy_spinner = enable;
+        self.grids.values().for_each(|g| g.set_busy_spinner(enable));
+    }
+
+    fn set_zoom_animation(&mut self, enable: bool) {
+        self.zoom_animation = enable;
+        self.grids
+            .values()
+            .for_each(|g| g.set_zoom_animation(enable));
+    }
+
+    fn set_active_border(&mut self, color: Color, width: f64) {
+        self.active_border = Some((color, width)).filter(|_| width > 0.0);
+        self.grids
+            .values()
+            .for_each(|g| g.set_active_border(self.active_border));
+    }
+
+    fn set_cursor_outline(&mut self, color: Color, width: f64) {
+        self.cursor_outline = Some((color, width)).filter(|_| width > 0.0);
+        self.grids
+            .values()
+            .for_each(|g| g.set_cursor_outline(self.cursor_outline));
+    }
+
+    fn set_typewriter_mode(&mut self, enable: bool) {
+        self.typewriter_mode = enable;
+        self.grids
+            .values()
+            .for_each(|g| g.set_typewriter_mode(enable));
+    }
+
+    fn set_dim_lines(&mut self, above: u64, below: u64, amount: f64) {
+        let amount = amount.max(0.0).min(1.0);
+        self.dim_lines = Some((above, below, amount)).filter(|_| amount > 0.0);
+        self.grids
+            .values()
+            .for_each(|g| g.set_dim_lines(self.dim_lines));
+    }
+
+    fn set_mouse(&mut self, on: bool) {
+        self.mouse_on = on;
+        self.update_mouse_enabled();
+    }
+
+    fn set_respect_mouse_option(&mut self, respect: bool) {
+        self.respect_mouse_option = respect;
+        self.update_mouse_enabled();
+    }
+
+    fn set_gui_selection(&mut self, enable: bool) {
+        self.gui_selection = enable;
+        self.update_mouse_enabled();
+    }
+
+    fn update_mouse_enabled(&self) {
+        self.mouse_enabled
+            .set(self.mouse_on || !self.respect_mouse_option);
+
+        let gui_selection = self.gui_selection && !self.mouse_on;
+        self.grids
+            .values()
+            .for_each(|g| g.set_gui_selection(gui_selection));
+    }
+
     fn flush(&mut self, nvim: &GioNeovim, window: &gtk::ApplicationWindow) {
+        if let Some((grid_id, row, col)) = self.pending_cursor.take() {
+            // The grid might have been destroyed since.
+            if let Some(grid) = self.grids.get(&grid_id) {
+                grid.cursor_goto(row, col, &self.hl_defs);
+            }
+        }
+
+        if self.redraw_on_flush {
+            self.redraw_on_flush = false;
+            for grid in self.grids.values() {
+                grid.redraw(&self.hl_defs);
+            }
+        }
+
         for grid in self.grids.values() {
             grid.flush(&self.hl_defs);
         }
@@ -388,13 +889,16 @@ impl UIState {
         if let Some(opts) = self.resize_on_flush.take() {
             let win = window.get_window().unwrap();
             for grid in self.grids.values() {
-                grid.update_cell_metrics(
-                    opts.font.clone(),
-                    opts.line_space,
-                    &win,
-                );
+                let font = self
+                    .grid_fonts
+                    .get(&grid.id)
+                    .cloned()
+                    .unwrap_or_else(|| opts.font.clone());
+                grid.update_cell_metrics(font, opts.line_space, &win);
             }
 
+            self.update_min_size(window);
+
             let grid = self.grids.get(&1).unwrap();
             let (cols, rows) = grid.calc_size();
 
@@ -416,7 +920,9 @@ impl UIState {
             self.popupmenu.set_font(opts.font.clone(), &self.hl_defs);
             self.cmdline.set_font(opts.font.clone(), &self.hl_defs);
             self.tabline.set_font(opts.font.clone(), &self.hl_defs);
-            #[cfg(feature = "libwebkit2gtk")]
+            self.mode_indicator
+                .set_font(opts.font.clone(), &self.hl_defs);
+            self.pending_keys.set_font(opts.font.clone(), &self.hl_defs);
             self.cursor_tooltip.set_font(opts.font.clone());
 
             self.cmdline.set_line_space(opts.line_space);
@@ -430,6 +936,8 @@ impl UIState {
             self.tabline.set_colors(&self.hl_defs);
             self.cmdline.set_colors(&self.hl_defs);
             self.cmdline.wildmenu_set_colors(&self.hl_defs);
+            self.mode_indicator.set_colors(&self.hl_defs);
+            self.pending_keys.set_colors(&self.hl_defs);
 
             let msgsep = self
                 .hl_defs
@@ -472,14 +980,24 @@ impl UIState {
         } else {
             self.popupmenu.set_items(popupmenu.items, &self.hl_defs);
 
-            let grid = self.grids.get(&self.current_grid).unwrap();
-            let mut rect = grid.get_rect_for_cell(popupmenu.row, popupmenu.col);
+            let (row, col, anchor_grid) = self.popupmenu_anchor.unwrap_or((
+                popupmenu.row,
+                popupmenu.col,
+                popupmenu.grid,
+            ));
 
-            if let Some(window) = self.windows.get(&popupmenu.grid) {
+            let grid = self
+                .grids
+                .get(&anchor_grid)
+                .or_else(|| self.grids.get(&self.current_grid))
+                .unwrap();
+            let mut rect = grid.get_rect_for_cell(row, col);
+
+            if let Some(window) = self.windows.get(&anchor_grid) {
                 rect.x += window.x as i32;
                 rect.y += window.y as i32;
-            } else if popupmenu.grid != 1 {
-                error!("No window for non-default grid ({})", popupmenu.grid);
+            } else if anchor_grid != 1 {
+                error!("No window for non-default grid ({})", anchor_grid);
             }
 
             self.popupmenu.set_anchor(rect);
@@ -530,9 +1048,25 @@ impl UIState {
         }
     }
 
+    fn tabline_set_position(&mut self, pos: TablinePosition) {
+        let widget = self.tabline.get_widget();
+        let (position, tab_pos) = match pos {
+            TablinePosition::Top => (0, gtk::PositionType::Top),
+            TablinePosition::Bottom => (-1, gtk::PositionType::Bottom),
+        };
+
+        self.main_box.reorder_child(&widget, position);
+        self.tabline.set_tab_pos(tab_pos);
+    }
+
     fn tabline_update(
         &mut self,
-        TablineUpdate { current, tabs }: TablineUpdate,
+        TablineUpdate {
+            current,
+            tabs,
+            current_buffer,
+            buffers,
+        }: TablineUpdate,
         nvim: &GioNeovim,
     ) {
         let current = Tabpage::new(current, nvim.clone());
@@ -540,7 +1074,16 @@ impl UIState {
             .into_iter()
             .map(|(value, name)| (Tabpage::new(value, nvim.clone()), name))
             .collect();
-        self.tabline.update(current, tabs);
+        let current_buffer = if current_buffer.is_nil() {
+            None
+        } else {
+            Some(Buffer::new(current_buffer, nvim.clone()))
+        };
+        let buffers = buffers
+            .into_iter()
+            .map(|(value, name)| (Buffer::new(value, nvim.clone()), name))
+            .collect();
+        self.tabline.update(current, tabs, current_buffer, buffers);
     }
 
     fn cmdline_show(&mut self, cmdline_show: CmdlineShow) {
@@ -763,6 +1306,330 @@ impl UIState {
             .for_each(|g| g.enable_cursor_animations(enable));
     }
 
+    fn set_inactive_dim(&mut self, dim: f64) {
+        let dim = dim.max(0.0).min(1.0);
+        self.inactive_dim = dim;
+        self.grids.values().for_each(|g| g.set_inactive_dim(dim));
+    }
+
+    fn set_invert_colors(&mut self, invert: bool) {
+        self.invert_colors = invert;
+        self.grids
+            .values()
+            .for_each(|g| g.set_invert_colors(invert));
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.grids.values().for_each(|g| g.set_focused(focused));
+    }
+
+    fn set_unfocused_cursor(&mut self, cursor: UnfocusedCursor) {
+        self.unfocused_cursor = cursor;
+        self.grids
+            .values()
+            .for_each(|g| g.set_unfocused_cursor(cursor));
+    }
+
+    fn set_underline_style(&mut self, scale: f64, offset: f64) {
+        self.underline_thickness_scale = scale;
+        self.underline_offset = offset;
+
+        for grid in self.grids.values() {
+            grid.set_underline_style(scale, offset);
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.cursor_visible = visible;
+        self.grids
+            .values()
+            .for_each(|g| g.set_cursor_visible(visible));
+    }
+
+    fn set_cursor_over_text(&mut self, over_text: bool) {
+        self.cursor_over_text = over_text;
+
+        for grid in self.grids.values() {
+            grid.set_cursor_over_text(over_text);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    fn set_scroll_cache(&mut self, enable: bool) {
+        self.scroll_cache = enable;
+        self.grids.values().for_each(|g| g.set_scroll_cache(enable));
+    }
+
+    fn set_extra_cursors(&mut self, cursors: &[(i64, u64, u64)]) {
+        for (id, grid) in self.grids.iter() {
+            let positions = cursors
+                .iter()
+                .filter(|(cursor_grid, _, _)| cursor_grid == id)
+                .map(|(_, row, col)| (*row, *col))
+                .collect();
+            grid.set_extra_cursors(positions);
+        }
+    }
+
+    fn set_remember_geometry(
+        &mut self,
+        window: &gtk::ApplicationWindow,
+        remember: bool,
+    ) {
+        self.remember_geometry = remember;
+
+        if remember {
+            self.save_geometry(window);
+        } else if let Err(err) = geometry::remove() {
+            error!("Failed to remove window geometry: {}", err);
+        }
+    }
+
+    /// Saves the window geometry, if the user wants it to be remembered.
+    pub fn save_geometry(&self, window: &gtk::ApplicationWindow) {
+        if !self.remember_geometry {
+            return;
+        }
+
+        if let Err(err) = geometry::save(&Geometry::from_window(window)) {
+            error!("Failed to save window geometry: {}", err);
+        }
+    }
+
+    fn set_min_size(
+        &mut self,
+        window: &gtk::ApplicationWindow,
+        cols: u64,
+        rows: u64,
+    ) {
+        self.min_size = Some((cols, rows)).filter(|_| cols > 0 && rows > 0);
+        self.update_min_size(window);
+    }
+
+    /// Sets the window's geometry hints so that the base grid can't become
+    /// smaller than `min_size`.
+    fn update_min_size(&self, window: &gtk::ApplicationWindow) {
+        let (cols, rows) = match self.min_size {
+            Some(size) => size,
+            None => {
+                window.set_geometry_hints(
+                    None::<&gtk::Widget>,
+                    None,
+                    gdk::WindowHints::empty(),
+                );
+                return;
+            }
+        };
+
+        let grid = match self.grids.get(&1) {
+            Some(grid) => grid,
+            None => return,
+        };
+
+        // Account for the other widgets around the grid (e.g. the tabline).
+        let (width, height) = window.get_size();
+        let alloc = grid.widget().get_allocation();
+        let extra_w = (width - alloc.width).max(0);
+        let extra_h = (height - alloc.height).max(0);
+
+        let metrics = grid.get_grid_metrics();
+        let geometry = gdk::Geometry {
+            min_width: (cols as f64 * metrics.cell_width).ceil() as i32
+                + extra_w,
+            min_height: (rows as f64 * metrics.cell_height).ceil() as i32
+                + extra_h,
+            max_width: 0,
+            max_height: 0,
+            base_width: 0,
+            base_height: 0,
+            width_inc: 0,
+            height_inc: 0,
+            min_aspect: 0.0,
+            max_aspect: 0.0,
+            win_gravity: gdk::Gravity::NorthWest,
+        };
+
+        window.set_geometry_hints(
+            None::<&gtk::Widget>,
+            Some(&geometry),
+            gdk::WindowHints::MIN_SIZE,
+        );
+    }
+
+    fn set_box_drawing(&mut self, enable: bool) {
+        self.hl_defs.box_drawing = enable;
+
+        for grid in self.grids.values() {
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    fn set_min_contrast(&mut self, ratio: f64) {
+        self.hl_defs.min_contrast = ratio;
+
+        for grid in self.grids.values() {
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    fn set_pixel_snap(&mut self, snap: bool) {
+        self.hl_defs.pixel_snap = snap;
+
+        for grid in self.grids.values() {
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    fn set_render_whitespace(&mut self, ws: RenderWhitespace) {
+        self.hl_defs.render_whitespace = ws;
+
+        for grid in self.grids.values() {
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    fn set_whitespace_glyphs(&mut self, glyphs: Option<WhitespaceGlyphs>) {
+        self.hl_defs.whitespace_glyphs = glyphs;
+
+        for grid in self.grids.values() {
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    fn set_text_shadow(&mut self, shadow: Option<TextShadow>) {
+        self.hl_defs.text_shadow = shadow;
+
+        for grid in self.grids.values() {
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
+    /// Restores the settings that can be changed with `GnvimEvent`s to their
+    /// defaults, and re-applies the colors from the current hl defs.
+    /// Content set by plugins (winbars, images, extra cursors etc.), the
+    /// remembered geometry and the ext_* UI options are left alone.
+    fn reset_settings(
+        &mut self,
+        window: &gtk::ApplicationWindow,
+        nvim: &GioNeovim,
+    ) {
+        self.popupmenu.reset_widths();
+        self.popupmenu.set_show_menu_on_all_items(false);
+        self.popupmenu.set_max_width(0);
+        self.popupmenu.set_animation(false);
+        self.popupmenu.set_selection_pulse(false);
+        self.popupmenu.set_style(0, false, &self.hl_defs);
+        self.popupmenu_anchor = None;
+
+        self.cmdline.wildmenu_set_layout(WildmenuLayout::Vertical);
+        self.cmdline.set_block_max_rows(0);
+        self.cmdline.set_position(0.0);
+        self.cmdline.set_border(false, &self.hl_defs);
+
+        self.tabline_set_position(TablinePosition::Top);
+        self.tabline.set_mode(TablineMode::Tabs);
+        self.tabline.set_label_format(TablineLabelFormat::Full);
+        self.tabline_set_font("");
+
+        self.mode_indicator.set_enabled(false);
+        self.context_menu.reset();
+        #[cfg(feature = "libwebkit2gtk")]
+        {
+            self.cursor_tooltip.reset_style();
+            self.cursor_tooltip.set_code_font(None);
+        }
+
+        self.hl_defs.virtual_text_style = VirtualTextStyle::default();
+        self.hl_defs.box_drawing = false;
+        self.hl_defs.min_contrast = 0.0;
+        self.hl_defs.pixel_snap = false;
+        self.hl_defs.render_whitespace = RenderWhitespace::None;
+        self.hl_defs.text_shadow = None;
+        self.hl_defs.whitespace_glyphs = None;
+
+        self.enable_cursor_animations(true);
+        self.set_inactive_dim(0.0);
+        self.set_invert_colors(false);
+        self.set_unfocused_cursor(UnfocusedCursor::Hollow);
+        self.underline_thickness_scale = DEFAULT_UNDERLINE_THICKNESS_SCALE;
+        self.underline_offset = 0.0;
+        self.set_cursor_visible(true);
+        self.set_cursor_over_text(false);
+        self.set_cursor_outline(Color::default(), 0.0);
+        self.set_active_border(Color::default(), 0.0);
+        self.set_dim_lines(0, 0, 0.0);
+        self.set_zoom_animation(false);
+        self.set_typewriter_mode(false);
+        self.set_scroll_cache(false);
+        self.set_busy_spinner(false);
+        self.set_input_method(true);
+
+        self.grid_lines = None;
+        self.letterbox_color = None;
+        self.animation_fps = None;
+        self.cursor_blinks.clear();
+        for grid in self.grids.values() {
+            grid.set_underline_style(DEFAULT_UNDERLINE_THICKNESS_SCALE, 0.0);
+            grid.set_cursor_blinks(HashMap::new());
+            grid.set_grid_lines(None);
+            grid.set_scroll_indicator(None);
+            grid.set_letterbox_color(None);
+            grid.set_animation_fps(None, &self.hl_defs);
+        }
+
+        self.natural_scroll.set(false);
+        self.scroll_target.set(ScrollTarget::Pointer);
+        self.forwarded_buttons.set(MouseButtons::default());
+        self.respect_mouse_option = false;
+        self.set_gui_selection(false);
+
+        self.clipboard_sync = false;
+        self.defer_colors_redraw = true;
+        self.resize_debounce.set(DEFAULT_RESIZE_DEBOUNCE);
+        self.set_min_size(window, 0, 0);
+
+        let grid_fonts = self.grid_fonts.keys().cloned().collect::<Vec<_>>();
+        for grid in grid_fonts {
+            self.set_grid_font(grid, "", nvim, window);
+        }
+
+        // Reset the cell size and the font zoom, and resize nvim once on the
+        // flush below.
+        self.letter_spacing = 0.0;
+        self.cell_width_scale = 1.0;
+        let win = window.get_window().unwrap();
+        for grid in self.grids.values() {
+            grid.set_letter_spacing(0.0, &win);
+            grid.set_cell_width_scale(1.0, &win);
+            grid.redraw(&self.hl_defs);
+        }
+
+        let mut opts = self.take_resize_options();
+        opts.font = self.font.clone();
+        self.resize_on_flush = Some(opts);
+
+        self.hl_changed = true;
+        self.flush(nvim, window);
+    }
+
+    fn set_virtual_text_style(&mut self, style: VirtualTextStyle) {
+        self.hl_defs.virtual_text_style = style;
+
+        for grid in self.grids.values() {
+            grid.redraw(&self.hl_defs);
+            grid.flush(&self.hl_defs);
+        }
+    }
+
     fn handle_redraw_event(
         &mut self,
         window: &gtk::ApplicationWindow,
@@ -773,6 +1640,9 @@ impl UIState {
             RedrawEvent::SetTitle(evt) => {
                 evt.iter().for_each(|e| self.set_title(&window, e));
             }
+            RedrawEvent::SetIcon(evt) => {
+                evt.iter().for_each(|e| self.set_icon(&window, e));
+            }
             RedrawEvent::GridLine(evt) => {
                 evt.into_iter().for_each(|line| self.grid_line(line))
             }
@@ -810,6 +1680,7 @@ impl UIState {
                 evt.into_iter().for_each(|e| self.mode_change(e));
             }
             RedrawEvent::SetBusy(busy) => self.set_busy(busy),
+            RedrawEvent::SetMouse(on) => self.set_mouse(on),
             RedrawEvent::Flush() => self.flush(nvim, window),
             RedrawEvent::PopupmenuShow(evt) => {
                 evt.into_iter().for_each(|e| self.popupmenu_show(e));
@@ -874,7 +1745,12 @@ impl UIState {
         });
     }
 
-    fn handle_gnvim_event(&mut self, event: &GnvimEvent, nvim: &GioNeovim) {
+    fn handle_gnvim_event(
+        &mut self,
+        window: &gtk::ApplicationWindow,
+        event: &GnvimEvent,
+        nvim: &GioNeovim,
+    ) {
         match event {
             GnvimEvent::CompletionMenuToggleInfo => {
                 self.popupmenu.toggle_show_info()
@@ -888,9 +1764,250 @@ impl UIState {
             GnvimEvent::PopupmenuShowMenuOnAllItems(should_show) => {
                 self.popupmenu.set_show_menu_on_all_items(*should_show);
             }
+            GnvimEvent::PopupmenuInfoScroll(delta) => {
+                self.popupmenu.scroll_info(*delta);
+            }
+            GnvimEvent::PopupmenuSetAnchor(row, col, grid) => {
+                self.popupmenu_anchor = Some((*row, *col, *grid));
+            }
+            GnvimEvent::PopupmenuClearAnchor => {
+                self.popupmenu_anchor = None;
+            }
+            GnvimEvent::PopupmenuMaxWidthPx(width) => {
+                self.popupmenu
+                    .set_max_width(i32::try_from(*width).unwrap_or(i32::MAX));
+            }
+            GnvimEvent::SetPopupmenuAnimation(enable) => {
+                self.popupmenu.set_animation(*enable);
+            }
+            GnvimEvent::PopupmenuSelectionPulse(enable) => {
+                self.popupmenu.set_selection_pulse(*enable);
+            }
+            GnvimEvent::PopupmenuSetStyle(radius, shadow) => {
+                self.popupmenu.set_style(*radius, *shadow, &self.hl_defs);
+            }
             GnvimEvent::EnableCursorAnimations(enable) => {
                 self.enable_cursor_animations(*enable);
             }
+            GnvimEvent::SetInactiveDim(dim) => {
+                self.set_inactive_dim(*dim);
+            }
+            GnvimEvent::TablineSetPosition(pos) => {
+                self.tabline_set_position(*pos);
+            }
+            GnvimEvent::SetVirtualTextStyle(style) => {
+                self.set_virtual_text_style(*style);
+            }
+            GnvimEvent::FontZoom(delta) => {
+                self.font_zoom(Some(*delta), nvim, window);
+            }
+            GnvimEvent::FontZoomReset => {
+                self.font_zoom(None, nvim, window);
+            }
+            GnvimEvent::SetInvertColors(invert) => {
+                self.set_invert_colors(*invert);
+            }
+            GnvimEvent::SetModeIndicator(enable) => {
+                self.mode_indicator.set_enabled(*enable);
+            }
+            GnvimEvent::WildmenuSetLayout(layout) => {
+                self.cmdline.wildmenu_set_layout(*layout);
+            }
+            GnvimEvent::SetUnfocusedCursor(cursor) => {
+                self.set_unfocused_cursor(*cursor);
+            }
+            GnvimEvent::SetUnderlineStyle(scale, offset) => {
+                self.set_underline_style(*scale, *offset);
+            }
+            GnvimEvent::SetCursorVisible(visible) => {
+                self.set_cursor_visible(*visible);
+            }
+            GnvimEvent::SetRememberGeometry(remember) => {
+                self.set_remember_geometry(window, *remember);
+            }
+            GnvimEvent::SetBoxDrawing(enable) => {
+                self.set_box_drawing(*enable);
+            }
+            GnvimEvent::SetLetterSpacing(spacing) => {
+                self.set_letter_spacing(*spacing, nvim, window);
+            }
+            GnvimEvent::SetMinContrast(ratio) => {
+                self.set_min_contrast(*ratio);
+            }
+            GnvimEvent::CmdlineBlockMaxHeight(rows) => {
+                self.cmdline.set_block_max_rows(*rows);
+            }
+            GnvimEvent::Paste => clipboard::paste(nvim.clone()),
+            GnvimEvent::SetPixelSnap(snap) => {
+                self.set_pixel_snap(*snap);
+            }
+            GnvimEvent::SetCursorOverText(over_text) => {
+                self.set_cursor_over_text(*over_text);
+            }
+            GnvimEvent::SetRenderWhitespace(ws) => {
+                self.set_render_whitespace(*ws);
+            }
+            GnvimEvent::SetExtraCursors(cursors) => {
+                self.set_extra_cursors(cursors);
+            }
+            GnvimEvent::SetResizeDebounce(ms) => {
+                self.resize_debounce.set(*ms);
+            }
+            GnvimEvent::SetScrollCache(enable) => {
+                self.set_scroll_cache(*enable);
+            }
+            GnvimEvent::SetRespectMouseOption(respect) => {
+                self.set_respect_mouse_option(*respect);
+            }
+            GnvimEvent::SetGuiSelection(enable) => {
+                self.set_gui_selection(*enable);
+            }
+            GnvimEvent::CmdlineSetPosition(position) => {
+                self.cmdline.set_position(*position);
+            }
+            GnvimEvent::SetBusySpinner(enable) => {
+                self.set_busy_spinner(*enable);
+            }
+            GnvimEvent::TablineMode(mode) => {
+                self.tabline.set_mode(*mode);
+            }
+            GnvimEvent::PlaceImage(image) => self.place_image(image),
+            GnvimEvent::ClearImages => {
+                self.grids.values().for_each(|g| g.clear_images());
+            }
+            GnvimEvent::SetInputMethod(enable) => {
+                self.set_input_method(*enable);
+            }
+            GnvimEvent::SetNaturalScroll(enable) => {
+                self.natural_scroll.set(*enable);
+            }
+            GnvimEvent::SetDimInactiveLines(above, below, amount) => {
+                self.set_dim_lines(*above, *below, *amount);
+            }
+            GnvimEvent::SetMinSize(cols, rows) => {
+                self.set_min_size(window, *cols, *rows);
+            }
+            GnvimEvent::SetGridFont(grid, font) => {
+                self.set_grid_font(*grid, font, nvim, window);
+            }
+            GnvimEvent::SetWinbar(winbar) => self.set_winbar(winbar),
+            GnvimEvent::SetZoomAnimation(enable) => {
+                self.set_zoom_animation(*enable);
+            }
+            GnvimEvent::SetClipboardSync(enable) => {
+                self.clipboard_sync = *enable;
+            }
+            GnvimEvent::ClipboardSetText(text) => {
+                if self.clipboard_sync {
+                    clipboard::set_text(text);
+                }
+            }
+            GnvimEvent::SetLineScale(grid, row, scale) => {
+                if let Some(grid) = self.grids.get(grid) {
+                    grid.set_line_scale(*row, *scale);
+                } else {
+                    error!("Can't scale line of non-existing grid ({})", grid);
+                }
+            }
+            GnvimEvent::SetPendingKeys(keys) => {
+                self.pending_keys.set_keys(keys);
+            }
+            GnvimEvent::CmdlineSetBorder(border) => {
+                self.cmdline.set_border(*border, &self.hl_defs);
+            }
+            GnvimEvent::TablineSetFont(guifont) => {
+                self.tabline_set_font(guifont);
+            }
+            GnvimEvent::SetTypewriterMode(enable) => {
+                self.set_typewriter_mode(*enable);
+            }
+            GnvimEvent::CmdlineSetCursorBlink(on, off) => {
+                self.cmdline.set_cursor_blink(*on, *off);
+            }
+            GnvimEvent::SetCellWidthScale(scale) => {
+                self.set_cell_width_scale(*scale, nvim, window);
+            }
+            GnvimEvent::SetActiveBorder(color, width) => {
+                self.set_active_border(*color, *width);
+            }
+            GnvimEvent::SetLetterboxColor(color) => {
+                self.letterbox_color = *color;
+                self.grids
+                    .values()
+                    .for_each(|g| g.set_letterbox_color(*color));
+            }
+            GnvimEvent::TablineSetLabelFormat(format) => {
+                self.tabline.set_label_format(*format);
+            }
+            GnvimEvent::SetScrollTarget(target) => {
+                self.scroll_target.set(*target);
+            }
+            GnvimEvent::SetForwardedMouseButtons(buttons) => {
+                self.forwarded_buttons.set(*buttons);
+            }
+            GnvimEvent::SetCursorOutline(color, width) => {
+                self.set_cursor_outline(*color, *width);
+            }
+            GnvimEvent::SetGridLines(enable, color) => {
+                self.grid_lines = Some(*color).filter(|_| *enable);
+                let color = self.grid_lines;
+                self.grids.values().for_each(|g| g.set_grid_lines(color));
+            }
+            GnvimEvent::SetWhitespaceGlyphs(space, _tab, nbsp) => {
+                // Tabs are sent by nvim as spaces, so there's nothing to
+                // draw the tab glyph on.
+                self.set_whitespace_glyphs(Some(WhitespaceGlyphs {
+                    space: *space,
+                    nbsp: *nbsp,
+                }));
+            }
+            GnvimEvent::SetScrollIndicator(line, total) => {
+                let position = Some((*line, *total)).filter(|_| *total > 0);
+                if let Some(grid) = self.grids.get(&1) {
+                    grid.set_scroll_indicator(position);
+                }
+            }
+            GnvimEvent::SetTextShadow(color, dx, dy, blur) => {
+                let shadow = TextShadow {
+                    color: *color,
+                    dx: *dx,
+                    dy: *dy,
+                    blur: blur.max(0.0),
+                };
+                let enabled = dx.abs() > 0.0 || dy.abs() > 0.0;
+                self.set_text_shadow(Some(shadow).filter(|_| enabled));
+            }
+            GnvimEvent::SetContextMenu(enable) => {
+                self.context_menu.set_enabled(*enable);
+            }
+            GnvimEvent::ContextMenuSetItems(items) => {
+                self.context_menu.set_items(items.clone());
+            }
+            GnvimEvent::SetDeferColorsRedraw(defer) => {
+                self.defer_colors_redraw = *defer;
+            }
+            GnvimEvent::SetCursorBlinkForShape(shape, on, off, wait) => {
+                self.cursor_blinks.insert(shape.clone(), (*on, *off, *wait));
+                for grid in self.grids.values() {
+                    grid.set_cursor_blinks(self.cursor_blinks.clone());
+                }
+            }
+            GnvimEvent::ResetGnvimSettings => {
+                self.reset_settings(window, nvim);
+            }
+            GnvimEvent::SetAnimationFps(fps) => {
+                self.animation_fps = Some(*fps);
+                for grid in self.grids.values() {
+                    grid.set_animation_fps(self.animation_fps, &self.hl_defs);
+                }
+            }
+            GnvimEvent::FlashRegion(flash) => {
+                if let Some(grid) = self.grids.get(&flash.grid) {
+                    grid.flash(&flash.ranges, flash.color, flash.duration);
+                } else {
+                    error!("Can't flash non-existing grid ({})", flash.grid);
+                }
+            }
             GnvimEvent::EnableExtTabline(enable) => {
                 self.set_ui_option("ext_tabline".into(), *enable, nvim.clone());
             }
@@ -908,14 +2025,27 @@ impl UIState {
                 debug!("Received unknown GnvimEvent: {}", msg);
             }
 
+            // Without webkit, the tooltip is shown as plain text and the
+            // styles aren't available.
+            #[cfg(not(feature = "libwebkit2gtk"))]
+            GnvimEvent::CursorTooltipShow(content, row, col) => {
+                self.cursor_tooltip.show(content);
+
+                let grid = self.grids.get(&self.current_grid).unwrap();
+                let rect = grid.get_rect_for_cell(*row, *col);
+
+                self.cursor_tooltip.move_to(&rect);
+            }
+            #[cfg(not(feature = "libwebkit2gtk"))]
+            GnvimEvent::CursorTooltipHide => self.cursor_tooltip.hide(),
             #[cfg(not(feature = "libwebkit2gtk"))]
             GnvimEvent::CursorTooltipLoadStyle(..)
-            | GnvimEvent::CursorTooltipShow(..)
-            | GnvimEvent::CursorTooltipHide
-            | GnvimEvent::CursorTooltipSetStyle(..) => {
+            | GnvimEvent::CursorTooltipSetStyle(..)
+            | GnvimEvent::CursorTooltipReloadStyle
+            | GnvimEvent::CursorTooltipSetCodeFont(..) => {
                 let nvim = nvim.clone();
-                let msg =
-                    "echom \"Cursor tooltip not supported in this build\"";
+                let msg = "echom \"Cursor tooltip styles not supported in \
+                           this build\"";
                 spawn_local(async move {
                     if let Err(err) = nvim.command(&msg).await {
                         error!("Failed to execute nvim command: {}", err)
@@ -927,7 +2057,9 @@ impl UIState {
             GnvimEvent::CursorTooltipLoadStyle(..)
             | GnvimEvent::CursorTooltipShow(..)
             | GnvimEvent::CursorTooltipHide
-            | GnvimEvent::CursorTooltipSetStyle(..) => match event {
+            | GnvimEvent::CursorTooltipSetStyle(..)
+            | GnvimEvent::CursorTooltipReloadStyle
+            | GnvimEvent::CursorTooltipSetCodeFont(..) => match event {
                 GnvimEvent::CursorTooltipLoadStyle(path) => {
                     if let Err(err) =
                         self.cursor_tooltip.load_style(path.clone())
@@ -959,17 +2091,72 @@ impl UIState {
                 GnvimEvent::CursorTooltipSetStyle(style) => {
                     self.cursor_tooltip.set_style(style)
                 }
+                GnvimEvent::CursorTooltipReloadStyle => {
+                    if let Err(err) = self.cursor_tooltip.reload_style() {
+                        let msg = format!(
+                            "echom \"Cursor tooltip reload failed: '{}'\"",
+                            err
+                        );
+                        let nvim = nvim.clone();
+                        spawn_local(async move {
+                            if let Err(err) = nvim.command(&msg).await {
+                                error!(
+                                    "Failed to execute nvim command: {}",
+                                    err
+                                )
+                            }
+                        });
+                    }
+                }
+                GnvimEvent::CursorTooltipSetCodeFont(guifont) => {
+                    if guifont.is_empty() {
+                        self.cursor_tooltip.set_code_font(None);
+                    } else if let Ok(font) = Font::from_guifont(guifont) {
+                        self.cursor_tooltip.set_code_font(Some(font));
+                    } else {
+                        error!("Invalid font for cursor tooltip: {}", guifont);
+                    }
+                }
                 _ => unreachable!(),
             },
         }
     }
 }
 
-pub fn attach_grid_events(grid: &Grid, nvim: GioNeovim) {
+/// Connects the grid's mouse events to nvim. The events are only sent to nvim
+/// when `mouse_enabled` is set. When `natural_scroll` is set, the scroll
+/// direction is reversed. With `ScrollTarget::Active`, scrolls are sent to
+/// `active_cursor` instead of the cell under the pointer. Only the buttons in
+/// `forwarded_buttons` are sent to nvim. Right clicks open `context_menu`
+/// instead, when it is enabled.
+#[allow(clippy::too_many_arguments)]
+pub fn attach_grid_events(
+    grid: &Grid,
+    nvim: GioNeovim,
+    mouse_enabled: Rc<Cell<bool>>,
+    natural_scroll: Rc<Cell<bool>>,
+    scroll_target: Rc<Cell<ScrollTarget>>,
+    active_cursor: Rc<Cell<(i64, u64, u64)>>,
+    forwarded_buttons: Rc<Cell<MouseButtons>>,
+    context_menu: Rc<ContextMenu>,
+) {
     let id = grid.id;
     // Mouse button press event.
     grid.connect_mouse_button_press_events(
-        clone!(nvim => move |button, row, col| {
+        clone!(nvim, mouse_enabled, forwarded_buttons, context_menu => move |button, row, col| {
+            if let MouseButton::Right = button {
+                if context_menu.is_enabled() {
+                    context_menu.popup();
+                    return Inhibit(true);
+                }
+            }
+
+            if !mouse_enabled.get()
+                || !is_forwarded(forwarded_buttons.get(), &context_menu, &button)
+            {
+                return Inhibit(false);
+            }
+
             let nvim = nvim.clone();
             spawn_local(async move {
                 nvim.input_mouse(&button.to_string(), "press", "", id, row as i64, col as i64).await.expect("Couldn't send mouse input");
@@ -981,7 +2168,13 @@ pub fn attach_grid_events(grid: &Grid, nvim: GioNeovim) {
 
     // Mouse button release events.
     grid.connect_mouse_button_release_events(
-        clone!(nvim => move |button, row, col| {
+        clone!(nvim, mouse_enabled, forwarded_buttons, context_menu => move |button, row, col| {
+            if !mouse_enabled.get()
+                || !is_forwarded(forwarded_buttons.get(), &context_menu, &button)
+            {
+                return Inhibit(false);
+            }
+
             let nvim = nvim.clone();
             spawn_local(async move {
                 nvim.input_mouse(&button.to_string(), "release", "", id, row as i64, col as i64).await.expect("Couldn't send mouse input");
@@ -993,7 +2186,13 @@ pub fn attach_grid_events(grid: &Grid, nvim: GioNeovim) {
 
     // Mouse drag events.
     grid.connect_motion_events_for_drag(
-        clone!(nvim => move |button, row, col| {
+        clone!(nvim, mouse_enabled, forwarded_buttons, context_menu => move |button, row, col| {
+            if !mouse_enabled.get()
+                || !is_forwarded(forwarded_buttons.get(), &context_menu, &button)
+            {
+                return Inhibit(false);
+            }
+
             let nvim = nvim.clone();
             spawn_local(async move {
                 nvim.input_mouse(&button.to_string(), "drag", "", id, row as i64, col as i64).await.expect("Couldn't send mouse input");
@@ -1005,6 +2204,16 @@ pub fn attach_grid_events(grid: &Grid, nvim: GioNeovim) {
 
     // Scrolling events.
     grid.connect_scroll_events(clone!(nvim => move |dir, row, col| {
+        if !mouse_enabled.get() {
+            return Inhibit(false);
+        }
+
+        let dir = if natural_scroll.get() { dir.reversed() } else { dir };
+        let (id, row, col) = match scroll_target.get() {
+            ScrollTarget::Pointer => (id, row, col),
+            ScrollTarget::Active => active_cursor.get(),
+        };
+
         let nvim = nvim.clone();
         spawn_local(async move {
             nvim.input_mouse("wheel", &dir.to_string(), "", id, row as i64, col as i64).await.expect("Couldn't send mouse input");
@@ -1014,6 +2223,21 @@ pub fn attach_grid_events(grid: &Grid, nvim: GioNeovim) {
     }));
 }
 
+/// Returns true if the input from `button` is sent to nvim. The right button
+/// is kept for `context_menu` when it is enabled.
+fn is_forwarded(
+    buttons: MouseButtons,
+    context_menu: &ContextMenu,
+    button: &MouseButton,
+) -> bool {
+    match button {
+        MouseButton::Left => buttons.left,
+        MouseButton::Middle => buttons.middle,
+        MouseButton::Right => buttons.right && !context_menu.is_enabled(),
+        MouseButton::X1 | MouseButton::X2 => buttons.extra,
+    }
+}
+
 fn widget_show(widget: &gtk::Widget, show: bool) {
     if show {
         widget.show();
@@ -1,10 +1,13 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::path::Path;
 use std::rc::Rc;
 
 use gtk::prelude::*;
 
-use nvim_rs::Tabpage;
+use log::error;
+use nvim_rs::{Buffer, Tabpage};
 
+use crate::nvim_bridge::{TablineLabelFormat, TablineMode};
 use crate::nvim_gio::{GioNeovim, GioWriter};
 use crate::ui::color::{Color, HlDefs, HlGroup};
 use crate::ui::common::{calc_line_space, spawn_local};
@@ -20,17 +23,36 @@ pub struct TablineColors {
     pub sel_fg: Option<Color>,
 }
 
+/// Contents of the latest tabline update from nvim.
+struct TablineData {
+    current_tab: Tabpage<GioWriter>,
+    tabs: Vec<(Tabpage<GioWriter>, String)>,
+    current_buffer: Option<Buffer<GioWriter>>,
+    buffers: Vec<(Buffer<GioWriter>, String)>,
+}
+
 pub struct Tabline {
     notebook: gtk::Notebook,
     css_provider: gtk::CssProvider,
     switch_tab_signal: glib::SignalHandlerId,
 
+    nvim: GioNeovim,
+
     tabpage_data: Rc<RefCell<Vec<Tabpage<GioWriter>>>>,
+    buffer_data: Rc<RefCell<Vec<Buffer<GioWriter>>>>,
+    /// If we're showing tabs or buffers.
+    mode: Rc<Cell<TablineMode>>,
+    /// Latest update, so we can redo the tabs when the mode changes.
+    data: Option<TablineData>,
+    /// How the labels are formatted.
+    label_format: TablineLabelFormat,
 
     /// Our colors.
     colors: TablineColors,
     /// Our font.
     font: Font,
+    /// Font set for just the tabline. Overrides `font`.
+    own_font: Option<Font>,
 
     line_space: i64,
 }
@@ -44,18 +66,27 @@ impl Tabline {
         add_css_provider!(&css_provider, notebook);
 
         let tabpage_data = Rc::new(RefCell::new(vec![]));
+        let buffer_data = Rc::new(RefCell::new(vec![]));
+        let mode = Rc::new(Cell::new(TablineMode::Tabs));
         let switch_tab_signal = notebook.connect_switch_page(
-            clone!(tabpage_data, nvim => move |_, _, page_num| {
+            clone!(tabpage_data, buffer_data, mode, nvim => move |_, _, page_num| {
+                if mode.get() == TablineMode::Buffers {
+                    switch_buffer(&nvim, &buffer_data, page_num as usize);
+                    return;
+                }
+
                 let tabpage_data = tabpage_data.clone();
                 let nvim = nvim.clone();
                 spawn_local(async move {
                     let pages = tabpage_data.borrow();
                     if let Some(ref page) = pages.get(page_num as usize) {
-                        nvim.set_current_tabpage(&page)
-                            .await
-                            .unwrap();
+                        if let Err(err) =
+                            nvim.set_current_tabpage(&page).await
+                        {
+                            error!("Failed to switch tab page: {:?}", err);
+                        }
                     } else {
-                        println!("Failed to get tab page {}", page_num);
+                        error!("Failed to get tab page {}", page_num);
                     }
                 });
             }),
@@ -65,9 +96,15 @@ impl Tabline {
             notebook,
             css_provider,
             switch_tab_signal,
+            nvim,
             tabpage_data,
+            buffer_data,
+            mode,
+            data: None,
+            label_format: TablineLabelFormat::Full,
             colors: TablineColors::default(),
             font: Font::default(),
+            own_font: None,
             line_space: 0,
         }
     }
@@ -76,47 +113,143 @@ impl Tabline {
         self.notebook.clone().upcast()
     }
 
+    /// Sets which side of the notebook the tabs are drawn on.
+    pub fn set_tab_pos(&self, pos: gtk::PositionType) {
+        self.notebook.set_tab_pos(pos);
+    }
+
     pub fn update(
-        &self,
-        current: Tabpage<GioWriter>,
+        &mut self,
+        current_tab: Tabpage<GioWriter>,
         tabs: Vec<(Tabpage<GioWriter>, String)>,
+        current_buffer: Option<Buffer<GioWriter>>,
+        buffers: Vec<(Buffer<GioWriter>, String)>,
     ) {
+        self.tabpage_data
+            .replace(tabs.iter().map(|t| t.0.clone()).collect());
+        self.buffer_data
+            .replace(buffers.iter().map(|b| b.0.clone()).collect());
+
+        self.data = Some(TablineData {
+            current_tab,
+            tabs,
+            current_buffer,
+            buffers,
+        });
+
+        self.render();
+    }
+
+    /// Sets if the tabline shows the tabpages or the buffers.
+    pub fn set_mode(&mut self, mode: TablineMode) {
+        self.mode.set(mode);
+        self.render();
+    }
+
+    /// Sets how the labels are formatted.
+    pub fn set_label_format(&mut self, format: TablineLabelFormat) {
+        self.label_format = format;
+        self.render();
+    }
+
+    /// Recreates the tabs from the latest update.
+    fn render(&self) {
         glib::signal_handler_block(&self.notebook, &self.switch_tab_signal);
         for child in self.notebook.get_children() {
             self.notebook.remove(&child);
         }
         glib::signal_handler_unblock(&self.notebook, &self.switch_tab_signal);
 
-        if tabs.len() < 2 {
+        let data = match self.data {
+            Some(ref data) => data,
+            None => return,
+        };
+
+        // Names of the entries, the index of the current one, and a
+        // function to close an entry (by its index).
+        let nvim = self.nvim.clone();
+        let (names, current, close): (Vec<&str>, _, Rc<dyn Fn(usize)>) =
+            match self.mode.get() {
+                TablineMode::Tabs => (
+                    data.tabs.iter().map(|t| t.1.as_str()).collect(),
+                    data.tabs.iter().position(|t| {
+                        t.0.get_value() == data.current_tab.get_value()
+                    }),
+                    // Tab numbers start from one.
+                    Rc::new(move |i: usize| close_tab(&nvim, i + 1)),
+                ),
+                TablineMode::Buffers => {
+                    let buffers = self.buffer_data.clone();
+                    (
+                        data.buffers.iter().map(|b| b.1.as_str()).collect(),
+                        data.buffers.iter().position(|b| {
+                            data.current_buffer.as_ref().map(|c| c.get_value())
+                                == Some(b.0.get_value())
+                        }),
+                        Rc::new(move |i: usize| {
+                            close_buffer(&nvim, &buffers, i)
+                        }),
+                    )
+                }
+            };
+
+        if names.len() < 2 {
             self.notebook.hide();
             return;
         }
 
         glib::signal_handler_block(&self.notebook, &self.switch_tab_signal);
 
-        let mut page = 0;
-        for (i, tab) in tabs.iter().enumerate() {
-            let tab_label = gtk::Label::new(Some(tab.1.as_str()));
+        let home = glib::get_home_dir();
+        for (i, name) in names.into_iter().enumerate() {
+            let label = format_label(name, self.label_format, home.as_deref());
+            let tab_label = gtk::Label::new(Some(&label));
             tab_label.set_hexpand(true);
             tab_label.set_ellipsize(pango::EllipsizeMode::End);
             add_css_provider!(&self.css_provider, tab_label);
 
+            let close_button = gtk::Button::from_icon_name(
+                Some("window-close-symbolic"),
+                gtk::IconSize::Menu,
+            );
+            close_button.set_relief(gtk::ReliefStyle::None);
+            close_button.set_focus_on_click(false);
+            add_css_provider!(&self.css_provider, close_button);
+
+            close_button.connect_clicked(clone!(close => move |_| {
+                close(i);
+            }));
+
+            let tab_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+            tab_box.pack_start(&tab_label, true, true, 0);
+            tab_box.pack_start(&close_button, false, false, 0);
+
+            // Event box for middle click to close the tab. Other clicks
+            // are passed on to the notebook, so it can switch the tab.
+            let tab_widget = gtk::EventBox::new();
+            tab_widget.add(&tab_box);
+            tab_widget.connect_button_press_event(
+                clone!(close => move |_, e| {
+                    if e.get_button() == 2 {
+                        close(i);
+                        Inhibit(true)
+                    } else {
+                        Inhibit(false)
+                    }
+                }),
+            );
+            tab_widget.show_all();
+
             self.notebook.append_page(
                 &gtk::Box::new(gtk::Orientation::Vertical, 0),
-                Some(&tab_label),
+                Some(&tab_widget),
             );
-
-            if tab.0.get_value() == current.get_value() {
-                page = i;
-            }
         }
 
         self.notebook.show_all();
 
-        self.notebook.set_current_page(Some(page as u32));
-
-        self.tabpage_data
-            .replace(tabs.into_iter().map(|t| t.0).collect());
+        self.notebook
+            .set_current_page(Some(current.unwrap_or(0) as u32));
 
         glib::signal_handler_unblock(&self.notebook, &self.switch_tab_signal);
     }
@@ -126,6 +259,17 @@ impl Tabline {
         self.set_styles(hl_defs);
     }
 
+    /// Sets a font for just the tabline, so it won't follow the grid font.
+    /// `None` makes the tabline use the grid font again.
+    pub fn set_own_font(&mut self, font: Option<Font>, hl_defs: &HlDefs) {
+        self.own_font = font;
+        self.set_styles(hl_defs);
+    }
+
+    fn get_font(&self) -> &Font {
+        self.own_font.as_ref().unwrap_or(&self.font)
+    }
+
     pub fn set_line_space(&mut self, space: i64, hl_defs: &HlDefs) {
         self.line_space = space;
         self.set_styles(hl_defs);
@@ -209,8 +353,21 @@ impl Tabline {
             tab:hover {{
                 box-shadow: inset 73px 0px 0px -70px #{selected_fg};
             }}
+            button {{
+                color: #{normal_fg};
+                background: transparent;
+                border: none;
+                box-shadow: none;
+                padding: 0px;
+                margin-left: 5px;
+                min-width: 0px;
+                min-height: 0px;
+            }}
+            tab:checked button {{
+                color: #{selected_fg};
+            }}
             ",
-            font_wild = self.font.as_wild_css(FontUnit::Point),
+            font_wild = self.get_font().as_wild_css(FontUnit::Point),
             normal_fg = self.colors.fg.unwrap_or(hl_defs.default_fg).to_hex(),
             normal_bg = self.colors.bg.unwrap_or(hl_defs.default_bg).to_hex(),
             selected_fg =
@@ -265,8 +422,19 @@ impl Tabline {
             tab:hover {{
                 box-shadow: inset 73px 0px 0px -70px #{selected_fg};
             }}
+            GtkButton {{
+                color: #{normal_fg};
+                background: transparent;
+                border: none;
+                box-shadow: none;
+                padding: 0px;
+                margin-left: 5px;
+            }}
+            tab:active GtkButton {{
+                color: #{selected_fg};
+            }}
             ",
-            font_wild = self.font.as_wild_css(FontUnit::Pixel),
+            font_wild = self.get_font().as_wild_css(FontUnit::Pixel),
             normal_fg = self.colors.fg.unwrap_or(hl_defs.default_fg).to_hex(),
             normal_bg = self.colors.bg.unwrap_or(hl_defs.default_bg).to_hex(),
             selected_fg =
@@ -281,3 +449,141 @@ impl Tabline {
             .unwrap();
     }
 }
+
+/// Formats `name` (usually a path) for a tab's label. `home` is replaced
+/// with `~` when shortening.
+fn format_label(
+    name: &str,
+    format: TablineLabelFormat,
+    home: Option<&Path>,
+) -> String {
+    match format {
+        TablineLabelFormat::Full => name.to_string(),
+        TablineLabelFormat::FileName => Path::new(name)
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| name.to_string()),
+        TablineLabelFormat::Shortened => {
+            let relative =
+                home.and_then(|h| Path::new(name).strip_prefix(h).ok());
+            let path = match relative {
+                Some(rest) => format!("~/{}", rest.to_string_lossy()),
+                None => name.to_string(),
+            };
+
+            let mut parts = path.split('/').collect::<Vec<&str>>();
+            let file = parts.pop().unwrap_or("");
+            let dirs = parts
+                .into_iter()
+                .map(|dir| {
+                    // Keep leading dot of hidden directories.
+                    let len = if dir.starts_with('.') { 2 } else { 1 };
+                    dir.chars().take(len).collect::<String>()
+                })
+                .collect::<Vec<String>>();
+
+            if dirs.is_empty() {
+                file.to_string()
+            } else {
+                format!("{}/{}", dirs.join("/"), file)
+            }
+        }
+    }
+}
+
+/// Closes tab number `nr` (starting from one).
+fn close_tab(nvim: &GioNeovim, nr: usize) {
+    let nvim = nvim.clone();
+    spawn_local(async move {
+        let cmd = format!("tabclose {}", nr);
+        if let Err(err) = nvim.command(&cmd).await {
+            error!("Failed to close tab {}: {:?}", nr, err);
+        }
+    });
+}
+
+/// Switches to buffer number `i` (starting from zero) in `buffers`.
+fn switch_buffer(
+    nvim: &GioNeovim,
+    buffers: &Rc<RefCell<Vec<Buffer<GioWriter>>>>,
+    i: usize,
+) {
+    let buffer = match buffers.borrow().get(i) {
+        Some(buffer) => buffer.clone(),
+        None => {
+            error!("Failed to get buffer {}", i);
+            return;
+        }
+    };
+
+    let nvim = nvim.clone();
+    spawn_local(async move {
+        if let Err(err) = nvim.set_current_buf(&buffer).await {
+            error!("Failed to switch buffer: {:?}", err);
+        }
+    });
+}
+
+/// Deletes buffer number `i` (starting from zero) in `buffers`.
+fn close_buffer(
+    nvim: &GioNeovim,
+    buffers: &Rc<RefCell<Vec<Buffer<GioWriter>>>>,
+    i: usize,
+) {
+    let buffer = match buffers.borrow().get(i) {
+        Some(buffer) => buffer.clone(),
+        None => {
+            error!("Failed to get buffer {}", i);
+            return;
+        }
+    };
+
+    let nvim = nvim.clone();
+    spawn_local(async move {
+        let res = match buffer.get_number().await {
+            Ok(nr) => nvim.command(&format!("bdelete {}", nr)).await,
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = res {
+            error!("Failed to close buffer: {:?}", err);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_label() {
+        let home = Some(Path::new("/home/user"));
+        let path = "/home/user/projects/.config/src/file.rs";
+
+        assert_eq!(format_label(path, TablineLabelFormat::Full, home), path);
+        assert_eq!(
+            format_label(path, TablineLabelFormat::FileName, home),
+            "file.rs"
+        );
+        assert_eq!(
+            format_label(path, TablineLabelFormat::Shortened, home),
+            "~/p/.c/s/file.rs"
+        );
+        assert_eq!(
+            format_label(
+                "/etc/nvim/init.vim",
+                TablineLabelFormat::Shortened,
+                home
+            ),
+            "/e/n/init.vim"
+        );
+        assert_eq!(
+            format_label("file.rs", TablineLabelFormat::Shortened, None),
+            "file.rs"
+        );
+        assert_eq!(
+            format_label("[No Name]", TablineLabelFormat::FileName, None),
+            "[No Name]"
+        );
+    }
+}
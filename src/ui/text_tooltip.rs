@@ -0,0 +1,64 @@
+use gtk::prelude::*;
+
+use crate::ui::font::{Font, FontUnit};
+
+/// Plain text version of the cursor tooltip, for builds without webkit. The
+/// content is shown as is (i.e. markdown isn't rendered) in a popover.
+pub struct TextTooltip {
+    popover: gtk::Popover,
+    label: gtk::Label,
+    css_provider: gtk::CssProvider,
+}
+
+impl TextTooltip {
+    pub fn new(parent: &gtk::Overlay) -> Self {
+        let label = gtk::Label::new(None);
+        label.set_line_wrap(true);
+        label.set_max_width_chars(80);
+        label.set_xalign(0.0);
+        label.set_margin_start(4);
+        label.set_margin_end(4);
+
+        let css_provider = gtk::CssProvider::new();
+        add_css_provider!(&css_provider, label);
+
+        let popover = gtk::Popover::new(Some(parent));
+        // Don't take the keyboard from nvim.
+        popover.set_modal(false);
+        popover.set_position(gtk::PositionType::Top);
+        popover.add(&label);
+        label.show();
+
+        TextTooltip {
+            popover,
+            label,
+            css_provider,
+        }
+    }
+
+    pub fn set_font(&self, font: Font) {
+        let unit = if gtk::get_minor_version() < 20 {
+            FontUnit::Pixel
+        } else {
+            FontUnit::Point
+        };
+
+        let css = font.as_wild_css(unit);
+        CssProviderExt::load_from_data(&self.css_provider, css.as_bytes())
+            .unwrap();
+    }
+
+    pub fn hide(&self) {
+        self.popover.hide();
+    }
+
+    pub fn show(&self, content: &str) {
+        self.label.set_text(content.trim());
+        self.popover.show();
+    }
+
+    /// Points the tooltip to `rect`.
+    pub fn move_to(&self, rect: &gdk::Rectangle) {
+        self.popover.set_pointing_to(rect);
+    }
+}
@@ -1,4 +1,4 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
 
@@ -7,18 +7,28 @@ use gtk::prelude::*;
 use log::{debug, error};
 use rmpv::Value;
 
-use crate::nvim_bridge::{Message, Request};
+use crate::nvim_bridge::{
+    Message, MouseButtons, Request, ScrollTarget, UnfocusedCursor,
+};
 use crate::nvim_gio::GioNeovim;
 use crate::ui::cmdline::Cmdline;
 use crate::ui::color::{Highlight, HlDefs};
-use crate::ui::common::spawn_local;
+use crate::ui::common::{spawn_local, vim_string};
+use crate::ui::context_menu::ContextMenu;
 #[cfg(feature = "libwebkit2gtk")]
 use crate::ui::cursor_tooltip::CursorTooltip;
 use crate::ui::font::Font;
-use crate::ui::grid::Grid;
+use crate::ui::geometry;
+use crate::ui::grid::{Grid, DEFAULT_UNDERLINE_THICKNESS_SCALE};
+use crate::ui::mode_indicator::ModeIndicator;
+use crate::ui::pending_keys::PendingKeys;
 use crate::ui::popupmenu::Popupmenu;
-use crate::ui::state::{attach_grid_events, UIState, Windows};
+use crate::ui::state::{
+    attach_grid_events, UIState, Windows, DEFAULT_RESIZE_DEBOUNCE,
+};
 use crate::ui::tabline::Tabline;
+#[cfg(not(feature = "libwebkit2gtk"))]
+use crate::ui::text_tooltip::TextTooltip;
 use crate::ui::window::MsgWindow;
 
 /// Main UI structure.
@@ -52,6 +62,13 @@ impl UI {
         window.set_title("Neovim");
         window.set_default_size(window_size.0, window_size.1);
 
+        // Restore the window geometry from the last session, if the user has
+        // opted in to remembering it.
+        let saved_geometry = geometry::load();
+        if let Some(ref geometry) = saved_geometry {
+            geometry.apply(&window);
+        }
+
         // Realize window resources.
         window.realize();
 
@@ -108,6 +125,10 @@ impl UI {
         overlay.set_overlay_pass_through(&windows_float_container, true);
         overlay.set_overlay_pass_through(&msg_window_container, true);
 
+        let mode_indicator = ModeIndicator::new(&overlay);
+        let pending_keys = PendingKeys::new(&overlay);
+        let context_menu = Rc::new(ContextMenu::new(nvim.clone()));
+
         // When resizing our window (main grid), we'll have to tell neovim to
         // resize it self also. The notify to nvim is send with a small delay,
         // so we don't spam it multiple times a second. source_id is used to
@@ -115,16 +136,23 @@ impl UI {
         // redraw even handler if we receive a message that changes the size
         // of the main grid.
         let source_id = Rc::new(RefCell::new(None));
-        grid.connect_da_resize(clone!(nvim, source_id => move |rows, cols| {
+        // Delay (in ms) for the resize notify. Can be changed from nvim.
+        let resize_debounce = Rc::new(Cell::new(DEFAULT_RESIZE_DEBOUNCE));
+        grid.connect_da_resize(clone!(nvim, source_id, resize_debounce => move |rows, cols| {
+            // If we have earlier timeout, remove it.
+            if let Some(old) = source_id.borrow_mut().take() {
+                glib::source::source_remove(old);
+            }
+
+            let debounce = resize_debounce.get();
+            if debounce == 0 {
+                try_resize(nvim.clone(), cols, rows);
+                return false;
+            }
 
             // Set timeout to notify nvim about the new size.
-            let new = gtk::timeout_add(30, clone!(nvim, source_id => move || {
-                let nvim = nvim.clone();
-                spawn_local(async move {
-                    if let Err(err) = nvim.ui_try_resize(cols as i64, rows as i64).await {
-                        error!("Error: failed to resize nvim when grid size changed ({:?})", err);
-                    }
-                });
+            let new = gtk::timeout_add(debounce, clone!(nvim, source_id => move || {
+                try_resize(nvim.clone(), cols, rows);
 
                 // Set the source_id to none, so we don't accidentally remove
                 // it since it used at this point.
@@ -133,34 +161,51 @@ impl UI {
                 Continue(false)
             }));
 
-            let mut source_id = source_id.borrow_mut();
-            // If we have earlier timeout, remove it.
-            if let Some(old) = source_id.take() {
-                glib::source::source_remove(old);
-            }
-
-            *source_id = Some(new);
+            *source_id.borrow_mut() = Some(new);
 
             false
         }));
 
-        attach_grid_events(&grid, nvim.clone());
+        let mouse_enabled = Rc::new(Cell::new(true));
+        let natural_scroll = Rc::new(Cell::new(false));
+        let scroll_target = Rc::new(Cell::new(ScrollTarget::Pointer));
+        let active_cursor = Rc::new(Cell::new((1, 0, 0)));
+        let forwarded_buttons = Rc::new(Cell::new(MouseButtons::default()));
+        attach_grid_events(
+            &grid,
+            nvim.clone(),
+            mouse_enabled.clone(),
+            natural_scroll.clone(),
+            scroll_target.clone(),
+            active_cursor.clone(),
+            forwarded_buttons.clone(),
+            context_menu.clone(),
+        );
 
         // IMMulticontext is used to handle most of the inputs.
         let im_context = gtk::IMMulticontext::new();
         im_context.set_use_preedit(false);
         im_context.connect_commit(clone!(nvim => move |_, input| {
-            // "<" needs to be escaped for nvim.input()
-            let nvim_input = input.replace("<", "<lt>");
+            send_input(&nvim, input);
+        }));
 
-            let nvim = nvim.clone();
-            spawn_local(async move {
-                nvim.input(&nvim_input).await.expect("Couldn't send input");
-            });
+        // When the input method is disabled (see `UIState::set_input_method`),
+        // the simple context is used instead. It still handles dead keys,
+        // but doesn't do any composition.
+        let input_method = Rc::new(Cell::new(true));
+        let simple_context = gtk::IMContextSimple::new();
+        simple_context.connect_commit(clone!(nvim => move |_, input| {
+            send_input(&nvim, input);
         }));
 
-        window.connect_key_press_event(clone!(nvim, im_context => move |_, e| {
-            if im_context.filter_keypress(e) {
+        window.connect_key_press_event(clone!(nvim, im_context, simple_context, input_method => move |_, e| {
+            let filtered = if input_method.get() {
+                im_context.filter_keypress(e)
+            } else {
+                simple_context.filter_keypress(e)
+            };
+
+            if filtered {
                 Inhibit(true)
             } else {
                 if let Some(input) = event_to_nvim_input(e) {
@@ -180,31 +225,77 @@ impl UI {
             }
         }));
 
-        window.connect_key_release_event(clone!(im_context => move |_, e| {
-            im_context.filter_keypress(e);
-            Inhibit(false)
-        }));
+        window.connect_key_release_event(
+            clone!(im_context, simple_context, input_method => move |_, e| {
+                if input_method.get() {
+                    im_context.filter_keypress(e);
+                } else {
+                    simple_context.filter_keypress(e);
+                }
+                Inhibit(false)
+            }),
+        );
 
-        window.connect_focus_in_event(clone!(im_context => move |_, _| {
-            im_context.focus_in();
-            Inhibit(false)
-        }));
+        window.connect_focus_in_event(
+            clone!(im_context, input_method => move |_, _| {
+                if input_method.get() {
+                    im_context.focus_in();
+                }
+                Inhibit(false)
+            }),
+        );
 
         window.connect_focus_out_event(clone!(im_context => move |_, _| {
             im_context.focus_out();
             Inhibit(false)
         }));
 
+        // Open the files that are dropped into the window.
+        let targets = vec![gtk::TargetEntry::new(
+            "text/uri-list",
+            gtk::TargetFlags::OTHER_APP,
+            0,
+        )];
+        window.drag_dest_set(
+            gtk::DestDefaults::ALL,
+            &targets,
+            gdk::DragAction::COPY,
+        );
+        window.connect_drag_data_received(
+            clone!(nvim => move |_, _, _, _, data, _, _| {
+                let paths = data
+                    .get_uris()
+                    .iter()
+                    .filter_map(|uri| glib::filename_from_uri(uri).ok())
+                    .map(|(path, _)| path.to_string_lossy().into_owned())
+                    .collect::<Vec<String>>();
+
+                if paths.is_empty() {
+                    return;
+                }
+
+                let nvim = nvim.clone();
+                spawn_local(async move {
+                    if let Err(err) =
+                        nvim.command(&file_drop_command(&paths)).await
+                    {
+                        error!("Failed to open dropped files: {:?}", err);
+                    }
+                });
+            }),
+        );
+
         let cmdline = Cmdline::new(&overlay, nvim.clone());
         #[cfg(feature = "libwebkit2gtk")]
         let cursor_tooltip = CursorTooltip::new(&overlay);
+        #[cfg(not(feature = "libwebkit2gtk"))]
+        let cursor_tooltip = TextTooltip::new(&overlay);
 
         window.show_all();
 
         grid.set_im_context(&im_context);
 
         cmdline.hide();
-        #[cfg(feature = "libwebkit2gtk")]
         cursor_tooltip.hide();
 
         let mut grids = HashMap::new();
@@ -217,6 +308,7 @@ impl UI {
             rx,
             state: Rc::new(RefCell::new(UIState {
                 css_provider,
+                main_box: b,
                 windows: Windows::new(),
                 windows_container,
                 msg_window_container,
@@ -226,20 +318,63 @@ impl UI {
                 mode_infos: vec![],
                 current_grid: 1,
                 wildmenu_shown: false,
+                popupmenu_anchor: None,
                 popupmenu: Popupmenu::new(&overlay, nvim.clone()),
                 cmdline,
                 overlay,
                 tabline,
-                #[cfg(feature = "libwebkit2gtk")]
+                mode_indicator,
+                pending_keys,
+                context_menu,
                 cursor_tooltip,
                 resize_source_id: source_id,
+                resize_debounce,
                 hl_defs,
                 resize_on_flush: None,
                 hl_changed: false,
+                defer_colors_redraw: true,
+                redraw_on_flush: false,
                 font,
+                grid_fonts: HashMap::new(),
                 line_space,
                 current_mode: None,
                 enable_cursor_animations: true,
+                inactive_dim: 0.0,
+                invert_colors: false,
+                focused: true,
+                unfocused_cursor: UnfocusedCursor::Hollow,
+                underline_thickness_scale: DEFAULT_UNDERLINE_THICKNESS_SCALE,
+                underline_offset: 0.0,
+                cursor_visible: true,
+                remember_geometry: saved_geometry.is_some(),
+                min_size: None,
+                letter_spacing: 0.0,
+                cell_width_scale: 1.0,
+                active_border: None,
+                cursor_outline: None,
+                grid_lines: None,
+                letterbox_color: None,
+                cursor_over_text: false,
+                scroll_cache: false,
+                mouse_on: true,
+                respect_mouse_option: false,
+                mouse_enabled,
+                natural_scroll,
+                scroll_target,
+                active_cursor,
+                pending_cursor: None,
+                forwarded_buttons,
+                gui_selection: false,
+                busy_spinner: false,
+                clipboard_sync: false,
+                zoom_animation: false,
+                typewriter_mode: false,
+                dim_lines: None,
+                paused: false,
+                animation_fps: None,
+                cursor_blinks: HashMap::new(),
+                im_context,
+                input_method,
             })),
             nvim,
         }
@@ -255,6 +390,38 @@ impl UI {
             nvim,
         } = self;
 
+        // Let the grids know about the focus, so they can draw their cursor
+        // accordingly.
+        win.connect_focus_in_event(clone!(state => move |_, _| {
+            state.borrow_mut().set_focused(true);
+            Inhibit(false)
+        }));
+
+        win.connect_focus_out_event(clone!(state => move |_, _| {
+            state.borrow_mut().set_focused(false);
+            Inhibit(false)
+        }));
+
+        // Don't run the animations while the window isn't visible.
+        win.connect_window_state_event(clone!(state => move |_, e| {
+            let hidden = e.get_new_window_state().intersects(
+                gdk::WindowState::ICONIFIED | gdk::WindowState::WITHDRAWN,
+            );
+            state.borrow_mut().set_paused(hidden);
+            Inhibit(false)
+        }));
+
+        // Recreate the grids' surfaces when moved to a monitor with a
+        // different scale factor, so the content isn't blurry.
+        win.connect_property_scale_factor_notify(clone!(state => move |win| {
+            state.borrow_mut().update_scale_factor(win);
+        }));
+
+        win.connect_delete_event(clone!(state => move |win, _| {
+            state.borrow().save_geometry(win);
+            Inhibit(false)
+        }));
+
         rx.attach(None, move |message| {
             match message {
                 // Handle a notify.
@@ -281,6 +448,54 @@ impl UI {
     }
 }
 
+/// Sends text `input` (e.g. from an input method) to nvim.
+fn send_input(nvim: &GioNeovim, input: &str) {
+    // "<" needs to be escaped for nvim.input()
+    let nvim_input = input.replace('<', "<lt>");
+
+    let nvim = nvim.clone();
+    spawn_local(async move {
+        nvim.input(&nvim_input).await.expect("Couldn't send input");
+    });
+}
+
+/// Tells nvim that the size of the main grid has changed.
+fn try_resize(nvim: GioNeovim, cols: u64, rows: u64) {
+    spawn_local(async move {
+        if let Err(err) = nvim.ui_try_resize(cols as i64, rows as i64).await {
+            error!(
+                "Error: failed to resize nvim when grid size changed ({:?})",
+                err
+            );
+        }
+    });
+}
+
+/// Returns a command that opens `paths` in nvim. The paths are stored in
+/// `g:gnvim_dropped_files`, and if there is a `User GnvimFileDrop` autocmd,
+/// it is executed instead.
+fn file_drop_command(paths: &[String]) -> String {
+    let files = paths
+        .iter()
+        .map(String::as_str)
+        .map(vim_string)
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    format!(
+        "let g:gnvim_dropped_files = [{}] \
+        | if exists('#User#GnvimFileDrop') \
+        | doautocmd User GnvimFileDrop \
+        | elseif len(g:gnvim_dropped_files) == 1 \
+        | execute 'edit ' . fnameescape(g:gnvim_dropped_files[0]) \
+        | else \
+        | execute 'args ' . join(map(copy(g:gnvim_dropped_files), \
+        'fnameescape(v:val)')) \
+        | endif",
+        files
+    )
+}
+
 #[cfg_attr(not(feature = "libwebkit2gtk"), allow(unused_variables))] // Silence clippy
 fn handle_request(
     request: &Request,
@@ -300,6 +515,56 @@ fn handle_request(
         Request::CursorTooltipStyles => {
             Err("Cursor tooltip is not supported in this build".into())
         }
+        Request::GetFont => {
+            let grid = state
+                .grids
+                .get(&1)
+                .ok_or_else(|| String::from("Grid 1 doesn't exist"))?;
+
+            Ok(Value::Map(vec![
+                ("guifont".into(), grid.get_font().as_guifont().into()),
+                ("line_space".into(), grid.get_line_space().into()),
+            ]))
+        }
+        Request::GetCellRect(grid, row, col) => {
+            let rect = state
+                .grids
+                .get(grid)
+                .ok_or_else(|| format!("Grid {} doesn't exist", grid))?
+                .get_rect_for_cell(*row, *col);
+
+            Ok(Value::Map(vec![
+                ("x".into(), rect.x.into()),
+                ("y".into(), rect.y.into()),
+                ("width".into(), rect.width.into()),
+                ("height".into(), rect.height.into()),
+            ]))
+        }
+        Request::GetGridText(grid, with_hl) => {
+            let rows = state
+                .grids
+                .get(grid)
+                .ok_or_else(|| format!("Grid {} doesn't exist", grid))?
+                .get_text();
+
+            let lines: Vec<Value> = rows
+                .into_iter()
+                .map(|(text, hl_ids)| {
+                    if *with_hl {
+                        let hl_ids: Vec<Value> =
+                            hl_ids.into_iter().map(Value::from).collect();
+                        Value::Map(vec![
+                            ("text".into(), text.into()),
+                            ("hl_ids".into(), hl_ids.into()),
+                        ])
+                    } else {
+                        text.into()
+                    }
+                })
+                .collect();
+
+            Ok(lines.into())
+        }
     }
 }
 
@@ -390,3 +655,21 @@ fn event_to_nvim_input(e: &gdk::EventKey) -> Option<String> {
 
     Some(format!("<{}>", input))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_drop_command() {
+        let cmd = file_drop_command(&[
+            String::from("/tmp/foo bar.txt"),
+            String::from("/tmp/it's.txt"),
+        ]);
+
+        assert!(cmd.starts_with(
+            "let g:gnvim_dropped_files = \
+            ['/tmp/foo bar.txt', '/tmp/it''s.txt'] | if"
+        ));
+    }
+}
@@ -3,6 +3,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::nvim_bridge;
+use crate::nvim_bridge::WildmenuLayout;
 use crate::nvim_gio::GioNeovim;
 use crate::ui::color::{Color, HlDefs, HlGroup};
 use crate::ui::common::spawn_local;
@@ -18,7 +19,14 @@ struct State {
 pub struct Wildmenu {
     css_provider: gtk::CssProvider,
     frame: gtk::Frame,
+    /// Container for the vertical layout.
+    scrolled_list: gtk::ScrolledWindow,
     list: gtk::ListBox,
+    /// Container for the horizontal layout.
+    scrolled_row: gtk::ScrolledWindow,
+    row: gtk::Box,
+
+    layout: WildmenuLayout,
 
     state: Rc<RefCell<State>>,
 }
@@ -42,6 +50,15 @@ impl Wildmenu {
 
         frame.add(&scrolledwindow);
 
+        let row = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        let scrolled_row = gtk::ScrolledWindow::new(
+            None::<&gtk::Adjustment>,
+            None::<&gtk::Adjustment>,
+        );
+        scrolled_row
+            .set_policy(gtk::PolicyType::Automatic, gtk::PolicyType::Never);
+        scrolled_row.add(&row);
+
         let frame_weak = frame.downgrade();
         // Make sure our container grows to certain height.
         list.connect_size_allocate(clone!(frame_weak => move |list, _| {
@@ -85,13 +102,41 @@ impl Wildmenu {
 
         Wildmenu {
             css_provider,
+            scrolled_list: scrolledwindow,
             list,
+            scrolled_row,
+            row,
             frame,
 
+            layout: WildmenuLayout::Vertical,
+
             state,
         }
     }
 
+    pub fn set_layout(&mut self, layout: WildmenuLayout) {
+        if self.layout == layout {
+            return;
+        }
+
+        self.clear();
+
+        match layout {
+            WildmenuLayout::Vertical => {
+                self.frame.remove(&self.scrolled_row);
+                self.frame.add(&self.scrolled_list);
+            }
+            WildmenuLayout::Horizontal => {
+                self.frame.remove(&self.scrolled_list);
+                self.frame.add(&self.scrolled_row);
+                // Reset the height set for the vertical list.
+                self.frame.set_size_request(-1, -1);
+            }
+        }
+
+        self.layout = layout;
+    }
+
     pub fn widget(&self) -> gtk::Widget {
         self.frame.clone().upcast()
     }
@@ -109,11 +154,27 @@ impl Wildmenu {
         while let Some(item) = children.pop() {
             self.list.remove(&item);
         }
+
+        let mut children = self.row.get_children();
+        while let Some(item) = children.pop() {
+            self.row.remove(&item);
+        }
     }
 
     pub fn set_items(&mut self, items: &[nvim_bridge::CompletionItem]) {
         self.clear();
 
+        if self.layout == WildmenuLayout::Horizontal {
+            for item in items {
+                let label = gtk::Label::new(Some(item.word.as_str()));
+                add_css_provider!(&self.css_provider, label);
+                self.row.pack_start(&label, false, false, 0);
+            }
+
+            self.row.show_all();
+            return;
+        }
+
         for item in items {
             let label = gtk::Label::new(Some(item.word.as_str()));
             label.set_halign(gtk::Align::Start);
@@ -132,6 +193,11 @@ impl Wildmenu {
     pub fn select(&mut self, item_num: i32) {
         self.state.borrow_mut().selected = item_num;
 
+        if self.layout == WildmenuLayout::Horizontal {
+            self.select_horizontal(item_num);
+            return;
+        }
+
         if item_num < 0 {
             self.list.unselect_all();
         } else if let Some(row) = self.list.get_row_at_index(item_num) {
@@ -140,6 +206,26 @@ impl Wildmenu {
         }
     }
 
+    fn select_horizontal(&self, item_num: i32) {
+        for (i, label) in self.row.get_children().iter().enumerate() {
+            let ctx = label.get_style_context();
+            if i as i32 == item_num {
+                ctx.add_class("selected");
+
+                // Scroll the selected item into view.
+                if let Some(adj) = self.scrolled_row.get_hadjustment() {
+                    let alloc = label.get_allocation();
+                    adj.clamp_page(
+                        alloc.x.into(),
+                        (alloc.x + alloc.width).into(),
+                    );
+                }
+            } else {
+                ctx.remove_class("selected");
+            }
+        }
+    }
+
     pub fn set_colors(&self, hl_defs: &HlDefs) {
         let color = hl_defs.get_hl_group(&HlGroup::Wildmenu);
         let color_sel = hl_defs.get_hl_group(&HlGroup::WildmenuSel);
@@ -185,6 +271,17 @@ impl Wildmenu {
             GtkListBoxRow:selected, GtkListBoxRow:selected > GtkLabel {{
                 color: #{sel_fg};
                 background: #{sel_bg};
+            }}
+
+            GtkBox > GtkLabel {{
+                padding: 6px;
+                color: #{fg};
+                background-color: #{bg};
+            }}
+
+            GtkBox > GtkLabel.selected {{
+                color: #{sel_fg};
+                background: #{sel_bg};
             }}",
             fg = fg.to_hex(),
             bg = bg.to_hex(),
@@ -217,6 +314,17 @@ impl Wildmenu {
             row:selected, row:selected > label {{
                 color: #{sel_fg};
                 background: #{sel_bg};
+            }}
+
+            box > label {{
+                padding: 6px;
+                color: #{fg};
+                background-color: #{bg};
+            }}
+
+            box > label.selected {{
+                color: #{sel_fg};
+                background: #{sel_bg};
             }}",
             fg = fg.to_hex(),
             bg = bg.to_hex(),
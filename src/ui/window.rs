@@ -69,6 +69,10 @@ impl MsgWindow {
 pub struct Window {
     fixed: gtk::Fixed,
     frame: gtk::Frame,
+    /// Header pinned to the top of the window, drawn over the grid.
+    winbar: gtk::Label,
+    /// If the winbar has any content to show.
+    winbar_shown: bool,
 
     external_win: Option<gtk::Window>,
 
@@ -93,13 +97,23 @@ impl Window {
         let widget = grid.widget();
         frame.add(&widget);
 
+        // Put after the frame so it is drawn on top of the grid.
+        let winbar = gtk::Label::new(None);
+        winbar.set_xalign(0.0);
+        winbar.set_ellipsize(pango::EllipsizeMode::End);
+        // Only shown when there is some content.
+        winbar.set_no_show_all(true);
+        fixed.put(&winbar, 0, 0);
+
         if let Some(css_provider) = css_provider {
-            add_css_provider!(&css_provider, frame);
+            add_css_provider!(&css_provider, frame, winbar);
         }
 
         Self {
             fixed,
             frame,
+            winbar,
+            winbar_shown: false,
             external_win: None,
             grid_id: grid.id,
             nvim_win: win,
@@ -111,13 +125,39 @@ impl Window {
     pub fn set_parent(&mut self, fixed: gtk::Fixed) {
         if self.fixed != fixed {
             self.fixed.remove(&self.frame);
+            self.fixed.remove(&self.winbar);
             self.fixed = fixed;
             self.fixed.put(&self.frame, 0, 0);
+            self.fixed.put(&self.winbar, 0, 0);
+            self.move_winbar();
         }
     }
 
     pub fn resize(&self, size: (i32, i32)) {
         self.frame.set_size_request(size.0, size.1);
+        self.winbar.set_size_request(size.0, -1);
+    }
+
+    /// Sets the content of the winbar. `None` hides the winbar.
+    pub fn set_winbar(&mut self, markup: Option<&str>) {
+        self.winbar_shown = markup.is_some();
+        self.winbar.set_markup(markup.unwrap_or(""));
+        self.update_winbar_visibility();
+    }
+
+    fn move_winbar(&self) {
+        self.fixed.move_(
+            &self.winbar,
+            self.x.floor() as i32,
+            self.y.floor() as i32,
+        );
+    }
+
+    fn update_winbar_visibility(&self) {
+        let visible = self.winbar_shown
+            && self.external_win.is_none()
+            && self.frame.is_visible();
+        self.winbar.set_visible(visible);
     }
 
     pub fn set_external(&mut self, parent: &gtk::Window, size: (i32, i32)) {
@@ -141,6 +181,7 @@ impl Window {
         win.show_all();
 
         self.external_win = Some(win);
+        self.update_winbar_visibility();
     }
 
     pub fn set_position(&mut self, x: f64, y: f64, w: f64, h: f64) {
@@ -148,6 +189,10 @@ impl Window {
             win.remove(&self.frame);
             self.fixed.add(&self.frame);
             win.close();
+
+            // Keep the winbar on top of the frame.
+            self.fixed.remove(&self.winbar);
+            self.fixed.put(&self.winbar, 0, 0);
         }
 
         self.x = x;
@@ -157,14 +202,20 @@ impl Window {
 
         self.frame
             .set_size_request(w.ceil() as i32, h.ceil() as i32);
+
+        self.move_winbar();
+        self.winbar.set_size_request(w.ceil() as i32, -1);
+        self.update_winbar_visibility();
     }
 
     pub fn show(&self) {
         self.frame.show_all();
+        self.update_winbar_visibility();
     }
 
     pub fn hide(&self) {
         self.frame.hide();
+        self.update_winbar_visibility();
     }
 }
 
@@ -177,6 +228,7 @@ impl Drop for Window {
         }
 
         self.fixed.remove(&self.frame);
+        self.fixed.remove(&self.winbar);
 
         if let Some(ref win) = self.external_win {
             win.close();